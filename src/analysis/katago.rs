@@ -0,0 +1,669 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command as Process, Stdio},
+};
+
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    go::{
+        any_game::AnyGame,
+        board::FlexibleBoard,
+        coordinate::FlexibleCoordinate,
+        coordinate_notation::{CoordinateError, CoordinateNotation},
+        game::Game,
+        player::Player,
+        playermove::{Move, PlaceStoneMove},
+        rules::Rules,
+    },
+    gtp::{format_color, parse_color},
+    parser::gsf::ParsedGame,
+};
+
+/// A query for KataGo's JSON analysis engine, one line of its input.
+///
+/// Points are written as GTP vertices, so boards up to 25x25 are supported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisQuery {
+    pub id: String,
+    pub width: u16,
+    pub height: u16,
+    pub rules: Rules,
+    pub komi: f64,
+    /// Stones on the board before the first move.
+    pub initial_stones: Vec<(Player, FlexibleCoordinate)>,
+    /// Player to make the first move, None lets KataGo decide.
+    pub initial_player: Option<Player>,
+    /// The moves after the initial stones, None is a pass.
+    pub moves: Vec<(Player, Option<FlexibleCoordinate>)>,
+    /// Positions to analyse, turn 0 is the position before the first move.
+    pub analyze_turns: Vec<usize>,
+    /// Overrides `maxVisits` of the KataGo config.
+    pub max_visits: Option<u32>,
+    pub include_ownership: bool,
+    pub include_policy: bool,
+}
+
+impl AnalysisQuery {
+    /// An empty board to analyse with the given settings, analysing turn 0.
+    pub fn new(id: &str, board_size: (u16, u16), rules: Rules, komi: f64) -> Self {
+        Self {
+            id: id.to_string(),
+            width: board_size.0,
+            height: board_size.1,
+            rules,
+            komi,
+            initial_stones: vec![],
+            initial_player: None,
+            moves: vec![],
+            analyze_turns: vec![0],
+            max_visits: None,
+            include_ownership: false,
+            include_policy: false,
+        }
+    }
+
+    /// Analyses the current position of the game.
+    ///
+    /// A game that did not start from an empty board is sent as its current position without
+    /// the moves that lead to it, since those can not be replayed.
+    pub fn from_game<TBoard: FlexibleBoard>(
+        id: &str,
+        game: &Game<TBoard>,
+        rules: Rules,
+        komi: f64,
+    ) -> Result<Self, AnalysisError> {
+        let board = game.get_board();
+        let size = board.get_size();
+        let mut query = Self::new(id, size, rules, komi);
+
+        let mut replayed = AnyGame::new(size);
+        let replays = game
+            .get_history()
+            .iter()
+            .all(|m| replayed.make_move(m).is_ok())
+            && points(size).all(|x| replayed.get_player_at(&x) == board.get_player_at(&x));
+        if replays {
+            query.add_moves(game.get_history())?;
+        } else {
+            query.initial_stones = points(size)
+                .filter_map(|x| board.get_player_at(&x).map(|player| (player, x)))
+                .collect();
+            query.initial_player = Some(game.get_current_player());
+        }
+        query.analyze_turns = vec![query.moves.len()];
+        Ok(query)
+    }
+
+    /// Analyses the final position of the main variation, with the rules and komi of the game.
+    /// Unknown rules fall back to the default ones.
+    pub fn from_parsed_game(id: &str, game: &ParsedGame) -> Result<Self, AnalysisError> {
+        let rules: Rules = game
+            .rules
+            .as_deref()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or_default();
+        let komi = game.komi.unwrap_or(rules.default_komi());
+        let mut query = Self::new(id, (game.width, game.height), rules, komi);
+        query.add_moves(&game.moves)?;
+        query.analyze_turns = vec![query.moves.len()];
+        Ok(query)
+    }
+
+    /// Setup moves before the first stone become the initial stones, later ones can not be
+    /// described to KataGo.
+    fn add_moves(&mut self, moves: &[Move]) -> Result<(), AnalysisError> {
+        let mut stones = HashMap::new();
+        for m in moves {
+            match m {
+                Move::Setup(setup) if self.moves.is_empty() => {
+                    for coord in setup.clear.iter() {
+                        stones.remove(coord);
+                    }
+                    for coord in setup.add_black.iter() {
+                        stones.insert(*coord, Player::Black);
+                    }
+                    for coord in setup.add_white.iter() {
+                        stones.insert(*coord, Player::White);
+                    }
+                    if setup.player_to_move.is_some() {
+                        self.initial_player = setup.player_to_move;
+                    }
+                }
+                Move::Setup(_) => return Err(AnalysisError::UnsupportedSetup),
+                Move::PlaceStone(PlaceStoneMove { player, coord }) => {
+                    self.moves.push((*player, Some(*coord)))
+                }
+                Move::Skip { player } => self.moves.push((*player, None)),
+            }
+        }
+
+        let mut stones: Vec<_> = stones.into_iter().map(|(x, player)| (player, x)).collect();
+        stones.sort_by_key(|(_, x)| (x.y, x.x));
+        self.initial_stones = stones;
+        Ok(())
+    }
+
+    /// The query as the single line of JSON KataGo reads.
+    pub fn to_json(&self) -> Result<String, AnalysisError> {
+        let size = (self.width, self.height);
+        let stone = |player: Player, coord: Option<FlexibleCoordinate>| {
+            let vertex = match coord {
+                Some(coord) => CoordinateNotation::Gtp.format(&coord, size)?,
+                None => "pass".to_string(),
+            };
+            Ok::<_, AnalysisError>(json!([format_color(player), vertex]))
+        };
+
+        let mut query = json!({
+            "id": self.id,
+            "boardXSize": self.width,
+            "boardYSize": self.height,
+            "rules": katago_rules(self.rules),
+            "komi": self.komi,
+            "initialStones": self
+                .initial_stones
+                .iter()
+                .map(|(player, coord)| stone(*player, Some(*coord)))
+                .collect::<Result<Vec<_>, _>>()?,
+            "moves": self
+                .moves
+                .iter()
+                .map(|(player, coord)| stone(*player, *coord))
+                .collect::<Result<Vec<_>, _>>()?,
+            "analyzeTurns": self.analyze_turns,
+            "includeOwnership": self.include_ownership,
+            "includePolicy": self.include_policy,
+        });
+        if let Some(player) = self.initial_player {
+            query["initialPlayer"] = json!(format_color(player));
+        }
+        if let Some(visits) = self.max_visits {
+            query["maxVisits"] = json!(visits);
+        }
+        Ok(query.to_string())
+    }
+}
+
+fn points(size: (u16, u16)) -> impl Iterator<Item = FlexibleCoordinate> {
+    (0..size.1).flat_map(move |y| (0..size.0).map(move |x| FlexibleCoordinate { x, y }))
+}
+
+/// The name KataGo uses for a ruleset.
+fn katago_rules(rules: Rules) -> &'static str {
+    match rules {
+        Rules::Chinese => "chinese",
+        Rules::Japanese => "japanese",
+        Rules::Aga => "aga",
+        Rules::NewZealand => "new-zealand",
+        Rules::TrompTaylor => "tromp-taylor",
+    }
+}
+
+/// Whose point of view KataGo reports winrates, score leads and ownership from, the
+/// `reportAnalysisWinratesAs` setting of its config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Perspective {
+    #[default]
+    Black,
+    White,
+    SideToMove,
+}
+
+/// The analysis of one turn.
+///
+/// Winrates, score leads and ownership are converted to black's point of view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisResponse {
+    pub id: String,
+    pub turn_number: usize,
+    /// True for the intermediate results KataGo sends with `reportDuringSearchEvery`.
+    pub is_during_search: bool,
+    /// Candidate moves, best first.
+    pub move_infos: Vec<MoveInfo>,
+    pub root_info: RootInfo,
+    /// From -1 for white to 1 for black for every point, when the query asked for it.
+    pub ownership: Option<PointValues>,
+    pub policy: Option<Policy>,
+}
+
+/// A candidate move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveInfo {
+    /// None is a pass.
+    pub coord: Option<FlexibleCoordinate>,
+    pub visits: u32,
+    pub winrate: f64,
+    pub score_lead: f64,
+    /// Policy of the neural net for the move.
+    pub prior: f64,
+    /// Rank of the move, 0 is the best.
+    pub order: u32,
+    /// The expected continuation, starting with this move.
+    pub pv: Vec<Option<FlexibleCoordinate>>,
+}
+
+/// The evaluation of the position itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootInfo {
+    pub current_player: Player,
+    pub visits: u32,
+    pub winrate: f64,
+    pub score_lead: f64,
+}
+
+/// One value for every point of the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointValues {
+    pub width: u16,
+    pub height: u16,
+    /// In reading order from the top left.
+    pub values: Vec<f64>,
+}
+
+impl PointValues {
+    pub fn get(&self, coord: &FlexibleCoordinate) -> Option<f64> {
+        if coord.x >= self.width || coord.y >= self.height {
+            return None;
+        }
+        self.values
+            .get(coord.y as usize * self.width as usize + coord.x as usize)
+            .copied()
+    }
+}
+
+/// Policy of the neural net for the position, negative for illegal moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    pub points: PointValues,
+    pub pass: f64,
+}
+
+impl AnalysisResponse {
+    /// Parses a response to a query on a board of `board_size`, see [`Perspective`].
+    pub fn parse(
+        json: &str,
+        board_size: (u16, u16),
+        perspective: Perspective,
+    ) -> Result<Self, AnalysisError> {
+        let root: Value = serde_json::from_str(json)?;
+        if let Some(message) = root["error"].as_str() {
+            return Err(AnalysisError::Engine {
+                id: root["id"].as_str().map(|x| x.to_string()),
+                message: message.to_string(),
+            });
+        }
+        let invalid = || AnalysisError::InvalidResponse(json.to_string());
+        let number = |value: &Value| value.as_f64().ok_or_else(invalid);
+        let count = |value: &Value| {
+            value
+                .as_u64()
+                .and_then(|x| u32::try_from(x).ok())
+                .ok_or_else(invalid)
+        };
+        let vertex = |value: &Value| -> Result<Option<FlexibleCoordinate>, AnalysisError> {
+            match value.as_str().ok_or_else(invalid)? {
+                text if text.eq_ignore_ascii_case("pass") => Ok(None),
+                text => Ok(Some(CoordinateNotation::Gtp.parse(text, board_size)?)),
+            }
+        };
+
+        let info = &root["rootInfo"];
+        let current_player = info["currentPlayer"]
+            .as_str()
+            .and_then(|x| parse_color(x).ok())
+            .ok_or_else(invalid)?;
+        // Multiplying by this turns values into black's point of view.
+        let sign = match (perspective, current_player) {
+            (Perspective::White, _) | (Perspective::SideToMove, Player::White) => -1.0,
+            _ => 1.0,
+        };
+        let winrate = |value: &Value| number(value).map(|x| if sign < 0.0 { 1.0 - x } else { x });
+
+        let root_info = RootInfo {
+            current_player,
+            visits: count(&info["visits"])?,
+            winrate: winrate(&info["winrate"])?,
+            score_lead: sign * number(&info["scoreLead"])?,
+        };
+        let move_infos = root["moveInfos"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|x| {
+                Ok(MoveInfo {
+                    coord: vertex(&x["move"])?,
+                    visits: count(&x["visits"])?,
+                    winrate: winrate(&x["winrate"])?,
+                    score_lead: sign * number(&x["scoreLead"])?,
+                    prior: number(&x["prior"])?,
+                    order: count(&x["order"])?,
+                    pv: x["pv"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(vertex)
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<_, AnalysisError>>()?;
+
+        let (width, height) = board_size;
+        let points = width as usize * height as usize;
+        let values = |key: &str, len: usize| match &root[key] {
+            Value::Null => Ok(None),
+            Value::Array(values) if values.len() == len => values
+                .iter()
+                .map(number)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Some),
+            _ => Err(invalid()),
+        };
+        let ownership = values("ownership", points)?.map(|values| PointValues {
+            width,
+            height,
+            values: values.into_iter().map(|x| sign * x).collect(),
+        });
+        let policy = values("policy", points + 1)?.map(|mut values| {
+            let pass = values.pop().expect("Checked the length");
+            Policy {
+                points: PointValues {
+                    width,
+                    height,
+                    values,
+                },
+                pass,
+            }
+        });
+
+        Ok(Self {
+            id: root["id"].as_str().ok_or_else(invalid)?.to_string(),
+            turn_number: count(&root["turnNumber"])? as usize,
+            is_during_search: root["isDuringSearch"].as_bool().unwrap_or(false),
+            move_infos,
+            root_info,
+            ownership,
+            policy,
+        })
+    }
+
+    /// The candidate KataGo likes best.
+    pub fn best_move(&self) -> Option<&MoveInfo> {
+        self.move_infos.iter().min_by_key(|x| x.order)
+    }
+}
+
+/// A client for KataGo's analysis engine, started with `katago analysis -config <file> -model
+/// <file>`.
+pub struct AnalysisClient<TReader: BufRead, TWriter: Write> {
+    reader: TReader,
+    writer: TWriter,
+    child: Option<Child>,
+    perspective: Perspective,
+    /// Board sizes of the queries that were sent, to parse their responses.
+    sizes: HashMap<String, (u16, u16)>,
+}
+
+/// A client for KataGo running as a subprocess.
+pub type KataGoProcess = AnalysisClient<BufReader<ChildStdout>, ChildStdin>;
+
+impl KataGoProcess {
+    /// Starts the engine with stderr passed through, where KataGo logs its progress.
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[S]) -> Result<Self, AnalysisError> {
+        let mut child = Process::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let writer = child.stdin.take().expect("Stdin is piped");
+        let reader = BufReader::new(child.stdout.take().expect("Stdout is piped"));
+        let mut client = Self::new(reader, writer);
+        client.child = Some(child);
+        Ok(client)
+    }
+}
+
+impl<TReader: BufRead, TWriter: Write> AnalysisClient<TReader, TWriter> {
+    /// A client that writes queries to `writer` and reads the responses from `reader`.
+    pub fn new(reader: TReader, writer: TWriter) -> Self {
+        Self {
+            reader,
+            writer,
+            child: None,
+            perspective: Perspective::default(),
+            sizes: HashMap::new(),
+        }
+    }
+
+    /// Sets the `reportAnalysisWinratesAs` of the KataGo config, black by default.
+    pub fn set_perspective(&mut self, perspective: Perspective) {
+        self.perspective = perspective;
+    }
+
+    /// Sends a query without waiting, the responses come from [`AnalysisClient::read`].
+    pub fn send(&mut self, query: &AnalysisQuery) -> Result<(), AnalysisError> {
+        writeln!(self.writer, "{}", query.to_json()?)?;
+        self.writer.flush()?;
+        self.sizes
+            .insert(query.id.clone(), (query.width, query.height));
+        Ok(())
+    }
+
+    /// The next response of any query, warnings are skipped.
+    pub fn read(&mut self) -> Result<AnalysisResponse, AnalysisError> {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(AnalysisError::Closed);
+            }
+            let value: Value = serde_json::from_str(&line)?;
+            if value.get("warning").is_some() {
+                continue;
+            }
+            let size = value["id"]
+                .as_str()
+                .and_then(|id| self.sizes.get(id))
+                .copied();
+            return match size {
+                Some(size) => AnalysisResponse::parse(&line, size, self.perspective),
+                // Errors about queries that could not be parsed have no known id.
+                None => match AnalysisResponse::parse(&line, (0, 0), self.perspective) {
+                    Err(error @ AnalysisError::Engine { .. }) => Err(error),
+                    _ => Err(AnalysisError::InvalidResponse(line)),
+                },
+            };
+        }
+    }
+
+    /// Sends a query and waits for the final analysis of every turn it asks for, ordered by
+    /// turn. Responses to other queries are dropped.
+    pub fn analyze(
+        &mut self,
+        query: &AnalysisQuery,
+    ) -> Result<Vec<AnalysisResponse>, AnalysisError> {
+        self.send(query)?;
+        let mut responses: Vec<AnalysisResponse> = vec![];
+        while responses.len() < query.analyze_turns.len() {
+            let response = self.read()?;
+            if response.id == query.id && !response.is_during_search {
+                responses.push(response);
+            }
+        }
+        self.sizes.remove(&query.id);
+        responses.sort_by_key(|x| x.turn_number);
+        Ok(responses)
+    }
+}
+
+impl<TReader: BufRead, TWriter: Write> Drop for AnalysisClient<TReader, TWriter> {
+    /// Stops a subprocess, KataGo does not exit on its own while stdin is open.
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnalysisError {
+    #[error("Could not talk to the engine")]
+    Io(#[from] io::Error),
+    #[error("The engine closed the connection")]
+    Closed,
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The engine answered with an error: {message}")]
+    Engine { id: Option<String>, message: String },
+    #[error("Unexpected response '{0}'")]
+    InvalidResponse(String),
+    #[error(transparent)]
+    Coordinate(#[from] CoordinateError),
+    #[error("Setup moves after the first move can not be analysed")]
+    UnsupportedSetup,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::{
+        bitmask_board::BitMaskBoard, coordinate_set::CoordinateSet,
+        dynamic_bitmask::DynamicBitMask, playermove::SetupMove,
+    };
+
+    fn place(player: Player, x: u16, y: u16) -> Move {
+        Move::PlaceStone(PlaceStoneMove {
+            player,
+            coord: FlexibleCoordinate { x, y },
+        })
+    }
+
+    #[test]
+    fn given_parsed_game_with_handicap_when_query_is_built_then_it_should_use_initial_stones() {
+        // Given
+        let game = ParsedGame {
+            width: 9,
+            height: 9,
+            komi: Some(0.5),
+            rules: Some("Japanese".to_string()),
+            moves: vec![
+                Move::Setup(SetupMove {
+                    add_black: CoordinateSet::set(&[(6, 2), (2, 6)]),
+                    add_white: CoordinateSet::new(vec![]),
+                    clear: CoordinateSet::new(vec![]),
+                    player_to_move: Some(Player::White),
+                }),
+                place(Player::White, 4, 4),
+                Move::Skip {
+                    player: Player::Black,
+                },
+            ],
+            ..Default::default()
+        };
+
+        // When
+        let mut query = AnalysisQuery::from_parsed_game("review", &game).expect("Expected a query");
+        query.analyze_turns = vec![0, 1, 2];
+        query.max_visits = Some(50);
+        let json: Value = serde_json::from_str(&query.to_json().expect("Expected JSON"))
+            .expect("Expected valid JSON");
+
+        // Then
+        assert_eq!(
+            json!({
+                "id": "review",
+                "boardXSize": 9,
+                "boardYSize": 9,
+                "rules": "japanese",
+                "komi": 0.5,
+                "initialStones": [["B", "G7"], ["B", "C3"]],
+                "initialPlayer": "W",
+                "moves": [["W", "E5"], ["B", "pass"]],
+                "analyzeTurns": [0, 1, 2],
+                "maxVisits": 50,
+                "includeOwnership": false,
+                "includePolicy": false,
+            }),
+            json
+        );
+    }
+
+    #[test]
+    fn given_response_from_side_to_move_when_parsed_then_values_should_be_for_black() {
+        // Given
+        let json = r#"{"id":"q","isDuringSearch":false,"turnNumber":1,
+            "moveInfos":[
+                {"move":"pass","visits":3,"winrate":0.4,"scoreLead":-1.5,"prior":0.1,"order":1,"pv":["pass"]},
+                {"move":"A1","visits":9,"winrate":0.75,"scoreLead":2.5,"prior":0.6,"order":0,"pv":["A1","B2"]}
+            ],
+            "rootInfo":{"currentPlayer":"W","visits":12,"winrate":0.7,"scoreLead":2.0},
+            "ownership":[1.0,-0.5,0.25,0.0],
+            "policy":[0.6,0.2,-1.0,0.1,0.1]}"#;
+
+        // When
+        let response = AnalysisResponse::parse(json, (2, 2), Perspective::SideToMove)
+            .expect("Expected a response");
+
+        // Then
+        let a1 = FlexibleCoordinate { x: 0, y: 1 };
+        let best = response.best_move().expect("Expected candidates");
+        assert_eq!(Some(a1), best.coord);
+        assert_eq!(
+            vec![Some(a1), Some(FlexibleCoordinate { x: 1, y: 0 })],
+            best.pv
+        );
+        assert_eq!(0.25, best.winrate);
+        assert_eq!(-2.5, best.score_lead);
+        assert_eq!(Player::White, response.root_info.current_player);
+        assert!((response.root_info.winrate - 0.3).abs() < 1e-9);
+        let ownership = response.ownership.expect("Expected ownership");
+        assert_eq!(Some(-0.25), ownership.get(&a1));
+        let policy = response.policy.expect("Expected policy");
+        assert_eq!(Some(-1.0), policy.points.get(&a1));
+        assert_eq!(0.1, policy.pass);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_stub_engine_when_game_is_analysed_then_every_turn_should_be_answered() {
+        // Given
+        let script = r#"read -r line
+printf '%s\n' '{"warning":"unused field","field":"foo"}'
+printf '%s\n' '{"id":"g","isDuringSearch":false,"turnNumber":1,"moveInfos":[],"rootInfo":{"currentPlayer":"W","visits":1,"winrate":0.5,"scoreLead":0.5}}'
+printf '%s\n' '{"id":"g","isDuringSearch":true,"turnNumber":0,"moveInfos":[],"rootInfo":{"currentPlayer":"B","visits":1,"winrate":0.5,"scoreLead":0.0}}'
+printf '%s\n' '{"id":"g","isDuringSearch":false,"turnNumber":0,"moveInfos":[],"rootInfo":{"currentPlayer":"B","visits":2,"winrate":0.6,"scoreLead":7.0}}'
+read -r line
+printf '%s\n' '{"id":"h","error":"Could not parse query"}'
+"#;
+        let mut client = KataGoProcess::spawn("sh", &["-c", script]).expect("Expected sh to start");
+        client.set_perspective(Perspective::SideToMove);
+        let mut game = Game::new(BitMaskBoard::new(|| DynamicBitMask::init((9, 9))));
+        game.make_move(&place(Player::Black, 2, 2))
+            .expect("Expected legal move");
+        let mut query =
+            AnalysisQuery::from_game("g", &game, Rules::Chinese, 7.5).expect("Expected a query");
+        query.analyze_turns = vec![0, 1];
+
+        // When
+        let responses = client.analyze(&query).expect("Expected responses");
+        let error = client.analyze(&AnalysisQuery::new("h", (9, 9), Rules::Chinese, 7.5));
+
+        // Then
+        let turns: Vec<_> = responses.iter().map(|x| x.turn_number).collect();
+        assert_eq!(vec![0, 1], turns);
+        assert_eq!(7.0, responses[0].root_info.score_lead);
+        assert_eq!(-0.5, responses[1].root_info.score_lead);
+        assert_eq!(
+            vec![(Player::Black, Some(FlexibleCoordinate { x: 2, y: 2 }))],
+            query.moves
+        );
+        assert!(matches!(
+            error,
+            Err(AnalysisError::Engine { id: Some(id), .. }) if id == "h"
+        ));
+    }
+}
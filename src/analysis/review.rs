@@ -0,0 +1,578 @@
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    fmt::Display,
+    io::{BufRead, Write},
+};
+
+use thiserror::Error;
+
+use crate::{
+    analysis::katago::{AnalysisClient, AnalysisError, AnalysisQuery},
+    go::{
+        any_game::AnyGame,
+        coordinate::FlexibleCoordinate,
+        coordinate_notation::CoordinateNotation,
+        game::MoveError,
+        player::Player,
+        playermove::{Move, PlaceStoneMove},
+        rules::Rules,
+    },
+    parser::gsf::{
+        ParsedGame, Variation,
+        properties::{Emphasis, MoveAnnotation, NodeProperties},
+    },
+};
+
+/// How a position looks, from black's point of view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// Points black is ahead by, negative when white is ahead.
+    pub score_lead: f64,
+    /// Chance black wins, None for evaluators that only count.
+    pub winrate: Option<f64>,
+    /// The best continuation, empty when the evaluator does not suggest moves.
+    pub pv: Vec<Option<FlexibleCoordinate>>,
+}
+
+/// Evaluates every position of a game.
+pub trait Evaluator {
+    /// One evaluation for every turn, the first one for the position before the first move.
+    /// Setup moves before the first move belong to that position.
+    fn evaluate(&mut self, game: &ParsedGame) -> Result<Vec<Evaluation>, ReviewError>;
+}
+
+impl<TReader: BufRead, TWriter: Write> Evaluator for AnalysisClient<TReader, TWriter> {
+    fn evaluate(&mut self, game: &ParsedGame) -> Result<Vec<Evaluation>, ReviewError> {
+        let mut query = AnalysisQuery::from_parsed_game("review", game)?;
+        query.analyze_turns = (0..=query.moves.len()).collect();
+        Ok(self
+            .analyze(&query)?
+            .into_iter()
+            .map(|response| Evaluation {
+                score_lead: response.root_info.score_lead,
+                winrate: Some(response.root_info.winrate),
+                pv: response
+                    .best_move()
+                    .map(|x| x.pv.clone())
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+/// Estimates the score without an engine, giving every empty point to the colour with the
+/// closest stone. Dead stones count as alive, so it is only a rough guess.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScoreEstimator;
+
+impl Evaluator for ScoreEstimator {
+    fn evaluate(&mut self, game: &ParsedGame) -> Result<Vec<Evaluation>, ReviewError> {
+        let komi = komi(game);
+        let mut replay = AnyGame::new((game.width, game.height));
+        let mut evaluations = vec![];
+        for m in &game.moves {
+            let is_turn = !matches!(m, Move::Setup(_));
+            if is_turn && evaluations.is_empty() {
+                evaluations.push(estimate(&replay, komi));
+            }
+            replay.make_move(m)?;
+            if is_turn {
+                evaluations.push(estimate(&replay, komi));
+            }
+        }
+        if evaluations.is_empty() {
+            evaluations.push(estimate(&replay, komi));
+        }
+        Ok(evaluations)
+    }
+}
+
+fn komi(game: &ParsedGame) -> f64 {
+    let rules: Rules = game
+        .rules
+        .as_deref()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or_default();
+    game.komi.unwrap_or(rules.default_komi())
+}
+
+fn estimate(game: &AnyGame, komi: f64) -> Evaluation {
+    let (width, height) = game.get_size();
+    let index = |coord: &FlexibleCoordinate| coord.y as usize * width as usize + coord.x as usize;
+    let points: Vec<_> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| FlexibleCoordinate { x, y }))
+        .collect();
+
+    // Distance of every point to the closest stone of a colour, through empty points.
+    let distances = |player: Player| {
+        let mut distance = vec![u32::MAX; points.len()];
+        let mut queue = VecDeque::new();
+        for coord in points
+            .iter()
+            .filter(|x| game.get_player_at(x) == Some(player))
+        {
+            distance[index(coord)] = 0;
+            queue.push_back(*coord);
+        }
+        while let Some(coord) = queue.pop_front() {
+            let next = distance[index(&coord)] + 1;
+            let (x, y) = (coord.x, coord.y);
+            let neighbours = [
+                (y > 0).then(|| FlexibleCoordinate { x, y: y - 1 }),
+                (x > 0).then(|| FlexibleCoordinate { x: x - 1, y }),
+                (x + 1 < width).then_some(FlexibleCoordinate { x: x + 1, y }),
+                (y + 1 < height).then_some(FlexibleCoordinate { x, y: y + 1 }),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if game.get_player_at(&neighbour).is_none() && distance[index(&neighbour)] > next {
+                    distance[index(&neighbour)] = next;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        distance
+    };
+    let black = distances(Player::Black);
+    let white = distances(Player::White);
+
+    let score: i32 = points
+        .iter()
+        .map(
+            |coord| match black[index(coord)].cmp(&white[index(coord)]) {
+                Ordering::Less => 1,
+                Ordering::Greater => -1,
+                Ordering::Equal => 0,
+            },
+        )
+        .sum();
+    Evaluation {
+        score_lead: score as f64 - komi,
+        winrate: None,
+        pv: vec![],
+    }
+}
+
+/// Thresholds of a review, in points lost by a move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewSettings {
+    pub mistake: f64,
+    pub blunder: f64,
+    /// Moves that do this much better than the position before them promised are good moves,
+    /// moves the evaluator did not see coming.
+    pub good_move: f64,
+    /// Moves of a suggested variation, the variation is left out at 0.
+    pub variation_length: usize,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        Self {
+            mistake: 3.0,
+            blunder: 8.0,
+            good_move: 3.0,
+            variation_length: 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveQuality {
+    Good,
+    Normal,
+    Mistake,
+    Blunder,
+}
+
+/// The review of a single move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveReview {
+    /// Index of the move in [`ParsedGame::moves`].
+    pub index: usize,
+    /// Counted from 1, setup moves are not counted.
+    pub move_number: usize,
+    pub player: Player,
+    /// None is a pass.
+    pub coord: Option<FlexibleCoordinate>,
+    /// Score lead for black after the move.
+    pub score_lead: f64,
+    /// Points the move lost for its player, negative when it gained.
+    pub loss: f64,
+    /// Chance of winning the move lost for its player, when the evaluator gives winrates.
+    pub winrate_loss: Option<f64>,
+    pub quality: MoveQuality,
+    /// What the evaluator would have played instead, starting with the best move.
+    pub best: Vec<Option<FlexibleCoordinate>>,
+}
+
+/// Summary of the moves of one player.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerStats {
+    pub moves: u32,
+    /// Sum of the losses, gains are counted as no loss.
+    pub total_loss: f64,
+    pub mistakes: u32,
+    pub blunders: u32,
+    pub good_moves: u32,
+    /// Move number of the move that lost the most, with its loss.
+    pub worst_move: Option<usize>,
+    pub worst_loss: f64,
+}
+
+impl PlayerStats {
+    pub fn average_loss(&self) -> f64 {
+        match self.moves {
+            0 => 0.0,
+            moves => self.total_loss / moves as f64,
+        }
+    }
+
+    fn add(&mut self, review: &MoveReview) {
+        self.moves += 1;
+        self.total_loss += review.loss.max(0.0);
+        match review.quality {
+            MoveQuality::Good => self.good_moves += 1,
+            MoveQuality::Normal => {}
+            MoveQuality::Mistake => self.mistakes += 1,
+            MoveQuality::Blunder => self.blunders += 1,
+        }
+        if review.loss > 0.0 && review.loss > self.worst_loss {
+            self.worst_loss = review.loss;
+            self.worst_move = Some(review.move_number);
+        }
+    }
+}
+
+impl Display for PlayerStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} moves, average loss {:.1}, {} mistakes, {} blunders, {} good moves",
+            self.moves,
+            self.average_loss(),
+            self.mistakes,
+            self.blunders,
+            self.good_moves
+        )?;
+        if let Some(worst) = self.worst_move {
+            write!(f, ", worst move {worst}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The moves of a game judged by an [`Evaluator`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub moves: Vec<MoveReview>,
+    pub black: PlayerStats,
+    pub white: PlayerStats,
+}
+
+/// Reviews the main variation of a game, a move loses the difference between the evaluations
+/// before and after it.
+pub fn review_game(
+    game: &ParsedGame,
+    evaluator: &mut dyn Evaluator,
+    settings: &ReviewSettings,
+) -> Result<Review, ReviewError> {
+    let mut turns = vec![];
+    for (index, m) in game.moves.iter().enumerate() {
+        match m {
+            Move::Setup(_) if turns.is_empty() => {}
+            Move::Setup(_) => return Err(AnalysisError::UnsupportedSetup.into()),
+            Move::PlaceStone(PlaceStoneMove { player, coord }) => {
+                turns.push((index, *player, Some(*coord)))
+            }
+            Move::Skip { player } => turns.push((index, *player, None)),
+        }
+    }
+
+    let evaluations = evaluator.evaluate(game)?;
+    if evaluations.len() != turns.len() + 1 {
+        return Err(ReviewError::EvaluationCount {
+            expected: turns.len() + 1,
+            found: evaluations.len(),
+        });
+    }
+
+    let mut review = Review {
+        moves: vec![],
+        black: PlayerStats::default(),
+        white: PlayerStats::default(),
+    };
+    for (turn, (index, player, coord)) in turns.into_iter().enumerate() {
+        let (before, after) = (&evaluations[turn], &evaluations[turn + 1]);
+        let sign = match player {
+            Player::Black => 1.0,
+            Player::White => -1.0,
+        };
+        let loss = sign * (before.score_lead - after.score_lead);
+        let quality = if loss >= settings.blunder {
+            MoveQuality::Blunder
+        } else if loss >= settings.mistake {
+            MoveQuality::Mistake
+        } else if loss <= -settings.good_move {
+            MoveQuality::Good
+        } else {
+            MoveQuality::Normal
+        };
+
+        let move_review = MoveReview {
+            index,
+            move_number: turn + 1,
+            player,
+            coord,
+            score_lead: after.score_lead,
+            loss,
+            winrate_loss: before
+                .winrate
+                .zip(after.winrate)
+                .map(|(before, after)| sign * (before - after)),
+            quality,
+            best: before.pv.clone(),
+        };
+        match player {
+            Player::Black => review.black.add(&move_review),
+            Player::White => review.white.add(&move_review),
+        }
+        review.moves.push(move_review);
+    }
+    Ok(review)
+}
+
+impl Review {
+    pub fn stats(&self, player: Player) -> &PlayerStats {
+        match player {
+            Player::Black => &self.black,
+            Player::White => &self.white,
+        }
+    }
+
+    /// A copy of the game with the review in it. Every reviewed move gets its score as `V`,
+    /// mistakes get `BM[1]`, blunders `BM[2]` and good moves `TE[1]`, each with a comment. The
+    /// best continuation is added as a variation for mistakes and blunders, and the summary of
+    /// both players goes in the comment of the root.
+    pub fn annotate(&self, game: &ParsedGame, settings: &ReviewSettings) -> ParsedGame {
+        let mut annotated = game.clone();
+        annotated
+            .properties
+            .resize(annotated.moves.len(), NodeProperties::default());
+        let size = (game.width, game.height);
+        let vertex = |coord: &Option<FlexibleCoordinate>| match coord {
+            Some(coord) => CoordinateNotation::Gtp
+                .format(coord, size)
+                .unwrap_or_else(|_| format!("({}, {})", coord.x, coord.y)),
+            None => "pass".to_string(),
+        };
+
+        for review in &self.moves {
+            let name = match review.player {
+                Player::Black => "Black",
+                Player::White => "White",
+            };
+            let (annotation, comment) = match review.quality {
+                MoveQuality::Normal => (None, None),
+                MoveQuality::Good => (
+                    Some(MoveAnnotation::Tesuji(Emphasis::Normal)),
+                    Some(format!(
+                        "Good move, {name} gains {:.1} points.",
+                        -review.loss
+                    )),
+                ),
+                MoveQuality::Mistake | MoveQuality::Blunder => {
+                    let (emphasis, label) = match review.quality {
+                        MoveQuality::Blunder => (Emphasis::Strong, "Blunder"),
+                        _ => (Emphasis::Normal, "Mistake"),
+                    };
+                    let mut comment = format!("{label}, {name} loses {:.1} points", review.loss);
+                    if let Some(winrate_loss) = review.winrate_loss {
+                        comment.push_str(&format!(" and {:.1}% winrate", winrate_loss * 100.0));
+                    }
+                    comment.push('.');
+                    if let Some(best) = review.best.first() {
+                        comment.push_str(&format!(" Best is {}.", vertex(best)));
+                    }
+                    (Some(MoveAnnotation::Bad(emphasis)), Some(comment))
+                }
+            };
+
+            annotated.properties[review.index].merge(NodeProperties {
+                comment,
+                value: Some(review.score_lead),
+                move_annotation: annotation,
+                ..Default::default()
+            });
+
+            let suggests_other_move = review
+                .best
+                .first()
+                .is_some_and(|best| *best != review.coord);
+            if matches!(review.quality, MoveQuality::Mistake | MoveQuality::Blunder)
+                && suggests_other_move
+                && settings.variation_length > 0
+            {
+                let moves: Vec<Move> = review
+                    .best
+                    .iter()
+                    .take(settings.variation_length)
+                    .enumerate()
+                    .map(|(i, coord)| {
+                        let player = if i % 2 == 0 {
+                            review.player
+                        } else {
+                            !review.player
+                        };
+                        match coord {
+                            Some(coord) => Move::PlaceStone(PlaceStoneMove {
+                                player,
+                                coord: *coord,
+                            }),
+                            None => Move::Skip { player },
+                        }
+                    })
+                    .collect();
+                let mut properties = vec![NodeProperties::default(); moves.len()];
+                properties[0].comment = Some("Suggested variation.".to_string());
+                annotated.variations.push(Variation {
+                    move_number: review.index,
+                    moves,
+                    properties,
+                });
+            }
+        }
+
+        annotated.root_properties.merge(NodeProperties {
+            comment: Some(format!(
+                "Review\nBlack: {}\nWhite: {}",
+                self.black, self.white
+            )),
+            ..Default::default()
+        });
+        annotated
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ReviewError {
+    #[error(transparent)]
+    Analysis(#[from] AnalysisError),
+    #[error("The game has an illegal move: {0}")]
+    Move(#[from] MoveError),
+    #[error("Expected {expected} evaluations, the evaluator gave {found}")]
+    EvaluationCount { expected: usize, found: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::gsf::{parse_sgf, writer::write_sgf};
+
+    struct ScriptedEvaluator(Vec<Evaluation>);
+
+    impl Evaluator for ScriptedEvaluator {
+        fn evaluate(&mut self, _: &ParsedGame) -> Result<Vec<Evaluation>, ReviewError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn evaluation(score_lead: f64, pv: &[(u16, u16)]) -> Evaluation {
+        Evaluation {
+            score_lead,
+            winrate: None,
+            pv: pv
+                .iter()
+                .map(|(x, y)| Some(FlexibleCoordinate { x: *x, y: *y }))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn given_analysis_engine_when_game_is_reviewed_then_losses_should_be_per_player() {
+        // Given
+        let game = parse_sgf("(;GM[1]SZ[9]KM[7.5];B[ee];W[aa];B[])").expect("Expected sgf");
+        let responses = [(0, "B", 0.5, -7.0), (1, "W", 0.6, -6.5), (2, "B", 0.8, 3.5)]
+            .map(|(turn, player, winrate, lead)| {
+                format!(
+                    r#"{{"id":"review","turnNumber":{turn},"moveInfos":[{{"move":"C3","visits":5,"winrate":{winrate},"scoreLead":{lead},"prior":0.5,"order":0,"pv":["C3","G7"]}}],"rootInfo":{{"currentPlayer":"{player}","visits":10,"winrate":{winrate},"scoreLead":{lead}}}}}"#
+                )
+            })
+            .join("\n")
+            + "\n"
+            + r#"{"id":"review","turnNumber":3,"moveInfos":[],"rootInfo":{"currentPlayer":"W","visits":10,"winrate":0.9,"scoreLead":12.0}}"#
+            + "\n";
+        let mut sent = vec![];
+        let mut client = AnalysisClient::new(responses.as_bytes(), &mut sent);
+
+        // When
+        let review =
+            review_game(&game, &mut client, &ReviewSettings::default()).expect("Expected a review");
+        drop(client);
+
+        // Then
+        let losses: Vec<_> = review.moves.iter().map(|x| x.loss).collect();
+        assert_eq!(vec![-0.5, 10.0, -8.5], losses);
+        let qualities: Vec<_> = review.moves.iter().map(|x| x.quality).collect();
+        assert_eq!(
+            vec![MoveQuality::Normal, MoveQuality::Blunder, MoveQuality::Good],
+            qualities
+        );
+        assert!((review.moves[1].winrate_loss.expect("Expected winrates") - 0.2).abs() < 1e-9);
+        assert_eq!(1, review.stats(Player::White).blunders);
+        assert_eq!(Some(2), review.white.worst_move);
+        assert_eq!(0.0, review.black.average_loss());
+        assert_eq!(1, review.black.good_moves);
+        let query = String::from_utf8(sent).expect("Expected utf8");
+        assert!(query.contains(r#""analyzeTurns":[0,1,2,3]"#));
+    }
+
+    #[test]
+    fn given_blunder_when_review_is_annotated_then_sgf_should_mark_it_and_suggest_a_variation() {
+        // Given
+        let game =
+            parse_sgf("(;GM[1]SZ[9]KM[6.5];B[ee];W[aa]C[Corner];B[ce])").expect("Expected sgf");
+        let mut evaluator = ScriptedEvaluator(vec![
+            evaluation(-6.5, &[(4, 4)]),
+            evaluation(-2.0, &[(2, 2), (6, 6)]),
+            evaluation(8.0, &[(2, 6)]),
+            evaluation(7.0, &[]),
+        ]);
+        let settings = ReviewSettings::default();
+        let review = review_game(&game, &mut evaluator, &settings).expect("Expected a review");
+
+        // When
+        let annotated = review.annotate(&game, &settings);
+        let sgf = write_sgf(&annotated);
+        let reparsed = parse_sgf(&sgf).expect("Expected annotated sgf to parse");
+
+        // Then
+        assert!(sgf.contains(
+            "(;W[aa]C[Corner\nBlunder, White loses 10.0 points. Best is C7.]V[8]BM[2]\n;B[ce]V[7]\n)"
+        ));
+        assert!(sgf.contains("(;W[cc]C[Suggested variation.]\n;B[gg]\n)"));
+        assert!(sgf.contains(";B[ee]C[Good move, Black gains 4.5 points.]V[-2]TE[1]"));
+        assert!(sgf.contains("White: 1 moves, average loss 10.0, 0 mistakes, 1 blunders"));
+        assert_eq!(game.moves, reparsed.moves);
+        assert_eq!(
+            Some(MoveAnnotation::Bad(Emphasis::Strong)),
+            reparsed.properties[1].move_annotation
+        );
+    }
+
+    #[test]
+    fn given_stones_when_estimated_then_closer_points_should_count_for_each_colour() {
+        // Given
+        let game = parse_sgf("(;GM[1]SZ[5]KM[0.5]AB[aa];W[ee];B[ca])").expect("Expected sgf");
+
+        // When
+        let evaluations = ScoreEstimator
+            .evaluate(&game)
+            .expect("Expected evaluations");
+
+        // Then
+        let leads: Vec<_> = evaluations.iter().map(|x| x.score_lead).collect();
+        // 25 points for black alone, then split along the diagonal, then black gets closer.
+        assert_eq!(vec![24.5, -0.5, 5.5], leads);
+        assert!(evaluations.iter().all(|x| x.winrate.is_none()));
+    }
+}
@@ -0,0 +1,16 @@
+use std::{
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use go_lib::gtp::engine::{GtpEngine, RandomMoveGenerator};
+
+/// Speaks GTP on stdin and stdout, playing random moves.
+fn main() -> io::Result<()> {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_nanos() as u64)
+        .unwrap_or_default();
+    let mut engine = GtpEngine::new(RandomMoveGenerator::new(seed));
+    engine.run(io::stdin().lock(), io::stdout().lock())
+}
@@ -0,0 +1,119 @@
+use std::{
+    env,
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use go_lib::gtp::{
+    controller::ProcessController,
+    engine::RandomMoveGenerator,
+    referee::{Bot, EnginePlayer, MatchPlayer, MatchSettings, run_match},
+};
+
+const USAGE: &str = "Usage: gtp-match [--games N] [--size N] [--komi K] [--rules NAME] \
+[--time SECONDS] [--max-moves N] [--sgf DIR] <engine> <engine>
+
+An engine is a command line like \"gnugo --mode gtp\", or \"random\" for the built-in bot.";
+
+/// Plays two engines against each other and prints the results.
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut settings = MatchSettings::default();
+    let mut komi = None;
+    let mut engines = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{arg} needs a value\n{USAGE}"))
+        };
+        let invalid = |value: &str| format!("'{value}' is not valid for {arg}");
+        match arg.as_str() {
+            "--games" => {
+                let value = value()?;
+                settings.games = value.parse().map_err(|_| invalid(&value))?;
+            }
+            "--size" => {
+                let value = value()?;
+                // GTP vertices only go up to 25 columns.
+                settings.board_size = value
+                    .parse()
+                    .ok()
+                    .filter(|size| (2..=25).contains(size))
+                    .ok_or_else(|| invalid(&value))?;
+            }
+            "--komi" => {
+                let value = value()?;
+                komi = Some(value.parse().map_err(|_| invalid(&value))?);
+            }
+            "--rules" => {
+                let value = value()?;
+                settings.rules = value.parse().map_err(|_| invalid(&value))?;
+            }
+            "--time" => {
+                let value = value()?;
+                let seconds = value.parse().map_err(|_| invalid(&value))?;
+                let time_limit =
+                    Duration::try_from_secs_f64(seconds).map_err(|_| invalid(&value))?;
+                settings.time_limit = Some(time_limit);
+            }
+            "--max-moves" => {
+                let value = value()?;
+                settings.max_moves = value.parse().map_err(|_| invalid(&value))?;
+            }
+            "--sgf" => settings.sgf_dir = Some(PathBuf::from(value()?)),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => engines.push(arg),
+        }
+    }
+    settings.komi = komi.unwrap_or(settings.rules.default_komi());
+
+    let [first, second] = engines.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+    let mut first = player(first, 1)?;
+    let mut second = player(second, 2)?;
+
+    let summary = run_match(first.as_mut(), second.as_mut(), &settings, |record| {
+        println!(
+            "{} (B) vs {} (W): {}",
+            record.black, record.white, record.result
+        );
+    })
+    .map_err(|error| format!("Could not write the games: {error}"))?;
+    println!("{summary}");
+    Ok(())
+}
+
+fn player(command: &str, seed: u64) -> Result<Box<dyn MatchPlayer>, String> {
+    if command == "random" {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_nanos() as u64)
+            .unwrap_or_default()
+            ^ seed;
+        return Ok(Box::new(Bot::new("random", RandomMoveGenerator::new(seed))));
+    }
+
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let (program, args) = words
+        .split_first()
+        .ok_or_else(|| "An engine command is empty".to_string())?;
+    let engine = ProcessController::spawn(*program, args)
+        .and_then(EnginePlayer::new)
+        .map_err(|error| format!("Could not start '{command}': {error}"))?;
+    Ok(Box::new(engine))
+}
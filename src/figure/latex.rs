@@ -0,0 +1,388 @@
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::{
+    figure::{Figure, FigureStone},
+    go::{coordinate::FlexibleCoordinate, coordinate_notation::gtp_column_letter, player::Player},
+    parser::gsf::properties::Markup,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatexPackage {
+    /// `\usepackage{igo}`, boards up to 19x19.
+    Igo,
+    /// `\usepackage{psgo}`, boards up to 25x25.
+    Psgo,
+}
+
+impl LatexPackage {
+    fn max_size(&self) -> u16 {
+        match self {
+            LatexPackage::Igo => 19,
+            LatexPackage::Psgo => 25,
+        }
+    }
+}
+
+/// Renders a figure for the `igo` or `psgo` package, in a `figure` environment when it has a
+/// caption and in a `center` environment when it does not.
+///
+/// Both packages only draw square boards, rectangular figures are drawn as the top left of a
+/// square board that is cropped to the figure. Numbered stones can not have markup, and
+/// selections, arrows and lines are left out.
+pub fn render_latex(figure: &Figure, package: LatexPackage) -> Result<String, LatexError> {
+    let size = figure.width.max(figure.height);
+    if size > package.max_size() {
+        return Err(LatexError::UnsupportedSize {
+            package,
+            width: figure.width,
+            height: figure.height,
+        });
+    }
+
+    let mut out = String::new();
+    match &figure.caption {
+        Some(_) => out.push_str("\\begin{figure}[ht]\n\\centering\n"),
+        None => out.push_str("\\begin{center}\n"),
+    }
+
+    match package {
+        LatexPackage::Igo => render_igo(&mut out, figure, size),
+        LatexPackage::Psgo => render_psgo(&mut out, figure, size),
+    }
+
+    for note in &figure.notes {
+        let _ = writeln!(out, "\\par {}", escape_latex(&figure.note_text(note)));
+    }
+
+    match &figure.caption {
+        Some(caption) => {
+            let _ = writeln!(out, "\\caption{{{}}}", escape_latex(caption));
+            out.push_str("\\end{figure}\n");
+        }
+        None => out.push_str("\\end{center}\n"),
+    }
+    Ok(out)
+}
+
+fn render_igo(out: &mut String, figure: &Figure, size: u16) {
+    let _ = writeln!(out, "\\cleargoban\n\\gobansize{{{size}}}");
+
+    for player in [Player::Black, Player::White] {
+        let command = match player {
+            Player::Black => "\\black",
+            Player::White => "\\white",
+        };
+
+        let plain: Vec<String> = visible_stones(figure)
+            // Labels on stones are not supported by igo, those stones are drawn plain.
+            .filter(|(coord, stone)| {
+                stone.player == player
+                    && stone.number.is_none()
+                    && !matches!(symbol(figure, coord), Some(Symbol::Mark(_)))
+            })
+            .map(|(coord, _)| igo_point(&coord, size))
+            .collect();
+        if !plain.is_empty() {
+            let _ = writeln!(out, "{command}{{{}}}", plain.join(","));
+        }
+
+        for (coord, stone) in visible_stones(figure).filter(|(_, x)| x.player == player) {
+            let option = match (stone.number, symbol(figure, &coord)) {
+                (Some(number), _) => number.to_string(),
+                (None, Some(Symbol::Mark(mark))) => igo_mark(mark).to_string(),
+                _ => continue,
+            };
+            let _ = writeln!(out, "{command}[{option}]{{{}}}", igo_point(&coord, size));
+        }
+    }
+
+    for (coord, symbol) in empty_symbols(figure) {
+        let symbol = match symbol {
+            Symbol::Mark(mark) => igo_mark(mark).to_string(),
+            Symbol::Label(label) => escape_latex(&label),
+        };
+        let _ = writeln!(
+            out,
+            "\\gobansymbol{{{}}}{{{symbol}}}",
+            igo_point(&coord, size)
+        );
+    }
+
+    let region = figure.region;
+    let _ = writeln!(
+        out,
+        "\\showgoban[{},{}]",
+        igo_point(
+            &FlexibleCoordinate {
+                x: region.left,
+                y: region.bottom
+            },
+            size
+        ),
+        igo_point(
+            &FlexibleCoordinate {
+                x: region.right,
+                y: region.top
+            },
+            size
+        )
+    );
+}
+
+fn render_psgo(out: &mut String, figure: &Figure, size: u16) {
+    let region = figure.region;
+    let cropped = region.columns() < size || region.rows() < size;
+    if cropped {
+        let _ = writeln!(
+            out,
+            "\\begin{{psgopartialboard}}[{size}]{{({},{})({},{})}}",
+            region.left + 1,
+            size - region.bottom,
+            region.right + 1,
+            size - region.top
+        );
+    } else {
+        let _ = writeln!(out, "\\begin{{psgoboard}}[{size}]");
+    }
+
+    for (coord, stone) in visible_stones(figure) {
+        let color = match stone.player {
+            Player::Black => "black",
+            Player::White => "white",
+        };
+        let option = match (stone.number, symbol(figure, &coord)) {
+            (Some(number), _) => format!("[{number}]"),
+            (None, Some(Symbol::Mark(mark))) => format!("[{}]", psgo_mark(mark)),
+            (None, Some(Symbol::Label(label))) => {
+                format!("[\\marklb{{{}}}]", escape_latex(&label))
+            }
+            (None, None) => String::new(),
+        };
+        let _ = writeln!(
+            out,
+            "\\stone{option}{{{color}}}{}",
+            psgo_point(&coord, size)
+        );
+    }
+
+    for (coord, symbol) in empty_symbols(figure) {
+        let symbol = match symbol {
+            Symbol::Mark(mark) => psgo_mark(mark).to_string(),
+            Symbol::Label(label) => format!("\\marklb{{{}}}", escape_latex(&label)),
+        };
+        let _ = writeln!(out, "\\markpos{{{symbol}}}{}", psgo_point(&coord, size));
+    }
+
+    if cropped {
+        out.push_str("\\end{psgopartialboard}\n");
+    } else {
+        out.push_str("\\end{psgoboard}\n");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Triangle,
+    Square,
+    Circle,
+    Cross,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Symbol {
+    Mark(Mark),
+    Label(String),
+}
+
+/// Stones in the region in reading order.
+fn visible_stones(figure: &Figure) -> impl Iterator<Item = (FlexibleCoordinate, FigureStone)> {
+    let region = figure.region;
+    (region.top..=region.bottom)
+        .flat_map(move |y| (region.left..=region.right).map(move |x| FlexibleCoordinate { x, y }))
+        .filter_map(|coord| figure.stones.get(&coord).map(|stone| (coord, *stone)))
+}
+
+/// The first markup on a point that the packages can draw.
+fn symbol(figure: &Figure, coord: &FlexibleCoordinate) -> Option<Symbol> {
+    figure.markup.iter().find_map(|markup| match markup {
+        Markup::Triangle(c) if c == coord => Some(Symbol::Mark(Mark::Triangle)),
+        Markup::Square(c) if c == coord => Some(Symbol::Mark(Mark::Square)),
+        Markup::Circle(c) if c == coord => Some(Symbol::Mark(Mark::Circle)),
+        Markup::Cross(c) if c == coord => Some(Symbol::Mark(Mark::Cross)),
+        Markup::Label(c, label) if c == coord => Some(Symbol::Label(label.clone())),
+        _ => None,
+    })
+}
+
+/// Markup on empty points in the region in reading order.
+fn empty_symbols(figure: &Figure) -> Vec<(FlexibleCoordinate, Symbol)> {
+    let region = figure.region;
+    (region.top..=region.bottom)
+        .flat_map(|y| (region.left..=region.right).map(move |x| FlexibleCoordinate { x, y }))
+        .filter(|coord| !figure.stones.contains_key(coord))
+        .filter_map(|coord| symbol(figure, &coord).map(|symbol| (coord, symbol)))
+        .collect()
+}
+
+/// Lowercase GTP coordinates, `d4`.
+fn igo_point(coord: &FlexibleCoordinate, size: u16) -> String {
+    format!(
+        "{}{}",
+        gtp_column_letter(coord.x)
+            .expect("Checked the size")
+            .to_ascii_lowercase(),
+        size - coord.y
+    )
+}
+
+/// Column letter and row number as two arguments, `{d}{4}`.
+fn psgo_point(coord: &FlexibleCoordinate, size: u16) -> String {
+    format!(
+        "{{{}}}{{{}}}",
+        gtp_column_letter(coord.x)
+            .expect("Checked the size")
+            .to_ascii_lowercase(),
+        size - coord.y
+    )
+}
+
+fn igo_mark(mark: Mark) -> &'static str {
+    match mark {
+        Mark::Triangle => "\\igotriangle",
+        Mark::Square => "\\igosquare",
+        Mark::Circle => "\\igocircle",
+        Mark::Cross => "\\igocross",
+    }
+}
+
+fn psgo_mark(mark: Mark) -> &'static str {
+    match mark {
+        Mark::Triangle => "\\marktr",
+        Mark::Square => "\\marksq",
+        Mark::Circle => "\\markcr",
+        Mark::Cross => "\\markma",
+    }
+}
+
+fn escape_latex(text: &str) -> String {
+    let mut res = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => res.push_str("\\textbackslash{}"),
+            '~' => res.push_str("\\textasciitilde{}"),
+            '^' => res.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                res.push('\\');
+                res.push(c);
+            }
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LatexError {
+    #[error("{package:?} can not draw boards of {width}x{height}")]
+    UnsupportedSize {
+        package: LatexPackage,
+        width: u16,
+        height: u16,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{figure::Region, parser::gsf::parse_sgf};
+
+    fn figure() -> Figure {
+        let game = parse_sgf("(;GM[1]SZ[9]AB[cc]AW[dc];B[cd];W[dd];B[]TR[cc]LB[ec:a])")
+            .expect("Expected sgf to parse");
+        let mut figure = Figure::from_parsed_game(&game, 1..4).expect("Expected legal moves");
+        figure.caption = Some("Moves 1-3 & a pass".to_string());
+        figure
+    }
+
+    #[test]
+    fn given_figure_when_rendered_for_igo_then_it_should_list_stones_marks_and_notes() {
+        // Given
+        let mut figure = figure();
+        figure.crop(Region {
+            left: 0,
+            top: 0,
+            right: 5,
+            bottom: 5,
+        });
+
+        // When
+        let res = render_latex(&figure, LatexPackage::Igo).expect("Expected 9x9 to fit");
+
+        // Then
+        let expected = "\\begin{figure}[ht]
+\\centering
+\\cleargoban
+\\gobansize{9}
+\\black[\\igotriangle]{c7}
+\\black[1]{c6}
+\\white{d7}
+\\white[2]{d6}
+\\gobansymbol{e7}{a}
+\\showgoban[a4,f9]
+\\par 3 pass
+\\caption{Moves 1-3 \\& a pass}
+\\end{figure}
+";
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn given_cropped_figure_when_rendered_for_psgo_then_it_should_use_a_partial_board() {
+        // Given
+        let mut figure = figure();
+        figure.caption = None;
+        figure.crop_to_content(1);
+
+        // When
+        let res = render_latex(&figure, LatexPackage::Psgo).expect("Expected 9x9 to fit");
+
+        // Then
+        assert!(res.starts_with("\\begin{center}\n\\begin{psgopartialboard}[9]{(1,5)(6,9)}\n"));
+        assert!(res.contains("\\stone[\\marktr]{black}{c}{7}\n"));
+        assert!(res.contains("\\stone[2]{white}{d}{6}\n"));
+        assert!(res.contains("\\markpos{\\marklb{a}}{e}{7}\n"));
+        assert!(res.ends_with("\\end{psgopartialboard}\n\\par 3 pass\n\\end{center}\n"));
+    }
+
+    #[test]
+    fn given_large_board_when_rendered_for_igo_then_it_should_fail() {
+        let game = parse_sgf("(;GM[1]SZ[21];B[aa])").expect("Expected sgf to parse");
+        let figure = Figure::from_parsed_game(&game, 0..1).expect("Expected legal moves");
+
+        assert_eq!(
+            Err(LatexError::UnsupportedSize {
+                package: LatexPackage::Igo,
+                width: 21,
+                height: 21
+            }),
+            render_latex(&figure, LatexPackage::Igo)
+        );
+        assert!(render_latex(&figure, LatexPackage::Psgo).is_ok());
+    }
+
+    #[test]
+    fn given_labelled_stone_when_rendered_for_igo_then_the_stone_should_be_kept() {
+        // Given
+        let game = parse_sgf("(;GM[1]SZ[9]AB[cc]LB[cc:A])").expect("Expected sgf to parse");
+        let figure = Figure::from_parsed_game(&game, 0..1).expect("Expected legal moves");
+
+        // When
+        let res = render_latex(&figure, LatexPackage::Igo).expect("Expected 9x9 to fit");
+
+        // Then
+        assert!(res.contains("\\black{c7}\n"));
+        assert!(!res.contains("\\gobansymbol"));
+    }
+}
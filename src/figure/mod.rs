@@ -0,0 +1,400 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+    go::{
+        any_game::AnyGame,
+        board::FlexibleBoard,
+        coordinate::FlexibleCoordinate,
+        coordinate_notation::{CoordinateNotation, gtp_column_letter},
+        game::Game,
+        player::Player,
+        playermove::{Move, PlaceStoneMove},
+    },
+    parser::gsf::{ParsedGame, ReplayError, properties::Markup},
+};
+
+pub mod latex;
+pub mod svg;
+
+/// A printable diagram of a position, optionally with a numbered sequence of moves, shared by
+/// the [`svg`] and [`latex`] renderers.
+///
+/// Stones of the sequence keep their number even when they are captured later on, like in
+/// printed diagrams. Moves on a point that already shows a stone are listed in [`Figure::notes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Figure {
+    pub width: u16,
+    pub height: u16,
+    pub stones: HashMap<FlexibleCoordinate, FigureStone>,
+    /// Moves that could not be shown on the board, in the order they were played.
+    pub notes: Vec<MoveNote>,
+    pub markup: Vec<Markup>,
+    /// Part of the board that is drawn.
+    pub region: Region,
+    /// Whether coordinates are drawn along the edges.
+    pub coordinates: bool,
+    pub caption: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FigureStone {
+    pub player: Player,
+    /// Number of the move that placed the stone, None for stones that were there before the
+    /// sequence.
+    pub number: Option<u32>,
+}
+
+/// A numbered move that was played on a point that already shows a stone, or a pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveNote {
+    pub number: u32,
+    pub player: Player,
+    /// None for a pass.
+    pub at: Option<FlexibleCoordinate>,
+}
+
+/// Rectangle of points, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub left: u16,
+    pub top: u16,
+    pub right: u16,
+    pub bottom: u16,
+}
+
+impl Region {
+    pub fn full(board_size: (u16, u16)) -> Self {
+        Self {
+            left: 0,
+            top: 0,
+            right: board_size.0.saturating_sub(1),
+            bottom: board_size.1.saturating_sub(1),
+        }
+    }
+
+    pub fn contains(&self, coord: &FlexibleCoordinate) -> bool {
+        (self.left..=self.right).contains(&coord.x) && (self.top..=self.bottom).contains(&coord.y)
+    }
+
+    pub fn columns(&self) -> u16 {
+        self.right - self.left + 1
+    }
+
+    pub fn rows(&self) -> u16 {
+        self.bottom - self.top + 1
+    }
+}
+
+impl Figure {
+    /// The stones of a board without any numbers.
+    pub fn from_board<TBoard: FlexibleBoard>(board: &TBoard) -> Self {
+        let (width, height) = board.get_size();
+        let mut stones = HashMap::new();
+        for y in 0..height {
+            for x in 0..width {
+                let coord = FlexibleCoordinate { x, y };
+                if let Some(player) = board.get_player_at(&coord) {
+                    stones.insert(
+                        coord,
+                        FigureStone {
+                            player,
+                            number: None,
+                        },
+                    );
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            stones,
+            notes: vec![],
+            markup: vec![],
+            region: Region::full((width, height)),
+            coordinates: true,
+            caption: None,
+        }
+    }
+
+    /// The position before `range` with the moves in `range` numbered on top of it.
+    ///
+    /// Moves are numbered from the start of the game, so the first numbered move of
+    /// `moves[50..100]` is 51 if there are no setup moves. Setup moves in the range add their
+    /// stones without a number. The range is clamped to the moves.
+    pub fn from_moves(
+        board_size: (u16, u16),
+        moves: &[Move],
+        range: Range<usize>,
+    ) -> Result<Self, ReplayError> {
+        let range = clamp(range, moves.len());
+        let mut game = AnyGame::new(board_size);
+        let mut number = 0;
+        for (index, m) in moves[..range.start].iter().enumerate() {
+            game.make_move(m)
+                .map_err(|error| ReplayError { index, error })?;
+            if !matches!(m, Move::Setup(_)) {
+                number += 1;
+            }
+        }
+
+        let mut figure = Self::from_any_game(&game);
+        for (index, m) in moves[range.clone()].iter().enumerate() {
+            let index = range.start + index;
+            game.make_move(m)
+                .map_err(|error| ReplayError { index, error })?;
+
+            match m {
+                Move::PlaceStone(PlaceStoneMove { player, coord }) => {
+                    number += 1;
+                    if figure.stones.contains_key(coord) {
+                        figure.notes.push(MoveNote {
+                            number,
+                            player: *player,
+                            at: Some(*coord),
+                        });
+                    } else {
+                        figure.stones.insert(
+                            *coord,
+                            FigureStone {
+                                player: *player,
+                                number: Some(number),
+                            },
+                        );
+                    }
+                }
+                Move::Skip { player } => {
+                    number += 1;
+                    figure.notes.push(MoveNote {
+                        number,
+                        player: *player,
+                        at: None,
+                    });
+                }
+                Move::Setup(setup_move) => {
+                    for (coords, player) in [
+                        (&setup_move.add_black, Player::Black),
+                        (&setup_move.add_white, Player::White),
+                    ] {
+                        for coord in coords.iter() {
+                            figure.stones.entry(*coord).or_insert(FigureStone {
+                                player,
+                                number: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(figure)
+    }
+
+    /// See [`Figure::from_moves`], the history is replayed from an empty board.
+    pub fn from_game<TBoard: FlexibleBoard>(
+        game: &Game<TBoard>,
+        range: Range<usize>,
+    ) -> Result<Self, ReplayError> {
+        Self::from_moves(game.get_board().get_size(), game.get_history(), range)
+    }
+
+    /// See [`Figure::from_moves`], with the markup of the last node in `range`.
+    pub fn from_parsed_game(game: &ParsedGame, range: Range<usize>) -> Result<Self, ReplayError> {
+        let range = clamp(range, game.moves.len());
+        let mut figure = Self::from_moves((game.width, game.height), &game.moves, range.clone())?;
+        if let Some(properties) = range
+            .end
+            .checked_sub(1)
+            .and_then(|x| game.properties.get(x))
+        {
+            figure.markup = properties.markup.clone();
+        }
+        Ok(figure)
+    }
+
+    fn from_any_game(game: &AnyGame) -> Self {
+        match game {
+            AnyGame::Nineteen(game) => Self::from_board(game.get_board()),
+            AnyGame::Dynamic(game) => Self::from_board(game.get_board()),
+        }
+    }
+
+    /// Only draws `region`, clamped to the board.
+    pub fn crop(&mut self, region: Region) {
+        self.region = Region {
+            left: region.left.min(self.width.saturating_sub(1)),
+            top: region.top.min(self.height.saturating_sub(1)),
+            right: region.right.min(self.width.saturating_sub(1)),
+            bottom: region.bottom.min(self.height.saturating_sub(1)),
+        };
+        self.region.right = self.region.right.max(self.region.left);
+        self.region.bottom = self.region.bottom.max(self.region.top);
+    }
+
+    /// Crops to the stones and markup with `margin` points around them, for problem diagrams.
+    /// Sides that end up within `margin` points of the edge are extended to the edge.
+    pub fn crop_to_content(&mut self, margin: u16) {
+        let coords: Vec<FlexibleCoordinate> = self
+            .stones
+            .keys()
+            .copied()
+            .chain(self.markup.iter().flat_map(markup_coordinates))
+            .collect();
+        if coords.is_empty() {
+            return;
+        }
+
+        let snap_low = |value: u16| {
+            let value = value.saturating_sub(margin);
+            if value <= margin { 0 } else { value }
+        };
+        let snap_high = |value: u16, size: u16| {
+            let value = (value + margin).min(size - 1);
+            if size - 1 - value <= margin {
+                size - 1
+            } else {
+                value
+            }
+        };
+        let left = coords.iter().map(|x| x.x).min().expect("Not empty");
+        let top = coords.iter().map(|x| x.y).min().expect("Not empty");
+        let right = coords.iter().map(|x| x.x).max().expect("Not empty");
+        let bottom = coords.iter().map(|x| x.y).max().expect("Not empty");
+
+        self.crop(Region {
+            left: snap_low(left),
+            top: snap_low(top),
+            right: snap_high(right, self.width),
+            bottom: snap_high(bottom, self.height),
+        });
+    }
+
+    /// Text for a note, like `12 at 7`, `12 at D4` when the stone there has no number, or
+    /// `12 pass`.
+    pub fn note_text(&self, note: &MoveNote) -> String {
+        match note.at {
+            None => format!("{} pass", note.number),
+            Some(at) => match self.stones.get(&at).and_then(|x| x.number) {
+                Some(number) => format!("{} at {number}", note.number),
+                None => format!("{} at {}", note.number, self.point_name(&at)),
+            },
+        }
+    }
+
+    /// GTP name of a point, or `column-row` on boards that are too wide for GTP.
+    pub fn point_name(&self, coord: &FlexibleCoordinate) -> String {
+        let size = (self.width, self.height);
+        CoordinateNotation::Gtp
+            .format(coord, size)
+            .or_else(|_| CoordinateNotation::Numeric.format(coord, size))
+            .unwrap_or_default()
+    }
+
+    /// GTP column letter, or the 1-based column number on boards that are too wide for GTP.
+    pub fn column_label(&self, x: u16) -> String {
+        match gtp_column_letter(x).filter(|_| self.width <= 25) {
+            Some(letter) => letter.to_string(),
+            None => (x + 1).to_string(),
+        }
+    }
+
+    /// Row number counted from the bottom, like GTP.
+    pub fn row_label(&self, y: u16) -> String {
+        (self.height - y).to_string()
+    }
+}
+
+/// Keeps `range` within `len` moves, an empty range at the end when it starts past them.
+fn clamp(range: Range<usize>, len: usize) -> Range<usize> {
+    let end = range.end.min(len);
+    range.start.min(end)..end
+}
+
+fn markup_coordinates(markup: &Markup) -> Vec<FlexibleCoordinate> {
+    match markup {
+        Markup::Triangle(c)
+        | Markup::Square(c)
+        | Markup::Circle(c)
+        | Markup::Cross(c)
+        | Markup::Selected(c)
+        | Markup::Label(c, _) => vec![*c],
+        Markup::Arrow(from, to) | Markup::Line(from, to) => vec![*from, *to],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::gsf::parse_sgf;
+
+    #[test]
+    fn given_sequence_with_ko_when_figure_is_made_then_retakes_should_be_noted() {
+        // Given
+        let game = parse_sgf(
+            "(;GM[1]SZ[9];B[ca];W[ba];B[db];W[ab];B[cc];W[cb];B[gg];W[bc];B[bb];W[ee];B[ff];W[cb];B[])",
+        )
+        .expect("Expected sgf to parse");
+
+        // When
+        let figure = Figure::from_parsed_game(&game, 6..13).expect("Expected legal moves");
+
+        // Then
+        let stone = |x, y| figure.stones.get(&FlexibleCoordinate { x, y }).copied();
+        assert_eq!(
+            Some(FigureStone {
+                player: Player::White,
+                number: None
+            }),
+            stone(2, 1)
+        );
+        assert_eq!(Some(8), stone(1, 2).and_then(|x| x.number));
+        assert_eq!(Some(9), stone(1, 1).and_then(|x| x.number));
+        let notes: Vec<String> = figure.notes.iter().map(|x| figure.note_text(x)).collect();
+        assert_eq!(vec!["12 at C8", "13 pass"], notes);
+    }
+
+    #[test]
+    fn given_problem_in_a_corner_when_cropped_to_content_then_it_should_reach_the_edges() {
+        // Given
+        let game = parse_sgf("(;GM[1]SZ[19]AB[pc][qd]AW[qc][rc])").expect("Expected sgf");
+        let mut figure = Figure::from_parsed_game(&game, 0..1).expect("Expected legal moves");
+
+        // When
+        figure.crop_to_content(2);
+
+        // Then
+        assert_eq!(
+            Region {
+                left: 13,
+                top: 0,
+                right: 18,
+                bottom: 5
+            },
+            figure.region
+        );
+    }
+
+    #[test]
+    fn given_range_past_the_moves_when_figure_is_made_then_it_should_be_clamped() {
+        // Given
+        let game = parse_sgf("(;GM[1]SZ[9];B[aa];W[bb];B[cc])").expect("Expected sgf");
+
+        // When
+        let past = Figure::from_parsed_game(&game, 5..10).expect("Expected legal moves");
+        let overlapping = Figure::from_parsed_game(&game, 1..10).expect("Expected legal moves");
+        let mut empty = Figure::from_moves((0, 0), &[], 3..10).expect("Expected no moves");
+        empty.crop(Region::full((9, 9)));
+
+        // Then
+        assert_eq!(3, past.stones.len());
+        assert!(past.stones.values().all(|x| x.number.is_none()));
+        let numbers: Vec<Option<u32>> = [(0, 0), (1, 1), (2, 2)]
+            .into_iter()
+            .map(|(x, y)| overlapping.stones[&FlexibleCoordinate { x, y }].number)
+            .collect();
+        assert_eq!(vec![None, Some(2), Some(3)], numbers);
+        assert!(empty.stones.is_empty());
+        assert_eq!(Region::full((0, 0)), empty.region);
+        assert!(svg::render_svg(&empty, &svg::SvgOptions::default()).starts_with("<svg"));
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt::Write;
+
+use crate::{
+    figure::Figure,
+    go::{coordinate::FlexibleCoordinate, handicap::star_points, player::Player},
+    parser::gsf::properties::Markup,
+};
+
+const BOARD_COLOR: &str = "#dcb35c";
+
+/// Size of the SVG output, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// Distance between two lines of the grid.
+    pub cell: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self { cell: 24.0 }
+    }
+}
+
+/// Renders a figure as a standalone SVG document.
+///
+/// Text uses the generic `sans-serif` font family, so the output needs no fonts or other files
+/// to display.
+pub fn render_svg(figure: &Figure, options: &SvgOptions) -> String {
+    let cell = options.cell;
+    let region = figure.region;
+    let margin = if figure.coordinates { cell * 1.5 } else { cell };
+    let board_width = (region.columns() - 1) as f64 * cell;
+    let board_height = (region.rows() - 1) as f64 * cell;
+    let text_lines = figure.notes.len() + figure.caption.iter().count();
+    let width = board_width + margin * 2.0;
+    let height = board_height + margin * 2.0 + text_lines as f64 * cell * 0.8;
+
+    let px = |x: u16| margin + (x - region.left) as f64 * cell;
+    let py = |y: u16| margin + (y - region.top) as f64 * cell;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif">"#
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="{width}" height="{height}" fill="{BOARD_COLOR}"/>"#
+    );
+
+    // Lines stop at the edge of the board, and run half a cell past the region where it was
+    // cropped.
+    let extend = |cropped: bool| if cropped { cell / 2.0 } else { 0.0 };
+    let x1 = px(region.left) - extend(region.left > 0);
+    let x2 = px(region.right) + extend(region.right + 1 < figure.width);
+    let y1 = py(region.top) - extend(region.top > 0);
+    let y2 = py(region.bottom) + extend(region.bottom + 1 < figure.height);
+    for x in region.left..=region.right {
+        let _ = writeln!(
+            out,
+            r#"<line x1="{0}" y1="{y1}" x2="{0}" y2="{y2}" stroke="black"/>"#,
+            px(x)
+        );
+    }
+    for y in region.top..=region.bottom {
+        let _ = writeln!(
+            out,
+            r#"<line x1="{x1}" y1="{0}" x2="{x2}" y2="{0}" stroke="black"/>"#,
+            py(y)
+        );
+    }
+
+    for star in star_points((figure.width, figure.height)).iter() {
+        if region.contains(star) && !figure.stones.contains_key(star) {
+            let _ = writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="black"/>"#,
+                px(star.x),
+                py(star.y),
+                cell / 8.0
+            );
+        }
+    }
+
+    if figure.coordinates {
+        let size = cell * 0.45;
+        for x in region.left..=region.right {
+            let label = figure.column_label(x);
+            for y in [margin - cell, margin + board_height + cell] {
+                text(&mut out, px(x), y, size, "black", &label);
+            }
+        }
+        for y in region.top..=region.bottom {
+            let label = figure.row_label(y);
+            for x in [margin - cell, margin + board_width + cell] {
+                text(&mut out, x, py(y), size, "black", &label);
+            }
+        }
+    }
+
+    for y in region.top..=region.bottom {
+        for x in region.left..=region.right {
+            let coord = FlexibleCoordinate { x, y };
+            let Some(stone) = figure.stones.get(&coord) else {
+                continue;
+            };
+            let (fill, ink) = match stone.player {
+                Player::Black => ("black", "white"),
+                Player::White => ("white", "black"),
+            };
+            let _ = writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{fill}" stroke="black"/>"#,
+                px(x),
+                py(y),
+                cell * 0.47
+            );
+            if let Some(number) = stone.number {
+                let size = if number >= 100 {
+                    cell * 0.4
+                } else {
+                    cell * 0.5
+                };
+                text(&mut out, px(x), py(y), size, ink, &number.to_string());
+            }
+        }
+    }
+
+    for markup in &figure.markup {
+        draw_markup(&mut out, figure, markup, &px, &py, cell);
+    }
+
+    let mut line_y = margin * 2.0 + board_height;
+    for note in &figure.notes {
+        let note = figure.note_text(note);
+        text(&mut out, width / 2.0, line_y, cell * 0.5, "black", &note);
+        line_y += cell * 0.8;
+    }
+    if let Some(caption) = &figure.caption {
+        text(&mut out, width / 2.0, line_y, cell * 0.5, "black", caption);
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+fn draw_markup(
+    out: &mut String,
+    figure: &Figure,
+    markup: &Markup,
+    px: &impl Fn(u16) -> f64,
+    py: &impl Fn(u16) -> f64,
+    cell: f64,
+) {
+    let ink = |coord: &FlexibleCoordinate| match figure.stones.get(coord).map(|x| x.player) {
+        Some(Player::Black) => "white",
+        _ => "black",
+    };
+    let visible = |coord: &FlexibleCoordinate| figure.region.contains(coord);
+    let r = cell * 0.3;
+
+    let _ = match markup {
+        Markup::Triangle(c) if visible(c) => {
+            let (x, y) = (px(c.x), py(c.y));
+            writeln!(
+                out,
+                r#"<polygon points="{},{} {},{} {},{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                x,
+                y - r,
+                x - r * 0.87,
+                y + r * 0.5,
+                x + r * 0.87,
+                y + r * 0.5,
+                ink(c)
+            )
+        }
+        Markup::Square(c) if visible(c) => writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+            px(c.x) - r * 0.75,
+            py(c.y) - r * 0.75,
+            r * 1.5,
+            r * 1.5,
+            ink(c)
+        ),
+        Markup::Circle(c) if visible(c) => writeln!(
+            out,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+            px(c.x),
+            py(c.y),
+            r * 0.8,
+            ink(c)
+        ),
+        Markup::Cross(c) if visible(c) => {
+            let (x, y, d) = (px(c.x), py(c.y), r * 0.6);
+            writeln!(
+                out,
+                r#"<path d="M{} {}L{} {}M{} {}L{} {}" stroke="{}" stroke-width="2"/>"#,
+                x - d,
+                y - d,
+                x + d,
+                y + d,
+                x - d,
+                y + d,
+                x + d,
+                y - d,
+                ink(c)
+            )
+        }
+        Markup::Selected(c) if visible(c) => writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="blue" fill-opacity="0.3"/>"#,
+            px(c.x) - cell / 2.0,
+            py(c.y) - cell / 2.0,
+            cell,
+            cell
+        ),
+        Markup::Label(c, label) if visible(c) => {
+            // Labels on empty points hide the grid behind them.
+            if !figure.stones.contains_key(c) {
+                let _ = writeln!(
+                    out,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{BOARD_COLOR}"/>"#,
+                    px(c.x),
+                    py(c.y),
+                    cell * 0.4
+                );
+            }
+            text(out, px(c.x), py(c.y), cell * 0.5, ink(c), label);
+            Ok(())
+        }
+        Markup::Line(from, to) if visible(from) && visible(to) => writeln!(
+            out,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="2"/>"#,
+            px(from.x),
+            py(from.y),
+            px(to.x),
+            py(to.y)
+        ),
+        Markup::Arrow(from, to) if visible(from) && visible(to) => {
+            let (x1, y1, x2, y2) = (px(from.x), py(from.y), px(to.x), py(to.y));
+            let angle = (y2 - y1).atan2(x2 - x1);
+            let head = |offset: f64| {
+                (
+                    x2 - r * (angle + offset).cos(),
+                    y2 - r * (angle + offset).sin(),
+                )
+            };
+            let (ax, ay) = head(0.5);
+            let (bx, by) = head(-0.5);
+            writeln!(
+                out,
+                r#"<path d="M{x1} {y1}L{x2} {y2}M{ax} {ay}L{x2} {y2}L{bx} {by}" fill="none" stroke="black" stroke-width="2"/>"#
+            )
+        }
+        _ => Ok(()),
+    };
+}
+
+fn text(out: &mut String, x: f64, y: f64, size: f64, fill: &str, text: &str) {
+    let _ = writeln!(
+        out,
+        r#"<text x="{x}" y="{y}" font-size="{size}" fill="{fill}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+        escape_xml(text)
+    );
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{figure::Region, parser::gsf::parse_sgf};
+
+    #[test]
+    fn given_numbered_sequence_when_rendered_then_it_should_draw_stones_numbers_and_notes() {
+        // Given
+        let game = parse_sgf(
+            "(;GM[1]SZ[9];B[ca];W[ba];B[db];W[ab];B[cc];W[cb];B[gg];W[bc];B[bb];W[ee];B[ff]
+;W[cb]TR[cb]LB[dd:a<b])",
+        )
+        .expect("Expected sgf to parse");
+        let mut figure = Figure::from_parsed_game(&game, 6..12).expect("Expected legal moves");
+        figure.caption = Some("Figure 1 (7-12)".to_string());
+
+        // When
+        let svg = render_svg(&figure, &SvgOptions::default());
+
+        // Then
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        // Stones, the star points that are still empty and the background of the label.
+        assert_eq!(11 + 2 + 1, svg.matches("<circle").count());
+        assert_eq!(9 + 9, svg.matches("<line").count());
+        assert!(svg.contains(">12 at C8</text>"));
+        assert!(svg.contains(">Figure 1 (7-12)</text>"));
+        assert!(svg.contains(">a&lt;b</text>"));
+        assert!(svg.contains("<polygon"));
+        assert!(!svg.contains("url("));
+    }
+
+    #[test]
+    fn given_cropped_figure_when_rendered_then_it_should_only_draw_the_region() {
+        // Given
+        let game = parse_sgf("(;GM[1]SZ[19]AB[pc][qd]AW[qc][rc])").expect("Expected sgf");
+        let mut figure = Figure::from_parsed_game(&game, 0..1).expect("Expected legal moves");
+        figure.crop(Region {
+            left: 13,
+            top: 0,
+            right: 18,
+            bottom: 5,
+        });
+        figure.coordinates = false;
+
+        // When
+        let svg = render_svg(&figure, &SvgOptions::default());
+
+        // Then
+        assert_eq!(6 + 6, svg.matches("<line").count());
+        assert!(svg.contains(r#"<line x1="12" y1="24" x2="144" y2="24" stroke="black"/>"#));
+        assert_eq!(4 + 1, svg.matches("<circle").count());
+    }
+}
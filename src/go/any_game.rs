@@ -0,0 +1,231 @@
+use std::fmt::Display;
+
+use crate::go::{
+    bitmask_board::BitMaskBoard,
+    bitmask19::BitMask19,
+    board::FlexibleBoard,
+    coordinate::FlexibleCoordinate,
+    coordinate_set::CoordinateSet,
+    dynamic_bitmask::DynamicBitMask,
+    game::{Game, GameResult, MoveError, MoveOutcome},
+    player::Player,
+    playermove::Move,
+    render::{BoardRenderer, GridStyle},
+    score::{AreaScore, TerritoryScore, area_score, territory_score},
+};
+
+/// A game on the fastest board backend available for its size, for when the size is only known
+/// at runtime.
+#[derive(Debug, Clone)]
+pub enum AnyGame {
+    Nineteen(Game<BitMaskBoard<BitMask19>>),
+    Dynamic(Game<BitMaskBoard<DynamicBitMask>>),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $game:ident => $body:expr) => {
+        match $self {
+            AnyGame::Nineteen($game) => $body,
+            AnyGame::Dynamic($game) => $body,
+        }
+    };
+}
+
+impl AnyGame {
+    /// Creates an empty game, picking the board backend based on the 1-based width and height.
+    pub fn new(size: (u16, u16)) -> Self {
+        match size {
+            (19, 19) => AnyGame::Nineteen(Game::new(BitMaskBoard::new(BitMask19::init))),
+            _ => AnyGame::Dynamic(Game::new(BitMaskBoard::new(|| DynamicBitMask::init(size)))),
+        }
+    }
+
+    pub fn make_move(&mut self, m: &Move) -> Result<MoveOutcome, MoveError> {
+        dispatch!(self, game => game.make_move(m))
+    }
+
+    /// Takes back the last move by replaying the moves before it on an empty board. Returns false
+    /// when there is no move to take back, when the game is over or when the game did not start
+    /// from an empty board, like games created with [`Game::from_position`].
+    pub fn undo(&mut self) -> bool {
+        let history = self.get_history();
+        let Some((_, moves)) = history.split_last() else {
+            return false;
+        };
+        if self.is_over() {
+            return false;
+        }
+        let moves = moves.to_vec();
+        let started_empty = self
+            .replay_from_empty(self.get_history())
+            .is_some_and(|game| game.is_same_position(self));
+        let Some(game) = self.replay_from_empty(&moves).filter(|_| started_empty) else {
+            return false;
+        };
+        *self = game;
+        true
+    }
+
+    fn replay_from_empty(&self, moves: &[Move]) -> Option<AnyGame> {
+        let mut game = AnyGame::new(self.get_size());
+        for m in moves {
+            game.make_move(m).ok()?;
+        }
+        Some(game)
+    }
+
+    fn is_same_position(&self, other: &AnyGame) -> bool {
+        let same_board = match (self, other) {
+            (AnyGame::Nineteen(a), AnyGame::Nineteen(b)) => a.get_board() == b.get_board(),
+            (AnyGame::Dynamic(a), AnyGame::Dynamic(b)) => a.get_board() == b.get_board(),
+            _ => false,
+        };
+        same_board
+            && self.get_current_player() == other.get_current_player()
+            && self.get_ko() == other.get_ko()
+            && [Player::Black, Player::White]
+                .into_iter()
+                .all(|x| self.get_captures(x) == other.get_captures(x))
+    }
+
+    pub fn get_size(&self) -> (u16, u16) {
+        dispatch!(self, game => game.get_board().get_size())
+    }
+
+    pub fn get_player_at(&self, coord: &FlexibleCoordinate) -> Option<Player> {
+        dispatch!(self, game => game.get_board().get_player_at(coord))
+    }
+
+    pub fn get_current_player(&self) -> Player {
+        dispatch!(self, game => game.get_current_player())
+    }
+
+    pub fn get_captures(&self, player: Player) -> u16 {
+        dispatch!(self, game => game.get_captures(player))
+    }
+
+    pub fn get_history(&self) -> &[Move] {
+        dispatch!(self, game => game.get_history())
+    }
+
+    pub fn get_ko(&self) -> Option<FlexibleCoordinate> {
+        dispatch!(self, game => game.get_ko())
+    }
+
+    /// Whether the move would be allowed, without making it.
+    pub fn is_legal(&self, m: &Move) -> bool {
+        self.clone().make_move(m).is_ok()
+    }
+
+    pub fn end(&mut self, result: GameResult) {
+        dispatch!(self, game => game.end(result))
+    }
+
+    pub fn get_result(&self) -> Option<&GameResult> {
+        dispatch!(self, game => game.get_result())
+    }
+
+    pub fn is_over(&self) -> bool {
+        dispatch!(self, game => game.is_over())
+    }
+
+    /// Area score of the board, see [`area_score`].
+    pub fn area_score(&self, komi: f64, dead: &CoordinateSet) -> AreaScore {
+        dispatch!(self, game => area_score(game.get_board(), komi, dead))
+    }
+
+    /// Territory score of the board, see [`territory_score`].
+    pub fn territory_score(
+        &self,
+        captures: (u16, u16),
+        komi: f64,
+        dead: &CoordinateSet,
+    ) -> TerritoryScore {
+        dispatch!(self, game => territory_score(game.get_board(), captures, komi, dead))
+    }
+
+    /// Renders the board with the last move and the ko marked, see [`BoardRenderer::for_game`].
+    pub fn render(&self, style: GridStyle) -> String {
+        dispatch!(self, game => BoardRenderer {
+            style,
+            ..BoardRenderer::for_game(game)
+        }
+        .render(game.get_board()))
+    }
+}
+
+impl Display for AnyGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        dispatch!(self, game => write!(f, "{}", game.get_board().display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::playermove::PlaceStoneMove;
+
+    #[test]
+    fn given_sizes_when_any_game_is_created_then_it_should_pick_the_backend_by_size() {
+        assert!(matches!(AnyGame::new((19, 19)), AnyGame::Nineteen(_)));
+        assert!(matches!(AnyGame::new((9, 9)), AnyGame::Dynamic(_)));
+        assert!(matches!(AnyGame::new((19, 13)), AnyGame::Dynamic(_)));
+    }
+
+    #[test]
+    fn given_rectangular_game_when_move_is_made_then_it_should_be_on_the_board() {
+        // Given
+        let mut game = AnyGame::new((5, 11));
+        let coord = FlexibleCoordinate { x: 4, y: 10 };
+
+        // When
+        let res = game.make_move(&Move::PlaceStone(PlaceStoneMove {
+            player: Player::Black,
+            coord,
+        }));
+
+        // Then
+        assert!(res.is_ok());
+        assert_eq!((5, 11), game.get_size());
+        assert_eq!(Some(Player::Black), game.get_player_at(&coord));
+        assert_eq!(Player::White, game.get_current_player());
+    }
+
+    #[test]
+    fn given_game_from_a_position_when_undone_then_it_should_keep_the_game() {
+        // Given
+        let mut board = BitMaskBoard::new(|| DynamicBitMask::init((9, 9)));
+        let stone = FlexibleCoordinate { x: 2, y: 2 };
+        board
+            .set_player_at(&stone, &Player::White)
+            .expect("Expected the point to be empty");
+        let mut game = AnyGame::Dynamic(Game::from_position(board, Player::Black, None, (0, 0)));
+        let m = Move::PlaceStone(PlaceStoneMove {
+            player: Player::Black,
+            coord: FlexibleCoordinate { x: 4, y: 4 },
+        });
+        game.make_move(&m).expect("Expected move to be allowed");
+        let mut finished = AnyGame::new((9, 9));
+        finished.make_move(&m).expect("Expected move to be allowed");
+        finished.end(GameResult::Resignation {
+            winner: Player::Black,
+        });
+        let mut played = AnyGame::new((9, 9));
+        played.make_move(&m).expect("Expected move to be allowed");
+
+        // When
+        let undone = game.undo();
+        let finished_undone = finished.undo();
+        let played_undone = played.undo();
+
+        // Then
+        assert!(!undone);
+        assert_eq!(Some(Player::White), game.get_player_at(&stone));
+        assert_eq!(1, game.get_history().len());
+        assert!(!finished_undone);
+        assert!(finished.is_over());
+        assert!(played_undone);
+        assert!(played.get_history().is_empty());
+        assert_eq!(Player::Black, played.get_current_player());
+    }
+}
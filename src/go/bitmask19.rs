@@ -1,6 +1,8 @@
 use crate::go::{bitmask::FlexibleBitMask, coordinate::FlexibleCoordinate};
 
+/// Serialized as the array of six 64-bit words, bit `x * 19 + y` is the point at `x`, `y`.
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitMask19(pub [u64; 6]);
 
 impl FlexibleBitMask for BitMask19 {
@@ -1,4 +1,4 @@
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
 
 use crate::go::{
     bitmask::FlexibleBitMask,
@@ -9,7 +9,12 @@ use crate::go::{
     playermove::PlaceStoneMove,
 };
 
+/// Serialized as `{"width": 19, "height": 19, "black_mask": ..., "white_mask": ...}` with the
+/// layout of the bitmask for the masks. Deserializing fails when the masks are not the size of the
+/// board or a point has both a black and a white stone.
 #[derive(Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BitMaskBoardRepr<TBitMask>"))]
 pub struct BitMaskBoard<TBitMask: FlexibleBitMask + Eq + PartialEq + Debug + Clone> {
     width: u16,
     height: u16,
@@ -38,12 +43,12 @@ impl<TBitMask: FlexibleBitMask + Eq + PartialEq + Debug + Clone> BitMaskBoard<TB
     ) -> Self {
         let mut board = Self::new(mask_factory);
 
-        assert_eq!(board.width, position.len() as u16);
-        assert_eq!(board.height, position[0].len() as u16);
+        assert_eq!(board.height, position.len() as u16);
+        assert_eq!(board.width, position[0].len() as u16);
 
-        for y in 0..board.width {
+        for y in 0..board.height {
             let vals = &position[y as usize];
-            for x in 0..board.height {
+            for x in 0..board.width {
                 let val = vals[x as usize];
                 if let Some(player) = val {
                     board
@@ -132,6 +137,53 @@ impl<TBitMask: FlexibleBitMask + PartialEq + Eq + Debug + Clone> FlexibleBoard
     }
 }
 
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BitMaskBoardRepr<TBitMask> {
+    width: u16,
+    height: u16,
+    black_mask: TBitMask,
+    white_mask: TBitMask,
+}
+
+#[cfg(feature = "serde")]
+impl<TBitMask: FlexibleBitMask + Eq + PartialEq + Debug + Clone> TryFrom<BitMaskBoardRepr<TBitMask>>
+    for BitMaskBoard<TBitMask>
+{
+    type Error = String;
+
+    fn try_from(value: BitMaskBoardRepr<TBitMask>) -> Result<Self, Self::Error> {
+        let BitMaskBoardRepr {
+            width,
+            height,
+            black_mask,
+            white_mask,
+        } = value;
+        let size = (width, height);
+        if black_mask.get_size() != size || white_mask.get_size() != size {
+            return Err(format!(
+                "the masks of a {width}x{height} board are {:?} and {:?}",
+                black_mask.get_size(),
+                white_mask.get_size()
+            ));
+        }
+        for y in 0..height {
+            for x in 0..width {
+                let coord = FlexibleCoordinate { x, y };
+                if black_mask.get_bit_at(&coord) && white_mask.get_bit_at(&coord) {
+                    return Err(format!("the point ({x}, {y}) has two stones"));
+                }
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            black_mask,
+            white_mask,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,6 +191,7 @@ mod tests {
         bitmask19::BitMask19,
         board::FlexibleBoard,
         coordinate::FlexibleCoordinate,
+        dynamic_bitmask::DynamicBitMask,
         player::{B, W},
     };
 
@@ -223,4 +276,52 @@ mod tests {
         // Then
         assert_eq!(W, res);
     }
+
+    #[test]
+    fn given_rectangular_position_when_from_position_is_called_then_rows_should_be_y() {
+        // Given
+        let e = None;
+        let position = vec![
+            vec![B, e, e, e, e],
+            vec![e, e, e, e, W],
+            vec![e, e, e, e, e],
+        ];
+
+        // When
+        let board = BitMaskBoard::from_position(|| DynamicBitMask::init((5, 3)), position);
+
+        // Then
+        assert_eq!((5, 3), board.get_size());
+        assert_eq!(B, board.get_player_at(&FlexibleCoordinate { x: 0, y: 0 }));
+        assert_eq!(W, board.get_player_at(&FlexibleCoordinate { x: 4, y: 1 }));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_board_with_inconsistent_masks_when_deserialized_then_it_should_be_rejected() {
+        // Given
+        let mask = |width: u16, height: u16, bits: &str| {
+            format!(r#"{{"width":{width},"height":{height},"bits":[{bits}]}}"#)
+        };
+        let board = |black: String, white: String| {
+            format!(r#"{{"width":9,"height":9,"black_mask":{black},"white_mask":{white}}}"#)
+        };
+        let sizes = board(mask(9, 9, "1,0"), mask(13, 13, "0,0,0"));
+        let overlap = board(mask(9, 9, "1,0"), mask(9, 9, "1,0"));
+        let valid = board(mask(9, 9, "1,0"), mask(9, 9, "2,0"));
+
+        // When
+        let sizes = serde_json::from_str::<BitMaskBoard<DynamicBitMask>>(&sizes);
+        let overlap = serde_json::from_str::<BitMaskBoard<DynamicBitMask>>(&overlap);
+        let valid = serde_json::from_str::<BitMaskBoard<DynamicBitMask>>(&valid);
+
+        // Then
+        assert!(sizes.is_err());
+        assert!(overlap.is_err());
+        let valid = valid.expect("Expected board to deserialize");
+        assert_eq!(
+            Some(Player::White),
+            valid.get_player_at(&FlexibleCoordinate { x: 1, y: 0 })
+        );
+    }
 }
@@ -1,8 +1,8 @@
 use std::{collections::HashSet, fmt::Display};
 
 use crate::go::{
-    coordinate::FlexibleCoordinate, coordinate_set::CoordinateSet, group::Group, player::Player,
-    playermove::PlaceStoneMove,
+    coordinate::FlexibleCoordinate, coordinate_set::CoordinateSet, diagram::Diagram, group::Group,
+    player::Player, playermove::PlaceStoneMove, render::BoardRenderer,
 };
 use thiserror::{self, Error};
 
@@ -65,6 +65,13 @@ pub trait FlexibleBoard: Sized {
             if !neighbour.is_in_board(self) || self.get_player_at(&neighbour) != Some(opponent) {
                 continue;
             }
+            // A group can touch the move on more than one side.
+            if res
+                .iter()
+                .any(|x: &Group| x.coordinates.contains(&neighbour))
+            {
+                continue;
+            }
 
             let group = self
                 .find_group(&neighbour)
@@ -80,22 +87,17 @@ pub trait FlexibleBoard: Sized {
     }
 }
 
+/// Prints the board with the default [`BoardRenderer`], the alternate form `{:#}` prints it as a
+/// [`Diagram`].
 pub struct DisplayFlexibleboard<'a, T: FlexibleBoard>(pub &'a T);
 
 impl<T: FlexibleBoard> Display for DisplayFlexibleboard<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let size = self.0.get_size();
-        for y in 0..size.1 {
-            for x in 0..size.0 {
-                match self.0.get_player_at(&FlexibleCoordinate { x, y }) {
-                    None => write!(f, "  "),
-                    Some(Player::Black) => write!(f, "⚫"),
-                    Some(Player::White) => write!(f, "⚪"),
-                }?;
-            }
-            writeln!(f)?;
+        if f.alternate() {
+            return write!(f, "{}", Diagram::from_board(self.0));
         }
-        Ok(())
+
+        write!(f, "{}", BoardRenderer::default().render(self.0))
     }
 }
 
@@ -0,0 +1,367 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::go::{
+    board::FlexibleBoard,
+    game::{Game, GameResult, MoveError},
+    player::Player,
+    playermove::Move,
+    time_control::{ClockState, TimeControl},
+};
+
+/// Where a game clock gets the current time from.
+pub trait ClockSource {
+    /// Time since some fixed moment, only differences between calls are used.
+    fn now(&self) -> Duration;
+}
+
+/// Reads the time from the system's monotonic clock.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockSource for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when told to, clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().expect("Clock lock poisoned") += duration;
+    }
+}
+
+impl ClockSource for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().expect("Clock lock poisoned")
+    }
+}
+
+/// Keeps the clocks of both players while a game is played.
+///
+/// The clock of the player to move runs from the moment it is started, playing a move through
+/// the clock charges the time spent and starts the clock of the next player.
+pub struct GameClock<TSource: ClockSource> {
+    time_control: TimeControl,
+    black: ClockState,
+    white: ClockState,
+    /// The player whose clock is running and the time it was started at.
+    running: Option<(Player, Duration)>,
+    source: TSource,
+}
+
+impl<TSource: ClockSource> GameClock<TSource> {
+    pub fn new(time_control: TimeControl, source: TSource) -> Self {
+        let clock = time_control.initial_clock();
+        Self {
+            time_control,
+            black: clock,
+            white: clock,
+            running: None,
+            source,
+        }
+    }
+
+    /// Starts the clock of the player, stopping the other clock without charging it.
+    pub fn start(&mut self, player: Player) {
+        self.running = Some((player, self.source.now()));
+    }
+
+    /// Stops the running clock without charging it, for example while the game is paused.
+    pub fn stop(&mut self) {
+        self.running = None;
+    }
+
+    pub fn get_time_control(&self) -> &TimeControl {
+        &self.time_control
+    }
+
+    /// The clock of the player right now, including the time spent on the current move.
+    ///
+    /// None when the player has run out of time.
+    pub fn remaining(&self, player: Player) -> Option<ClockState> {
+        match self.running {
+            Some((running, started)) if running == player => {
+                let elapsed = self.source.now().saturating_sub(started);
+                self.time_control.elapse(self.clock(player), elapsed)
+            }
+            _ => Some(*self.clock(player)),
+        }
+    }
+
+    /// Plays the move on the game and charges the time spent to the player who made it.
+    ///
+    /// When the player ran out of time the game ends with a timeout and the move is not played.
+    /// A move the game rejects leaves the clock running.
+    pub fn make_move<TBoard: FlexibleBoard>(
+        &mut self,
+        game: &mut Game<TBoard>,
+        m: &Move,
+    ) -> Result<(), ClockError> {
+        let player = match m {
+            Move::PlaceStone(place_stone_move) => place_stone_move.player,
+            Move::Skip { player } => *player,
+            Move::Setup(_) => {
+                game.make_move(m)?;
+                return Ok(());
+            }
+        };
+
+        self.check_timeout(game)?;
+
+        let charged = match self.running {
+            Some((running, _)) if running == player => self.charge(),
+            _ => Some(*self.clock(player)),
+        };
+        let Some(charged) = charged else {
+            return self.flag(game, player);
+        };
+
+        game.make_move(m)?;
+        *self.clock_mut(player) = charged;
+        self.start(game.get_current_player());
+        Ok(())
+    }
+
+    /// Ends the game when the running clock has run out of time.
+    pub fn check_timeout<TBoard: FlexibleBoard>(
+        &mut self,
+        game: &mut Game<TBoard>,
+    ) -> Result<(), ClockError> {
+        match self.running {
+            Some((player, _)) if self.charge().is_none() => self.flag(game, player),
+            _ => Ok(()),
+        }
+    }
+
+    fn flag<TBoard: FlexibleBoard>(
+        &mut self,
+        game: &mut Game<TBoard>,
+        player: Player,
+    ) -> Result<(), ClockError> {
+        self.running = None;
+        if !game.is_over() {
+            game.end(GameResult::Timeout { winner: !player });
+        }
+        Err(ClockError::Timeout { player })
+    }
+
+    /// The clock of the running player as if the move was made right now.
+    fn charge(&self) -> Option<ClockState> {
+        let (player, started) = self.running?;
+        let elapsed = self.source.now().saturating_sub(started);
+        self.time_control.charge(self.clock(player), elapsed)
+    }
+
+    fn clock(&self, player: Player) -> &ClockState {
+        match player {
+            Player::Black => &self.black,
+            Player::White => &self.white,
+        }
+    }
+
+    fn clock_mut(&mut self, player: Player) -> &mut ClockState {
+        match player {
+            Player::Black => &mut self.black,
+            Player::White => &mut self.white,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ClockError {
+    #[error(transparent)]
+    Move(#[from] MoveError),
+    #[error("{player:?} ran out of time.")]
+    Timeout { player: Player },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::{
+        bitmask_board::BitMaskBoard, bitmask19::BitMask19, coordinate::FlexibleCoordinate,
+        playermove::PlaceStoneMove,
+    };
+
+    fn place(player: Player, x: u16, y: u16) -> Move {
+        Move::PlaceStone(PlaceStoneMove {
+            player,
+            coord: FlexibleCoordinate { x, y },
+        })
+    }
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn given_byo_yomi_clock_when_moves_take_longer_than_a_period_then_periods_should_be_lost() {
+        // Given
+        let source = ManualClock::new();
+        let mut game = Game::new(BitMaskBoard::new(BitMask19::init));
+        let mut clock = GameClock::new(
+            TimeControl::ByoYomi {
+                main_time: secs(10),
+                periods: 3,
+                period_time: secs(30),
+            },
+            source.clone(),
+        );
+        clock.start(Player::Black);
+
+        // When
+        source.advance(secs(45));
+        clock
+            .make_move(&mut game, &place(Player::Black, 3, 3))
+            .expect("Expected move to be in time");
+        source.advance(secs(5));
+
+        // Then
+        assert_eq!(
+            Some(ClockState {
+                time_left: secs(30),
+                overtime_left: Some(2)
+            }),
+            clock.remaining(Player::Black)
+        );
+        assert_eq!(
+            Some(ClockState {
+                time_left: secs(5),
+                overtime_left: None
+            }),
+            clock.remaining(Player::White)
+        );
+    }
+
+    #[test]
+    fn given_fischer_clock_when_moves_are_made_then_the_increment_should_be_added() {
+        // Given
+        let source = ManualClock::new();
+        let mut game = Game::new(BitMaskBoard::new(BitMask19::init));
+        let mut clock = GameClock::new(
+            TimeControl::Fischer {
+                main_time: secs(60),
+                increment: secs(10),
+                max_time: None,
+            },
+            source.clone(),
+        );
+        clock.start(Player::Black);
+
+        // When
+        source.advance(secs(5));
+        clock
+            .make_move(
+                &mut game,
+                &Move::Skip {
+                    player: Player::Black,
+                },
+            )
+            .expect("Expected move to be in time");
+
+        // Then
+        assert_eq!(secs(65), clock.remaining(Player::Black).unwrap().time_left);
+        assert_eq!(secs(60), clock.remaining(Player::White).unwrap().time_left);
+    }
+
+    #[test]
+    fn given_running_clock_when_time_runs_out_then_the_game_should_end_in_a_timeout() {
+        // Given
+        let source = ManualClock::new();
+        let mut game = Game::new(BitMaskBoard::new(BitMask19::init));
+        let mut clock = GameClock::new(
+            TimeControl::Absolute {
+                main_time: secs(60),
+            },
+            source.clone(),
+        );
+        clock.start(Player::Black);
+        clock
+            .make_move(&mut game, &place(Player::Black, 3, 3))
+            .expect("Expected move to be in time");
+
+        // When
+        source.advance(secs(61));
+        let res = clock.make_move(&mut game, &place(Player::White, 15, 15));
+
+        // Then
+        assert!(matches!(
+            res,
+            Err(ClockError::Timeout {
+                player: Player::White
+            })
+        ));
+        assert_eq!(
+            Some(&GameResult::Timeout {
+                winner: Player::Black
+            }),
+            game.get_result()
+        );
+        assert_eq!(
+            None,
+            game.get_board()
+                .get_player_at(&FlexibleCoordinate { x: 15, y: 15 })
+        );
+        assert!(matches!(
+            game.make_move(&place(Player::Black, 4, 4)),
+            Err(MoveError::GameOver)
+        ));
+    }
+
+    #[test]
+    fn given_canadian_clock_when_checked_during_a_move_then_it_should_flag_once_time_is_up() {
+        // Given
+        let source = ManualClock::new();
+        let mut game = Game::new(BitMaskBoard::new(BitMask19::init));
+        let mut clock = GameClock::new(
+            TimeControl::Canadian {
+                main_time: secs(0),
+                stones: 5,
+                period_time: secs(300),
+            },
+            source.clone(),
+        );
+        clock.start(Player::Black);
+
+        // When
+        source.advance(secs(299));
+        let in_time = clock.check_timeout(&mut game);
+        source.advance(secs(2));
+        let flagged = clock.check_timeout(&mut game);
+
+        // Then
+        assert!(in_time.is_ok());
+        assert!(matches!(flagged, Err(ClockError::Timeout { .. })));
+        assert!(game.is_over());
+    }
+}
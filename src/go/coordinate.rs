@@ -1,6 +1,8 @@
 use crate::go::{bitmask::FlexibleBitMask, board::FlexibleBoard};
 
+/// Serialized as `{"x": 3, "y": 15}`.
 #[derive(Eq, PartialEq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FlexibleCoordinate {
     /// 0-based x-axis position
     pub x: u16,
@@ -0,0 +1,338 @@
+use thiserror::Error;
+
+use crate::go::coordinate::FlexibleCoordinate;
+
+const KANJI_DIGITS: [char; 9] = ['一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+/// Ways to write a point, all of them need the board size to convert to and from a
+/// [`FlexibleCoordinate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateNotation {
+    /// `D4`, a column letter that skips `I` and a row number counted from the bottom. Supports
+    /// boards up to 25 columns, parsing ignores case.
+    Gtp,
+    /// `dd`, column and row letters from the top left, `a` to `z` and then `A` to `Z`. Supports
+    /// boards up to 52x52.
+    Sgf,
+    /// `4-4`, the column and the row counted from 1 at the top left.
+    Numeric,
+    /// `4の四`, the column as a number and the row as a kanji numeral, counted from 1 at the top
+    /// left. Supports boards up to 99 rows, parsing also accepts full width digits.
+    Japanese,
+}
+
+impl CoordinateNotation {
+    pub fn format(
+        &self,
+        coord: &FlexibleCoordinate,
+        board_size: (u16, u16),
+    ) -> Result<String, CoordinateError> {
+        self.check_size(board_size)?;
+        let (width, height) = board_size;
+        if coord.x >= width || coord.y >= height {
+            return Err(CoordinateError::OutOfBoard {
+                text: format!("({}, {})", coord.x, coord.y),
+                width,
+                height,
+            });
+        }
+
+        Ok(match self {
+            CoordinateNotation::Gtp => format!(
+                "{}{}",
+                gtp_column_letter(coord.x).expect("Checked the width"),
+                height - coord.y
+            ),
+            CoordinateNotation::Sgf => format!(
+                "{}{}",
+                sgf_letter(coord.x).expect("Checked the width"),
+                sgf_letter(coord.y).expect("Checked the height")
+            ),
+            CoordinateNotation::Numeric => format!("{}-{}", coord.x + 1, coord.y + 1),
+            CoordinateNotation::Japanese => format!("{}の{}", coord.x + 1, kanji(coord.y + 1)),
+        })
+    }
+
+    pub fn parse(
+        &self,
+        text: &str,
+        board_size: (u16, u16),
+    ) -> Result<FlexibleCoordinate, CoordinateError> {
+        self.check_size(board_size)?;
+        let (width, height) = board_size;
+        let trimmed = text.trim();
+        let invalid = || CoordinateError::Invalid(text.to_string());
+
+        // 1-based column and row, the row is counted from the bottom for GTP.
+        let (column, row) = match self {
+            CoordinateNotation::Gtp => {
+                let mut chars = trimmed.chars();
+                let letter = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+                let column = (0..25)
+                    .find(|x| gtp_column_letter(*x) == Some(letter))
+                    .ok_or_else(invalid)?;
+                let row: u16 = parse_number(chars.as_str()).ok_or_else(invalid)?;
+                if row == 0 || row > height {
+                    return Err(CoordinateError::OutOfBoard {
+                        text: text.to_string(),
+                        width,
+                        height,
+                    });
+                }
+                (column + 1, height - row + 1)
+            }
+            CoordinateNotation::Sgf => match trimmed.as_bytes() {
+                [x, y] => (
+                    sgf_value(*x).ok_or_else(invalid)? + 1,
+                    sgf_value(*y).ok_or_else(invalid)? + 1,
+                ),
+                _ => return Err(invalid()),
+            },
+            CoordinateNotation::Numeric => {
+                let (column, row) = trimmed.split_once('-').ok_or_else(invalid)?;
+                (
+                    parse_number(column).ok_or_else(invalid)?,
+                    parse_number(row).ok_or_else(invalid)?,
+                )
+            }
+            CoordinateNotation::Japanese => {
+                let (column, row) = trimmed.split_once('の').ok_or_else(invalid)?;
+                (
+                    parse_number(column).ok_or_else(invalid)?,
+                    parse_kanji(row)
+                        .or_else(|| parse_number(row))
+                        .ok_or_else(invalid)?,
+                )
+            }
+        };
+
+        if column == 0 || row == 0 || column > width || row > height {
+            return Err(CoordinateError::OutOfBoard {
+                text: text.to_string(),
+                width,
+                height,
+            });
+        }
+        Ok(FlexibleCoordinate {
+            x: column - 1,
+            y: row - 1,
+        })
+    }
+
+    fn check_size(&self, board_size: (u16, u16)) -> Result<(), CoordinateError> {
+        let (width, height) = board_size;
+        let supported = match self {
+            CoordinateNotation::Gtp => width <= 25,
+            CoordinateNotation::Sgf => width <= 52 && height <= 52,
+            CoordinateNotation::Numeric => true,
+            CoordinateNotation::Japanese => height <= 99,
+        };
+        if !supported {
+            return Err(CoordinateError::UnsupportedSize {
+                notation: *self,
+                width,
+                height,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Letter of a GTP column, None past the 25th column.
+pub fn gtp_column_letter(x: u16) -> Option<char> {
+    ('A'..='Z').filter(|x| *x != 'I').nth(x as usize)
+}
+
+/// Letter of an SGF column or row, None past the 52nd.
+pub fn sgf_letter(value: u16) -> Option<char> {
+    match value {
+        0..26 => Some((b'a' + value as u8) as char),
+        26..52 => Some((b'A' + (value - 26) as u8) as char),
+        _ => None,
+    }
+}
+
+fn sgf_value(letter: u8) -> Option<u16> {
+    match letter {
+        b'a'..=b'z' => Some((letter - b'a') as u16),
+        b'A'..=b'Z' => Some((letter - b'A') as u16 + 26),
+        _ => None,
+    }
+}
+
+/// Parses ASCII or full width digits.
+fn parse_number(text: &str) -> Option<u16> {
+    if text.is_empty() {
+        return None;
+    }
+    text.chars().try_fold(0u16, |value, c| {
+        let digit = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            '０'..='９' => c as u32 - '０' as u32,
+            _ => return None,
+        };
+        value.checked_mul(10)?.checked_add(digit as u16)
+    })
+}
+
+fn kanji(value: u16) -> String {
+    let digit = |x: u16| KANJI_DIGITS[x as usize - 1];
+    let (tens, ones) = (value / 10, value % 10);
+    let mut res = String::new();
+    if tens > 1 {
+        res.push(digit(tens));
+    }
+    if tens > 0 {
+        res.push('十');
+    }
+    if ones > 0 {
+        res.push(digit(ones));
+    }
+    res
+}
+
+/// Kanji numerals from 1 to 99, like `四`, `十` and `十九`.
+fn parse_kanji(text: &str) -> Option<u16> {
+    let digit = |c: char| {
+        KANJI_DIGITS
+            .iter()
+            .position(|x| *x == c)
+            .map(|x| x as u16 + 1)
+    };
+    let single = |text: &str| {
+        let mut chars = text.chars();
+        let value = digit(chars.next()?)?;
+        chars.next().is_none().then_some(value)
+    };
+
+    match text.split_once('十') {
+        Some((tens, ones)) => {
+            let tens = if tens.is_empty() { 1 } else { single(tens)? };
+            let ones = if ones.is_empty() { 0 } else { single(ones)? };
+            Some(tens * 10 + ones)
+        }
+        None => single(text),
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CoordinateError {
+    #[error("'{0}' is not a valid coordinate")]
+    Invalid(String),
+    #[error("{text} is outside of the {width}x{height} board")]
+    OutOfBoard {
+        text: String,
+        width: u16,
+        height: u16,
+    },
+    #[error("{notation:?} coordinates do not support boards of {width}x{height}")]
+    UnsupportedSize {
+        notation: CoordinateNotation,
+        width: u16,
+        height: u16,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_upper_right_star_point_when_formatted_then_every_notation_should_parse_back() {
+        // Given
+        let coord = FlexibleCoordinate { x: 15, y: 3 };
+        let expected = [
+            (CoordinateNotation::Gtp, "Q16"),
+            (CoordinateNotation::Sgf, "pd"),
+            (CoordinateNotation::Numeric, "16-4"),
+            (CoordinateNotation::Japanese, "16の四"),
+        ];
+
+        for (notation, text) in expected {
+            // When
+            let formatted = notation
+                .format(&coord, (19, 19))
+                .expect("Expected point on the board");
+            let parsed = notation
+                .parse(text, (19, 19))
+                .expect("Expected notation to parse");
+
+            // Then
+            assert_eq!(text, formatted);
+            assert_eq!(coord, parsed);
+        }
+    }
+
+    #[test]
+    fn given_edge_cases_when_parsed_then_it_should_follow_each_notation() {
+        let gtp = CoordinateNotation::Gtp;
+        let japanese = CoordinateNotation::Japanese;
+
+        assert_eq!(
+            Ok(FlexibleCoordinate { x: 8, y: 0 }),
+            gtp.parse("j19", (19, 19))
+        );
+        assert_eq!(
+            Ok(FlexibleCoordinate { x: 24, y: 0 }),
+            gtp.parse("Z25", (25, 25))
+        );
+        assert_eq!(
+            Ok("A1".to_string()),
+            gtp.format(&FlexibleCoordinate { x: 0, y: 8 }, (13, 9))
+        );
+        assert_eq!(
+            Ok(FlexibleCoordinate { x: 18, y: 18 }),
+            japanese.parse("１９の十九", (19, 19))
+        );
+        assert_eq!(
+            Ok("3の二十二".to_string()),
+            japanese.format(&FlexibleCoordinate { x: 2, y: 21 }, (25, 25))
+        );
+        assert_eq!(
+            Ok(FlexibleCoordinate { x: 30, y: 1 }),
+            CoordinateNotation::Sgf.parse("Eb", (52, 52))
+        );
+    }
+
+    #[test]
+    fn given_invalid_coordinates_when_parsed_then_it_should_say_what_is_wrong() {
+        let gtp = CoordinateNotation::Gtp;
+
+        assert_eq!(
+            Err(CoordinateError::Invalid("I5".to_string())),
+            gtp.parse("I5", (19, 19))
+        );
+        assert_eq!(
+            Err(CoordinateError::OutOfBoard {
+                text: "K10".to_string(),
+                width: 9,
+                height: 9
+            }),
+            gtp.parse("K10", (9, 9))
+        );
+        assert_eq!(
+            Err(CoordinateError::UnsupportedSize {
+                notation: CoordinateNotation::Gtp,
+                width: 26,
+                height: 26
+            }),
+            gtp.parse("A1", (26, 26))
+        );
+        assert_eq!(
+            Err(CoordinateError::Invalid("4-".to_string())),
+            CoordinateNotation::Numeric.parse("4-", (19, 19))
+        );
+        assert_eq!(
+            Err(CoordinateError::Invalid("4の百".to_string())),
+            CoordinateNotation::Japanese.parse("4の百", (19, 19))
+        );
+        assert_eq!(
+            Err(CoordinateError::UnsupportedSize {
+                notation: CoordinateNotation::Japanese,
+                width: 100,
+                height: 100
+            }),
+            CoordinateNotation::Japanese.format(&FlexibleCoordinate { x: 0, y: 99 }, (100, 100))
+        );
+    }
+}
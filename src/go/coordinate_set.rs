@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use crate::go::coordinate::FlexibleCoordinate;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CoordinateSet(HashSet<FlexibleCoordinate>);
 
 impl CoordinateSet {
@@ -10,7 +10,6 @@ impl CoordinateSet {
         CoordinateSet(set)
     }
 
-
     pub fn new(coords: Vec<FlexibleCoordinate>) -> CoordinateSet {
         CoordinateSet(coords.into_iter().collect())
     }
@@ -23,10 +22,6 @@ impl CoordinateSet {
         self.0.iter()
     }
 
-    pub fn into_iter(self) -> impl Iterator<Item = FlexibleCoordinate> {
-        self.0.into_iter()
-    }
-
     pub fn remove(&mut self, coord: &FlexibleCoordinate) -> bool {
         self.0.remove(coord)
     }
@@ -115,6 +110,32 @@ impl CoordinateSet {
     }
 }
 
+impl IntoIterator for CoordinateSet {
+    type Item = FlexibleCoordinate;
+    type IntoIter = std::collections::hash_set::IntoIter<FlexibleCoordinate>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Serialized as an array of coordinates sorted by `y` and then `x`, so the output is stable.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CoordinateSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut sorted: Vec<&FlexibleCoordinate> = self.0.iter().collect();
+        sorted.sort_by_key(|c| (c.y, c.x));
+        serializer.collect_seq(sorted)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CoordinateSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<FlexibleCoordinate>::deserialize(deserializer).map(CoordinateSet::new)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,466 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::go::{
+    bitmask::FlexibleBitMask, bitmask_board::BitMaskBoard, board::FlexibleBoard,
+    coordinate::FlexibleCoordinate, coordinate_notation::gtp_column_letter,
+    dynamic_bitmask::DynamicBitMask, handicap::star_points, player::Player,
+};
+
+/// Text diagram of a position, one row of points per line from the top of the board.
+///
+/// Points are `X` for black, `O` for white and `.` for empty, `+` and `,` are empty star points.
+/// Points may be separated by spaces. Markers use the characters of Sensei's Library: `B` `W`
+/// for circled stones, `#` `@` for squared stones, `Y` `Q` for stones with a triangle, `Z` `P`
+/// for crossed stones, and `C` `S` `T` `M` for a circle, square, triangle or cross on an empty
+/// point. Lowercase letters are labels on empty points.
+///
+/// Rows may start or end with their number counted from the bottom, and a line of column letters
+/// starting with `A` may be put above or below the rows. Columns skip `I`, like GTP.
+///
+/// ```text
+///   A B C D E
+/// 3 . X O . .
+/// 2 . B . a .
+/// 1 . . . . .
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagram {
+    pub width: u16,
+    pub height: u16,
+    /// `position[y][x]`, in the layout of [`BitMaskBoard::from_position`].
+    pub position: Vec<Vec<Option<Player>>>,
+    /// Markers in reading order.
+    pub markers: Vec<(FlexibleCoordinate, Marker)>,
+    /// Whether row numbers and column letters are printed.
+    pub coordinates: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Circle,
+    Square,
+    Triangle,
+    Cross,
+    /// Only shown on empty points.
+    Label(char),
+}
+
+impl Diagram {
+    pub fn parse(diagram: &str) -> Result<Self, DiagramError> {
+        let mut rows: Vec<Vec<(Option<Player>, Option<Marker>)>> = vec![];
+        let mut row_labels = vec![];
+        let mut column_lines = vec![];
+
+        for (index, line) in diagram.lines().enumerate() {
+            let line_number = index + 1;
+            let mut tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.is_empty() {
+                continue;
+            }
+            if tokens[0] == "A" {
+                column_lines.push((line_number, tokens.join(" ")));
+                continue;
+            }
+
+            // Rows can be labelled on both sides, the labels are checked once the height is known.
+            let is_label = |token: &&str| token.bytes().all(|b| b.is_ascii_digit());
+            let trailing_label = if tokens.last().is_some_and(is_label) {
+                tokens.pop()
+            } else {
+                None
+            };
+            let label = if tokens.first().is_some_and(is_label) {
+                Some(tokens.remove(0))
+            } else {
+                None
+            };
+
+            let row = tokens
+                .iter()
+                .flat_map(|token| token.chars())
+                .map(|character| {
+                    parse_point(character).ok_or(DiagramError::InvalidCharacter {
+                        line: line_number,
+                        character,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let expected = rows.first().map_or(row.len(), |first| first.len());
+            if row.is_empty() || row.len() != expected {
+                return Err(DiagramError::RowLength {
+                    line: line_number,
+                    expected,
+                    found: row.len(),
+                });
+            }
+
+            rows.push(row);
+            row_labels.push((line_number, [label, trailing_label]));
+        }
+
+        if rows.is_empty() {
+            return Err(DiagramError::Empty);
+        }
+        let width = rows[0].len() as u16;
+        let height = rows.len() as u16;
+
+        for (y, (line, labels)) in row_labels.iter().enumerate() {
+            let expected = height - y as u16;
+            if let Some(label) = labels
+                .iter()
+                .flatten()
+                .find(|label| label.parse() != Ok(expected))
+            {
+                return Err(DiagramError::RowLabel {
+                    line: *line,
+                    expected,
+                    found: label.to_string(),
+                });
+            }
+        }
+
+        let expected_columns = column_labels(width).unwrap_or_default();
+        for (line, found) in &column_lines {
+            if *found != expected_columns {
+                return Err(DiagramError::ColumnLabels {
+                    line: *line,
+                    expected: expected_columns,
+                    found: found.clone(),
+                });
+            }
+        }
+
+        let mut markers = vec![];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, (_, marker)) in row.iter().enumerate() {
+                if let Some(marker) = marker {
+                    let coord = FlexibleCoordinate {
+                        x: x as u16,
+                        y: y as u16,
+                    };
+                    markers.push((coord, *marker));
+                }
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            position: rows
+                .into_iter()
+                .map(|row| row.into_iter().map(|(player, _)| player).collect())
+                .collect(),
+            markers,
+            coordinates: !column_lines.is_empty() || row_labels.iter().any(|(_, x)| x[0].is_some()),
+        })
+    }
+
+    pub fn from_board<TBoard: FlexibleBoard>(board: &TBoard) -> Self {
+        let (width, height) = board.get_size();
+        Self {
+            width,
+            height,
+            position: (0..height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| board.get_player_at(&FlexibleCoordinate { x, y }))
+                        .collect()
+                })
+                .collect(),
+            markers: vec![],
+            coordinates: false,
+        }
+    }
+
+    /// Builds a board from the stones, the bitmasks from `mask_factory` need to have the size of
+    /// the diagram.
+    pub fn to_board<TBitMask, TMaskFactory>(
+        &self,
+        mask_factory: TMaskFactory,
+    ) -> Result<BitMaskBoard<TBitMask>, DiagramError>
+    where
+        TBitMask: FlexibleBitMask + Eq + PartialEq + std::fmt::Debug + Clone,
+        TMaskFactory: Fn() -> TBitMask,
+    {
+        let size = mask_factory().get_size();
+        if size != (self.width, self.height) {
+            return Err(DiagramError::SizeMismatch {
+                expected: size,
+                found: (self.width, self.height),
+            });
+        }
+
+        Ok(BitMaskBoard::from_position(
+            mask_factory,
+            self.position.clone(),
+        ))
+    }
+
+    pub fn get_marker(&self, coord: &FlexibleCoordinate) -> Option<Marker> {
+        self.markers
+            .iter()
+            .find(|(x, _)| x == coord)
+            .map(|(_, marker)| *marker)
+    }
+}
+
+/// Parses a diagram into a board of its own size.
+pub fn parse_board(diagram: &str) -> Result<BitMaskBoard<DynamicBitMask>, DiagramError> {
+    let diagram = Diagram::parse(diagram)?;
+    let size = (diagram.width, diagram.height);
+    diagram.to_board(|| DynamicBitMask::init(size))
+}
+
+impl FromStr for Diagram {
+    type Err = DiagramError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Diagram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stars = star_points((self.width, self.height));
+        let label_width = self.height.to_string().len();
+
+        if self.coordinates
+            && let Some(columns) = column_labels(self.width)
+        {
+            writeln!(f, "{:label_width$} {columns}", "")?;
+        }
+
+        for (y, row) in self.position.iter().enumerate() {
+            if self.coordinates {
+                write!(f, "{:>label_width$} ", self.height as usize - y)?;
+            }
+            for (x, player) in row.iter().enumerate() {
+                let coord = FlexibleCoordinate {
+                    x: x as u16,
+                    y: y as u16,
+                };
+                if x > 0 {
+                    write!(f, " ")?;
+                }
+                let point = point_char(*player, self.get_marker(&coord), stars.contains(&coord));
+                write!(f, "{point}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_point(character: char) -> Option<(Option<Player>, Option<Marker>)> {
+    let black = Some(Player::Black);
+    let white = Some(Player::White);
+    Some(match character {
+        '.' | '+' | ',' => (None, None),
+        'X' => (black, None),
+        'O' => (white, None),
+        'B' => (black, Some(Marker::Circle)),
+        'W' => (white, Some(Marker::Circle)),
+        '#' => (black, Some(Marker::Square)),
+        '@' => (white, Some(Marker::Square)),
+        'Y' => (black, Some(Marker::Triangle)),
+        'Q' => (white, Some(Marker::Triangle)),
+        'Z' => (black, Some(Marker::Cross)),
+        'P' => (white, Some(Marker::Cross)),
+        'C' => (None, Some(Marker::Circle)),
+        'S' => (None, Some(Marker::Square)),
+        'T' => (None, Some(Marker::Triangle)),
+        'M' => (None, Some(Marker::Cross)),
+        'a'..='z' => (None, Some(Marker::Label(character))),
+        _ => return None,
+    })
+}
+
+fn point_char(player: Option<Player>, marker: Option<Marker>, star: bool) -> char {
+    match (player, marker) {
+        (Some(Player::Black), Some(Marker::Circle)) => 'B',
+        (Some(Player::White), Some(Marker::Circle)) => 'W',
+        (Some(Player::Black), Some(Marker::Square)) => '#',
+        (Some(Player::White), Some(Marker::Square)) => '@',
+        (Some(Player::Black), Some(Marker::Triangle)) => 'Y',
+        (Some(Player::White), Some(Marker::Triangle)) => 'Q',
+        (Some(Player::Black), Some(Marker::Cross)) => 'Z',
+        (Some(Player::White), Some(Marker::Cross)) => 'P',
+        (Some(Player::Black), _) => 'X',
+        (Some(Player::White), _) => 'O',
+        (None, Some(Marker::Circle)) => 'C',
+        (None, Some(Marker::Square)) => 'S',
+        (None, Some(Marker::Triangle)) => 'T',
+        (None, Some(Marker::Cross)) => 'M',
+        (None, Some(Marker::Label(label))) => label,
+        (None, None) if star => '+',
+        (None, None) => '.',
+    }
+}
+
+/// Column letters separated by spaces, None for boards wider than the 25 letters without `I`.
+fn column_labels(width: u16) -> Option<String> {
+    let letters = (0..width)
+        .map(|x| gtp_column_letter(x).map(|x| x.to_string()))
+        .collect::<Option<Vec<_>>>()?;
+    Some(letters.join(" "))
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DiagramError {
+    #[error("The diagram has no rows")]
+    Empty,
+    #[error("Line {line} has an unknown point '{character}'")]
+    InvalidCharacter { line: usize, character: char },
+    #[error("Line {line} has {found} points, expected {expected}")]
+    RowLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Line {line} is labelled as row {found}, expected row {expected}")]
+    RowLabel {
+        line: usize,
+        expected: u16,
+        found: String,
+    },
+    #[error("Line {line} has columns '{found}', expected '{expected}'")]
+    ColumnLabels {
+        line: usize,
+        expected: String,
+        found: String,
+    },
+    #[error("The diagram is {found:?} but the board is {expected:?}")]
+    SizeMismatch {
+        expected: (u16, u16),
+        found: (u16, u16),
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::{
+        bitmask19::BitMask19,
+        player::{B, W},
+    };
+
+    #[test]
+    fn given_diagram_with_coordinates_when_parsed_then_it_should_build_the_board() {
+        // Given
+        let input = "
+  A B C D E F G H J
+9 . . . . . . . . .
+8 . . . . . . . . .
+7 . . + . . . + . .
+6 . . . . . . . . .
+5 . . . . + . . . .
+4 . . . . . . . . .
+3 . . X . . . + . .
+2 . . O B . . . . .
+1 . . . . . . . a .
+";
+
+        // When
+        let diagram = Diagram::parse(input).expect("Expected diagram to parse");
+        let board = diagram
+            .to_board(|| DynamicBitMask::init((9, 9)))
+            .expect("Expected board of the same size");
+
+        // Then
+        assert!(diagram.coordinates);
+        assert_eq!(B, board.get_player_at(&FlexibleCoordinate { x: 2, y: 6 }));
+        assert_eq!(W, board.get_player_at(&FlexibleCoordinate { x: 2, y: 7 }));
+        assert_eq!(B, board.get_player_at(&FlexibleCoordinate { x: 3, y: 7 }));
+        assert_eq!(
+            None,
+            board.get_player_at(&FlexibleCoordinate { x: 2, y: 2 })
+        );
+        assert_eq!(
+            Some(Marker::Circle),
+            diagram.get_marker(&FlexibleCoordinate { x: 3, y: 7 })
+        );
+        assert_eq!(
+            Some(Marker::Label('a')),
+            diagram.get_marker(&FlexibleCoordinate { x: 7, y: 8 })
+        );
+        assert_eq!(input.trim_start_matches('\n'), diagram.to_string());
+    }
+
+    #[test]
+    fn given_rectangular_board_when_printed_then_it_should_parse_back() {
+        // Given
+        let board = parse_board(
+            "XO...
+             ..T..
+             ...OX",
+        )
+        .expect("Expected diagram to parse");
+
+        // When
+        let printed = format!("{:#}", board.display());
+        let res = parse_board(&printed).expect("Expected printed board to parse");
+
+        // Then
+        assert_eq!((5, 3), board.get_size());
+        assert_eq!("X O . . .\n. . . . .\n. . . O X\n", printed);
+        assert_eq!(board, res);
+    }
+
+    #[test]
+    fn given_invalid_diagram_when_parsed_then_it_should_say_what_is_wrong() {
+        assert_eq!(Err(DiagramError::Empty), Diagram::parse("\n\n"));
+        assert_eq!(
+            Err(DiagramError::InvalidCharacter {
+                line: 2,
+                character: '?'
+            }),
+            Diagram::parse(". . .\n. ? .")
+        );
+        assert_eq!(
+            Err(DiagramError::RowLength {
+                line: 2,
+                expected: 3,
+                found: 2
+            }),
+            Diagram::parse(". . .\n. .")
+        );
+        assert_eq!(
+            Err(DiagramError::RowLabel {
+                line: 2,
+                expected: 1,
+                found: "3".to_string()
+            }),
+            Diagram::parse("2 . .\n3 . .")
+        );
+        assert_eq!(
+            Err(DiagramError::RowLabel {
+                line: 1,
+                expected: 2,
+                found: "7".to_string()
+            }),
+            Diagram::parse(". . 7\n. . 1")
+        );
+        assert_eq!(
+            Err(DiagramError::RowLabel {
+                line: 2,
+                expected: 1,
+                found: "2".to_string()
+            }),
+            Diagram::parse("2 . . 2\n1 . . 2")
+        );
+        assert!(matches!(
+            Diagram::parse("A B D\n. . ."),
+            Err(DiagramError::ColumnLabels { line: 1, .. })
+        ));
+        assert_eq!(
+            Err(DiagramError::SizeMismatch {
+                expected: (19, 19),
+                found: (3, 1)
+            }),
+            Diagram::parse(". . .").and_then(|x| x.to_board(BitMask19::init))
+        );
+    }
+}
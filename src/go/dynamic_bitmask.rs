@@ -0,0 +1,127 @@
+use crate::go::{bitmask::FlexibleBitMask, coordinate::FlexibleCoordinate};
+
+/// Bitmask for boards of any size, including rectangular ones, for sizes that have no fixed size
+/// bitmask like [`BitMask19`](crate::go::bitmask19::BitMask19).
+///
+/// Serialized as `{"width": 9, "height": 9, "bits": [...]}` with bit `y * width + x` for the
+/// point at `x`, `y`. Deserializing fails when `bits` does not hold exactly the bits of the board.
+#[derive(Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DynamicBitMaskRepr"))]
+pub struct DynamicBitMask {
+    width: u16,
+    height: u16,
+    bits: Vec<u64>,
+}
+
+impl FlexibleBitMask for DynamicBitMask {
+    fn get_size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    fn get_bit_at(&self, coord: &FlexibleCoordinate) -> bool {
+        let (arr_index, int_index) = self.get_bit_position(coord);
+        (self.bits[arr_index] & 1 << int_index) != 0
+    }
+
+    fn set_bit_at(&mut self, coord: &FlexibleCoordinate, val: bool) {
+        let (arr_index, int_index) = self.get_bit_position(coord);
+        if val {
+            self.bits[arr_index] |= 1 << int_index;
+        } else {
+            self.bits[arr_index] &= !(1 << int_index);
+        }
+    }
+}
+
+impl DynamicBitMask {
+    pub fn init(size: (u16, u16)) -> Self {
+        let (width, height) = size;
+        let len = (width as usize * height as usize).div_ceil(64);
+        Self {
+            width,
+            height,
+            bits: vec![0; len],
+        }
+    }
+
+    fn get_bit_position(&self, coord: &FlexibleCoordinate) -> (usize, usize) {
+        let index = coord.y as usize * self.width as usize + coord.x as usize;
+
+        (index / 64, index % 64)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DynamicBitMaskRepr {
+    width: u16,
+    height: u16,
+    bits: Vec<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DynamicBitMaskRepr> for DynamicBitMask {
+    type Error = String;
+
+    fn try_from(value: DynamicBitMaskRepr) -> Result<Self, Self::Error> {
+        let DynamicBitMaskRepr {
+            width,
+            height,
+            bits,
+        } = value;
+        let len = (width as usize * height as usize).div_ceil(64);
+        if bits.len() != len {
+            return Err(format!(
+                "a {width}x{height} mask needs {len} words of bits, got {}",
+                bits.len()
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_rectangular_mask_when_bits_are_set_then_only_those_bits_are_set() {
+        // Given
+        let mut mask = DynamicBitMask::init((7, 13));
+        let first = FlexibleCoordinate { x: 6, y: 0 };
+        let last = FlexibleCoordinate { x: 6, y: 12 };
+
+        // When
+        mask.set_bit_at(&first, true);
+        mask.set_bit_at(&last, true);
+        mask.set_bit_at(&last, false);
+
+        // Then
+        assert!(mask.get_bit_at(&first));
+        assert!(!mask.get_bit_at(&last));
+        assert!(!mask.get_bit_at(&FlexibleCoordinate { x: 0, y: 1 }));
+        assert_eq!((7, 13), mask.get_size());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_mask_with_wrong_number_of_bits_when_deserialized_then_it_should_be_rejected() {
+        // Given
+        let empty = r#"{"width":19,"height":19,"bits":[]}"#;
+        let valid = r#"{"width":9,"height":9,"bits":[0,1]}"#;
+
+        // When
+        let empty = serde_json::from_str::<DynamicBitMask>(empty);
+        let valid = serde_json::from_str::<DynamicBitMask>(valid);
+
+        // Then
+        assert!(empty.is_err());
+        let valid = valid.expect("Expected mask to deserialize");
+        assert!(valid.get_bit_at(&FlexibleCoordinate { x: 1, y: 7 }));
+    }
+}
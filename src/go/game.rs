@@ -1,16 +1,34 @@
+use std::fmt::Display;
+
 use thiserror::Error;
 
 use crate::go::{
     board::FlexibleBoard,
+    coordinate::FlexibleCoordinate,
+    coordinate_set::CoordinateSet,
+    observer::{GameEvent, GameObserver, ObserverId, Observers},
     player::Player,
-    playermove::{Move, PlaceStoneMove},
+    playermove::{Move, PlaceStoneMove, SetupMove},
 };
 
+/// A game being played on a board.
+///
+/// With the `serde` feature the game serializes as an object with the fields `board`,
+/// `captured_by_black`, `captured_by_white`, `current_player`, `result`, `history` and `ko`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game<TBoard: FlexibleBoard> {
     board: TBoard,
     captured_by_black: u16,
     captured_by_white: u16,
     current_player: Player,
+    result: Option<GameResult>,
+    /// Every move that was made, in order.
+    history: Vec<Move>,
+    /// The point the player to move can not play on because it would retake a ko.
+    ko: Option<FlexibleCoordinate>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observers: Observers,
 }
 
 impl<TBoard: FlexibleBoard> Game<TBoard> {
@@ -20,21 +38,132 @@ impl<TBoard: FlexibleBoard> Game<TBoard> {
             captured_by_black: 0,
             captured_by_white: 0,
             current_player: Player::Black,
+            result: None,
+            history: vec![],
+            ko: None,
+            observers: Observers::default(),
         }
     }
 
-    pub fn make_move(&mut self, m: &Move) -> Result<(), MoveError> {
+    /// Continues from a position without the moves that lead to it, `captures` holds the stones
+    /// captured by black and by white.
+    pub fn from_position(
+        board: TBoard,
+        current_player: Player,
+        ko: Option<FlexibleCoordinate>,
+        captures: (u16, u16),
+    ) -> Self {
+        Game {
+            board,
+            captured_by_black: captures.0,
+            captured_by_white: captures.1,
+            current_player,
+            result: None,
+            history: vec![],
+            ko,
+            observers: Observers::default(),
+        }
+    }
+
+    /// Makes a move and returns what it changed on the board.
+    pub fn make_move(&mut self, m: &Move) -> Result<MoveOutcome, MoveError> {
+        if self.result.is_some() {
+            return Err(MoveError::GameOver);
+        }
+
+        let previous_ko = self.ko;
+        let (captured, removed) = self.apply_move(m)?;
+        self.history.push(m.clone());
+        let outcome = MoveOutcome {
+            m: m.clone(),
+            captured,
+            removed,
+            previous_ko,
+            ko: self.ko,
+            captured_by_black: self.captured_by_black,
+            captured_by_white: self.captured_by_white,
+        };
+
+        if !self.observers.is_empty() {
+            let events = self.move_events(&outcome);
+            self.observers.notify(&events);
+        }
+        Ok(outcome)
+    }
+
+    /// Events of a move in the order described on [`GameEvent`].
+    fn move_events(&self, outcome: &MoveOutcome) -> Vec<GameEvent> {
+        let mut events = vec![GameEvent::MovePlayed(outcome.clone())];
+        match &outcome.m {
+            Move::PlaceStone(PlaceStoneMove { player, coord }) => {
+                for stones in &outcome.captured {
+                    events.push(GameEvent::Captured {
+                        player: !*player,
+                        stones: stones.clone(),
+                    });
+                }
+                if let Some(at) = outcome.ko {
+                    events.push(GameEvent::KoCreated { at });
+                }
+
+                // Reading order, so groups in atari come in the same order every time.
+                let (width, height) = self.board.get_size();
+                let (x, y) = (coord.x, coord.y);
+                let neighbours = [
+                    (y > 0).then(|| FlexibleCoordinate { x, y: y - 1 }),
+                    (x > 0).then(|| FlexibleCoordinate { x: x - 1, y }),
+                    (x + 1 < width).then_some(FlexibleCoordinate { x: x + 1, y }),
+                    (y + 1 < height).then_some(FlexibleCoordinate { x, y: y + 1 }),
+                ];
+                let mut seen = CoordinateSet::new(vec![]);
+                for neighbour in neighbours.into_iter().flatten() {
+                    if seen.contains(&neighbour)
+                        || self.board.get_player_at(&neighbour) != Some(!*player)
+                    {
+                        continue;
+                    }
+                    let group = self
+                        .board
+                        .find_group(&neighbour)
+                        .expect("There is a stone on the neighbour");
+                    let liberties = self.board.get_liberties(&group);
+                    for stone in group.coordinates.iter() {
+                        seen.insert(*stone);
+                    }
+                    if liberties.len() == 1 {
+                        events.push(GameEvent::Atari {
+                            player: group.player,
+                            liberty: *liberties.iter().next().expect("One liberty"),
+                            stones: group.coordinates,
+                        });
+                    }
+                }
+            }
+            Move::Skip { player } => events.push(GameEvent::Pass { player: *player }),
+            Move::Setup(_) => {}
+        }
+        events
+    }
+
+    /// Returns the captured groups and the stones removed by a setup move.
+    fn apply_move(&mut self, m: &Move) -> Result<(Vec<CoordinateSet>, CoordinateSet), MoveError> {
         match m {
             Move::PlaceStone(place_stone_move) => {
                 let PlaceStoneMove { coord, player } = place_stone_move;
-                let occupying_player = self.board.get_player_at(&coord);
+                if !coord.is_in_board(&self.board) {
+                    return Err(MoveError::OutOfBoard { coord: *coord });
+                }
+                let occupying_player = self.board.get_player_at(coord);
                 if let Some(occupied_by) = occupying_player {
                     return Err(MoveError::CoordinateOccupied { occupied_by });
                 }
+                if self.ko == Some(*coord) && *player == self.current_player {
+                    return Err(MoveError::Ko);
+                }
 
                 let groups_to_capture = self
                     .board
-                    .find_groups_to_capture_from_move(&place_stone_move);
+                    .find_groups_to_capture_from_move(place_stone_move);
 
                 if groups_to_capture.is_empty() {
                     let is_suicide = self.board.is_potential_suicide(place_stone_move);
@@ -45,13 +174,24 @@ impl<TBoard: FlexibleBoard> Game<TBoard> {
                 }
 
                 let mut captured = 0;
+                let mut captured_stone = None;
+                let mut captured_groups = vec![];
 
                 for group in groups_to_capture {
+                    captured_stone = group.coordinates.iter().next().copied();
                     captured += self
                         .board
                         .capture(&group.coordinates)
                         .expect("Expected capture to work");
+                    captured_groups.push(group.coordinates);
                 }
+                captured_groups.sort_by_key(|group| {
+                    group
+                        .iter()
+                        .map(|x| (x.y, x.x))
+                        .min()
+                        .expect("Groups have stones")
+                });
 
                 match player {
                     Player::Black => self.captured_by_black += captured,
@@ -59,21 +199,247 @@ impl<TBoard: FlexibleBoard> Game<TBoard> {
                 };
 
                 self.board
-                    .set_player_at(&coord, &player)
+                    .set_player_at(coord, player)
                     .expect("Already checked whether spot is occupied or not");
 
-                Ok(())
+                // Taking a single stone with a single stone that is left in atari is a ko, the
+                // opponent can not take back right away.
+                self.ko = captured_stone.filter(|_| {
+                    let group = self
+                        .board
+                        .find_group(coord)
+                        .expect("Stone was placed right before");
+                    captured == 1
+                        && group.coordinates.len() == 1
+                        && self.board.get_liberties(&group).len() == 1
+                });
+
+                self.current_player = !*player;
+                Ok((captured_groups, CoordinateSet::new(vec![])))
             }
-            Move::Skip { .. } => {
-                self.current_player = !self.current_player;
-                Ok(())
-            },
+            Move::Skip { player } => {
+                self.ko = None;
+                self.current_player = !*player;
+                Ok((vec![], CoordinateSet::new(vec![])))
+            }
+            Move::Setup(setup_move) => {
+                let SetupMove {
+                    add_black,
+                    add_white,
+                    clear,
+                    ..
+                } = setup_move;
+                if let Some(coord) = clear
+                    .iter()
+                    .chain(add_black.iter())
+                    .chain(add_white.iter())
+                    .find(|x| !x.is_in_board(&self.board))
+                {
+                    return Err(MoveError::OutOfBoard { coord: *coord });
+                }
+                self.ko = None;
+                Ok((vec![], self.apply_setup(setup_move)))
+            }
+        }
+    }
+
+    /// Places and removes stones as described by the setup move, without captures or any other
+    /// move rules. Stones that are already on the board are replaced, the removed stones are
+    /// returned.
+    fn apply_setup(&mut self, setup_move: &SetupMove) -> CoordinateSet {
+        let SetupMove {
+            add_black,
+            add_white,
+            clear,
+            player_to_move,
+        } = setup_move;
+
+        let mut removed = CoordinateSet::new(vec![]);
+        for coord in clear.iter().chain(add_black.iter()).chain(add_white.iter()) {
+            if self.board.get_player_at(coord).is_some() {
+                self.board
+                    .clear_at(coord)
+                    .expect("Already checked whether spot is occupied or not");
+                removed.insert(*coord);
+            }
+        }
+
+        for (coords, player) in [(add_black, Player::Black), (add_white, Player::White)] {
+            for coord in coords.iter() {
+                self.board
+                    .set_player_at(coord, &player)
+                    .expect("Spot was cleared before placing setup stones");
+            }
+        }
+
+        if let Some(player) = player_to_move {
+            self.current_player = *player;
+        }
+        removed
+    }
+
+    pub fn get_current_player(&self) -> Player {
+        self.current_player
+    }
+
+    /// Amount of opponent stones the player has captured.
+    pub fn get_captures(&self, player: Player) -> u16 {
+        match player {
+            Player::Black => self.captured_by_black,
+            Player::White => self.captured_by_white,
         }
     }
 
     pub fn get_board(&self) -> &TBoard {
         &self.board
     }
+
+    /// Every move that was made, in order.
+    pub fn get_history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// The point the player to move can not play on because of a ko, if any.
+    pub fn get_ko(&self) -> Option<FlexibleCoordinate> {
+        self.ko
+    }
+
+    /// Ends the game, no moves can be made afterwards.
+    pub fn end(&mut self, result: GameResult) {
+        let mut events = vec![];
+        if let GameResult::Resignation { winner } = result {
+            events.push(GameEvent::Resignation { player: !winner });
+        }
+        events.push(GameEvent::GameEnded(result.clone()));
+        self.result = Some(result);
+        self.observers.notify(&events);
+    }
+
+    /// Calls `observer` with every event from now on, after the observers that were added
+    /// before it. Clones of the game do not keep the observers.
+    pub fn add_observer(&mut self, observer: impl GameObserver + 'static) -> ObserverId {
+        self.observers.add(Box::new(observer))
+    }
+
+    /// Returns whether the observer was still there.
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    pub fn get_result(&self) -> Option<&GameResult> {
+        self.result.as_ref()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+}
+
+/// What a move changed, so a view can follow a game without comparing boards.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveOutcome {
+    /// The move that was made.
+    pub m: Move,
+    /// Stones taken off the board by the move, one set per captured group in reading order of
+    /// their first stone.
+    pub captured: Vec<CoordinateSet>,
+    /// Stones a setup move took off the board, including stones it replaced.
+    pub removed: CoordinateSet,
+    /// The ko point before the move.
+    pub previous_ko: Option<FlexibleCoordinate>,
+    /// The ko point after the move.
+    pub ko: Option<FlexibleCoordinate>,
+    pub captured_by_black: u16,
+    pub captured_by_white: u16,
+}
+
+impl MoveOutcome {
+    /// The stone that was placed, None for passes and setup moves.
+    pub fn placed(&self) -> Option<&PlaceStoneMove> {
+        match &self.m {
+            Move::PlaceStone(place_stone_move) => Some(place_stone_move),
+            _ => None,
+        }
+    }
+
+    /// Amount of stones captured by the move.
+    pub fn captured_count(&self) -> u16 {
+        self.captured.iter().map(|x| x.len()).sum()
+    }
+
+    pub fn ko_changed(&self) -> bool {
+        self.previous_ko != self.ko
+    }
+}
+
+/// Serialized as `{"type": "resignation", "winner": "black"}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum GameResult {
+    Resignation {
+        winner: Player,
+    },
+    Timeout {
+        winner: Player,
+    },
+    /// The loser broke the rules, for example by playing an illegal move.
+    Forfeit {
+        winner: Player,
+    },
+    /// Counted at the end, None is a draw.
+    Score {
+        winner: Option<Player>,
+        margin: f64,
+    },
+}
+
+impl GameResult {
+    /// The result of a count where black is `margin` points ahead, negative when white is.
+    pub fn from_margin(margin: f64) -> Self {
+        let winner = if margin > 0.0 {
+            Some(Player::Black)
+        } else if margin < 0.0 {
+            Some(Player::White)
+        } else {
+            None
+        };
+        GameResult::Score {
+            winner,
+            margin: margin.abs(),
+        }
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Resignation { winner }
+            | GameResult::Timeout { winner }
+            | GameResult::Forfeit { winner } => Some(*winner),
+            GameResult::Score { winner, .. } => *winner,
+        }
+    }
+}
+
+impl Display for GameResult {
+    /// Formats the result like the SGF `RE` property, e.g. `W+R`, `B+T`, `B+F`, `W+6.5` or `0`
+    /// for a draw.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let winner = |player: &Player| match player {
+            Player::Black => "B",
+            Player::White => "W",
+        };
+        match self {
+            GameResult::Resignation { winner: player } => write!(f, "{}+R", winner(player)),
+            GameResult::Timeout { winner: player } => write!(f, "{}+T", winner(player)),
+            GameResult::Forfeit { winner: player } => write!(f, "{}+F", winner(player)),
+            GameResult::Score {
+                winner: Some(player),
+                margin,
+            } => write!(f, "{}+{margin}", winner(player)),
+            GameResult::Score { winner: None, .. } => write!(f, "0"),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -82,6 +448,12 @@ pub enum MoveError {
     CoordinateOccupied { occupied_by: Player },
     #[error("Killing yourself is not nice, we'd like for you to live thank you.")]
     Suicide,
+    #[error("The game is over, no more moves can be made.")]
+    GameOver,
+    #[error("Can not retake the ko right away, play somewhere else first.")]
+    Ko,
+    #[error("Can not place a stone at ({}, {}), it is outside of the board.", coord.x, coord.y)]
+    OutOfBoard { coord: FlexibleCoordinate },
 }
 
 #[cfg(test)]
@@ -90,11 +462,263 @@ mod test {
         bitmask::TestMask,
         bitmask_board::BitMaskBoard,
         coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
         player::{B, W},
     };
 
     use super::*;
 
+    #[test]
+    fn given_game_with_stones_when_setup_move_is_made_then_it_should_replace_stones_without_captures()
+     {
+        // Given
+        let e = None;
+        let position = vec![
+            vec![e, B, e, e, e, e, e, e, e],
+            vec![B, W, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+        ];
+        let board = BitMaskBoard::from_position(|| TestMask::empty((9, 9)), position);
+        let mut game = Game::new(board);
+
+        // When
+        let res = game.make_move(&Move::Setup(SetupMove {
+            add_black: CoordinateSet::set(&[(1, 1), (2, 1)]),
+            add_white: CoordinateSet::set(&[(0, 0)]),
+            clear: CoordinateSet::set(&[(0, 1)]),
+            player_to_move: Some(Player::White),
+        }));
+
+        // Then
+        assert!(res.is_ok());
+
+        let expected_position = vec![
+            vec![W, B, e, e, e, e, e, e, e],
+            vec![e, B, B, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+            vec![e, e, e, e, e, e, e, e, e],
+        ];
+        let expected_board =
+            BitMaskBoard::from_position(|| TestMask::empty((9, 9)), expected_position);
+
+        assert_eq!(&expected_board, game.get_board());
+        assert_eq!(0, game.captured_by_black);
+        assert_eq!(0, game.captured_by_white);
+        assert_eq!(Player::White, game.get_current_player());
+    }
+
+    fn place(player: Player, x: u16, y: u16) -> Move {
+        Move::PlaceStone(PlaceStoneMove {
+            player,
+            coord: FlexibleCoordinate { x, y },
+        })
+    }
+
+    fn ko_position() -> Vec<Vec<Option<Player>>> {
+        let e = None;
+        let mut position = vec![vec![e; 9]; 9];
+        position[0][1] = W;
+        position[0][2] = B;
+        position[1][0] = W;
+        position[1][2] = W;
+        position[1][3] = B;
+        position[2][1] = W;
+        position[2][2] = B;
+        position
+    }
+
+    #[test]
+    fn given_ko_when_opponent_retakes_right_away_then_it_should_be_refused() {
+        // Given
+        let board = BitMaskBoard::from_position(|| TestMask::empty((9, 9)), ko_position());
+        let mut game = Game::new(board);
+        game.make_move(&place(Player::Black, 1, 1))
+            .expect("Expected ko capture to be allowed");
+
+        // When
+        let retake = game.make_move(&place(Player::White, 2, 1));
+        game.make_move(&place(Player::White, 8, 8))
+            .expect("Expected move elsewhere to be allowed");
+        game.make_move(&place(Player::Black, 8, 6))
+            .expect("Expected move elsewhere to be allowed");
+        let later_retake = game.make_move(&place(Player::White, 2, 1));
+
+        // Then
+        assert!(matches!(retake, Err(MoveError::Ko)));
+        assert!(later_retake.is_ok());
+        assert_eq!(Some(FlexibleCoordinate { x: 1, y: 1 }), game.get_ko());
+        assert_eq!(4, game.get_history().len());
+        assert_eq!(1, game.get_captures(Player::Black));
+        assert_eq!(1, game.get_captures(Player::White));
+    }
+
+    #[test]
+    fn given_move_outside_of_the_board_when_made_then_it_should_be_refused() {
+        // Given
+        let board = BitMaskBoard::from_position(|| TestMask::empty((9, 9)), vec![vec![None; 9]; 9]);
+        let mut game = Game::new(board);
+
+        // When
+        let res = game.make_move(&place(Player::Black, 9, 0));
+        let setup = game.make_move(&Move::Setup(SetupMove {
+            add_black: CoordinateSet::set(&[(0, 0), (2, 9)]),
+            add_white: CoordinateSet::new(vec![]),
+            clear: CoordinateSet::new(vec![]),
+            player_to_move: None,
+        }));
+
+        // Then
+        assert!(matches!(
+            res,
+            Err(MoveError::OutOfBoard {
+                coord: FlexibleCoordinate { x: 9, y: 0 }
+            })
+        ));
+        assert!(matches!(setup, Err(MoveError::OutOfBoard { .. })));
+        assert_eq!(
+            None,
+            game.get_board()
+                .get_player_at(&FlexibleCoordinate { x: 0, y: 0 })
+        );
+        assert!(game.get_history().is_empty());
+    }
+
+    #[test]
+    fn given_group_touching_the_move_twice_when_captured_then_it_should_be_taken_once() {
+        // Given
+        let e = None;
+        let mut position = vec![vec![e; 9]; 9];
+        position[0][0] = W;
+        position[0][1] = W;
+        position[1][0] = W;
+        position[0][2] = B;
+        position[2][0] = B;
+        let board = BitMaskBoard::from_position(|| TestMask::empty((9, 9)), position);
+        let mut game = Game::new(board);
+
+        // When
+        game.make_move(&place(Player::Black, 1, 1))
+            .expect("Expected capture to be allowed");
+
+        // Then
+        let mut expected_position = vec![vec![e; 9]; 9];
+        expected_position[0][2] = B;
+        expected_position[1][1] = B;
+        expected_position[2][0] = B;
+        let expected_board =
+            BitMaskBoard::from_position(|| TestMask::empty((9, 9)), expected_position);
+        assert_eq!(&expected_board, game.get_board());
+        assert_eq!(3, game.get_captures(Player::Black));
+    }
+
+    #[test]
+    fn given_two_groups_in_atari_when_both_are_captured_then_outcome_should_list_each_group() {
+        // Given
+        let e = None;
+        let mut position = vec![vec![e; 9]; 9];
+        position[0][0] = W;
+        position[0][2] = W;
+        position[0][3] = B;
+        position[1][0] = B;
+        position[1][2] = B;
+        let board = BitMaskBoard::from_position(|| TestMask::empty((9, 9)), position);
+        let mut game = Game::new(board);
+
+        // When
+        let outcome = game
+            .make_move(&place(Player::Black, 1, 0))
+            .expect("Expected capture to be allowed");
+
+        // Then
+        assert_eq!(2, outcome.captured.len());
+        assert!(outcome.captured.contains(&CoordinateSet::set(&[(0, 0)])));
+        assert!(outcome.captured.contains(&CoordinateSet::set(&[(2, 0)])));
+        assert_eq!(2, outcome.captured_count());
+        assert_eq!(2, outcome.captured_by_black);
+        assert_eq!(None, outcome.ko);
+        assert!(!outcome.ko_changed());
+        assert!(outcome.removed.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_game_with_history_and_ko_when_serialized_then_it_should_round_trip() {
+        use crate::go::bitmask19::BitMask19;
+
+        // Given
+        let mut game = Game::new(BitMaskBoard::new(BitMask19::init));
+        let moves = [
+            Move::Setup(SetupMove {
+                add_black: CoordinateSet::set(&[(2, 0), (3, 1), (2, 2)]),
+                add_white: CoordinateSet::set(&[(1, 0), (0, 1), (2, 1), (1, 2)]),
+                clear: CoordinateSet::new(vec![]),
+                player_to_move: None,
+            }),
+            place(Player::Black, 1, 1),
+        ];
+        for m in &moves {
+            game.make_move(m).expect("Expected move to be allowed");
+        }
+
+        // When
+        let json = serde_json::to_string(&game).expect("Expected game to serialize");
+        let mut restored: Game<BitMaskBoard<BitMask19>> =
+            serde_json::from_str(&json).expect("Expected game to deserialize");
+
+        // Then
+        assert!(json.contains(r#""current_player":"white""#));
+        assert!(json.contains(r#""ko":{"x":2,"y":1}"#));
+        assert!(json.contains(r#"{"type":"place_stone","player":"black","coord":{"x":1,"y":1}}"#));
+        assert!(json.contains(r#""add_black":[{"x":2,"y":0},{"x":3,"y":1},{"x":2,"y":2}]"#));
+        assert_eq!(game.get_board(), restored.get_board());
+        assert_eq!(game.get_history(), restored.get_history());
+        assert_eq!(game.get_ko(), restored.get_ko());
+        assert_eq!(1, restored.get_captures(Player::Black));
+        assert!(matches!(
+            restored.make_move(&place(Player::White, 2, 1)),
+            Err(MoveError::Ko)
+        ));
+        assert_eq!(json, serde_json::to_string(&restored).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn given_finished_game_when_serialized_then_result_and_passes_should_round_trip() {
+        // Given
+        let mut game = Game::new(BitMaskBoard::new(|| {
+            crate::go::dynamic_bitmask::DynamicBitMask::init((9, 13))
+        }));
+        game.make_move(&Move::Skip {
+            player: Player::Black,
+        })
+        .unwrap();
+        game.end(GameResult::Resignation {
+            winner: Player::White,
+        });
+
+        // When
+        let json = serde_json::to_string(&game).expect("Expected game to serialize");
+        let restored: Game<BitMaskBoard<crate::go::dynamic_bitmask::DynamicBitMask>> =
+            serde_json::from_str(&json).expect("Expected game to deserialize");
+
+        // Then
+        assert!(json.contains(r#""result":{"type":"resignation","winner":"white"}"#));
+        assert!(json.contains(r#""history":[{"type":"skip","player":"black"}]"#));
+        assert_eq!(game.get_result(), restored.get_result());
+        assert_eq!((9, 13), restored.get_board().get_size());
+    }
+
     #[test]
     fn given_empty_game_when_make_move_is_called_then_it_should_place_the_stone() {
         // Given
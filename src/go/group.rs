@@ -1,6 +1,8 @@
 use crate::go::{coordinate_set::CoordinateSet, player::Player};
 
-#[derive(Debug)]
+/// Serialized as `{"player": "black", "coordinates": [...]}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {
     pub player: Player,
     pub coordinates: CoordinateSet,
@@ -0,0 +1,158 @@
+use thiserror::Error;
+
+use crate::go::{coordinate::FlexibleCoordinate, coordinate_set::CoordinateSet};
+
+/// Coordinates of the handicap stones for a fixed handicap, using the same placement as GNU Go
+/// and most servers.
+///
+/// Boards need to be at least 7x7. Only boards with an odd width and height of at least 9 have a
+/// center point and side star points, so they are the only boards that allow more than 4 stones.
+pub fn fixed_handicap_placement(
+    board_size: (u16, u16),
+    stones: u8,
+) -> Result<CoordinateSet, HandicapPlacementError> {
+    let (width, height) = board_size;
+    if width < 7 || height < 7 {
+        return Err(HandicapPlacementError::BoardTooSmall);
+    }
+
+    let has_center = width % 2 == 1 && height % 2 == 1 && width >= 9 && height >= 9;
+    let max_stones = if has_center { 9 } else { 4 };
+    if !(2..=max_stones).contains(&stones) {
+        return Err(HandicapPlacementError::InvalidStoneCount {
+            stones,
+            max: max_stones,
+        });
+    }
+
+    let edge = |size: u16| if size >= 13 { 3 } else { 2 };
+    let (left, top) = (edge(width), edge(height));
+    let (right, bottom) = (width - 1 - left, height - 1 - top);
+    let (center_x, center_y) = (width / 2, height / 2);
+
+    let mut coords = vec![(left, bottom), (right, top)];
+    if stones >= 3 {
+        coords.push((right, bottom));
+    }
+    if stones >= 4 {
+        coords.push((left, top));
+    }
+    if stones >= 6 {
+        coords.push((left, center_y));
+        coords.push((right, center_y));
+    }
+    if stones >= 8 {
+        coords.push((center_x, top));
+        coords.push((center_x, bottom));
+    }
+    if stones % 2 == 1 && stones >= 5 {
+        coords.push((center_x, center_y));
+    }
+
+    Ok(CoordinateSet::new(
+        coords
+            .into_iter()
+            .map(|(x, y)| FlexibleCoordinate { x, y })
+            .collect(),
+    ))
+}
+
+/// Star points that are marked on a board of this size, the same corner points as
+/// [`fixed_handicap_placement`], the center of odd boards of at least 9x9 and the side points of
+/// odd boards of at least 15x15.
+pub fn star_points(board_size: (u16, u16)) -> CoordinateSet {
+    let (width, height) = board_size;
+    if width < 7 || height < 7 {
+        return CoordinateSet::new(vec![]);
+    }
+
+    let edge = |size: u16| if size >= 13 { 3 } else { 2 };
+    let (left, top) = (edge(width), edge(height));
+    let (right, bottom) = (width - 1 - left, height - 1 - top);
+    let (center_x, center_y) = (width / 2, height / 2);
+    let odd = width % 2 == 1 && height % 2 == 1;
+
+    let mut coords = vec![(left, top), (right, top), (left, bottom), (right, bottom)];
+    if odd && width >= 9 && height >= 9 {
+        coords.push((center_x, center_y));
+    }
+    if odd && width >= 15 && height >= 15 {
+        coords.extend([
+            (center_x, top),
+            (center_x, bottom),
+            (left, center_y),
+            (right, center_y),
+        ]);
+    }
+
+    CoordinateSet::new(
+        coords
+            .into_iter()
+            .map(|(x, y)| FlexibleCoordinate { x, y })
+            .collect(),
+    )
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum HandicapPlacementError {
+    #[error("Fixed handicap is only defined for boards of at least 7x7")]
+    BoardTooSmall,
+    #[error("Can not place {stones} handicap stones, expected between 2 and {max}")]
+    InvalidStoneCount { stones: u8, max: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_19x19_board_when_placing_handicap_then_it_should_use_star_points() {
+        // Given
+        let size = (19, 19);
+
+        // When
+        let two = fixed_handicap_placement(size, 2).expect("Expected placement");
+        let three = fixed_handicap_placement(size, 3).expect("Expected placement");
+        let nine = fixed_handicap_placement(size, 9).expect("Expected placement");
+
+        // Then
+        assert!(two.equals(&CoordinateSet::set(&[(3, 15), (15, 3)])));
+        assert!(three.equals(&CoordinateSet::set(&[(3, 15), (15, 3), (15, 15)])));
+        assert!(nine.equals(&CoordinateSet::set(&[
+            (3, 3),
+            (3, 9),
+            (3, 15),
+            (9, 3),
+            (9, 9),
+            (9, 15),
+            (15, 3),
+            (15, 9),
+            (15, 15)
+        ])));
+    }
+
+    #[test]
+    fn given_9x9_board_when_placing_handicap_then_it_should_use_third_line() {
+        let res = fixed_handicap_placement((9, 9), 5).expect("Expected placement");
+
+        assert!(res.equals(&CoordinateSet::set(&[
+            (2, 6),
+            (6, 2),
+            (6, 6),
+            (2, 2),
+            (4, 4)
+        ])));
+    }
+
+    #[test]
+    fn given_even_board_when_placing_more_than_four_stones_then_it_should_fail() {
+        assert_eq!(
+            Err(HandicapPlacementError::InvalidStoneCount { stones: 5, max: 4 }),
+            fixed_handicap_placement((10, 10), 5).map(|x| x.len())
+        );
+        assert_eq!(
+            Err(HandicapPlacementError::BoardTooSmall),
+            fixed_handicap_placement((5, 5), 2).map(|x| x.len())
+        );
+    }
+}
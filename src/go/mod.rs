@@ -1,10 +1,23 @@
+pub mod any_game;
 pub mod bitmask;
 pub mod bitmask19;
 pub mod bitmask_board;
 pub mod board;
+pub mod clock;
 pub mod coordinate;
+pub mod coordinate_notation;
 pub mod coordinate_set;
+pub mod diagram;
+pub mod dynamic_bitmask;
 pub mod game;
 pub mod group;
+pub mod handicap;
+pub mod observer;
 pub mod player;
 pub mod playermove;
+pub mod position;
+pub mod rank;
+pub mod render;
+pub mod rules;
+pub mod score;
+pub mod time_control;
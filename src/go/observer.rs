@@ -0,0 +1,245 @@
+use std::fmt::Debug;
+
+use crate::go::{
+    coordinate::FlexibleCoordinate,
+    coordinate_set::CoordinateSet,
+    game::{GameResult, MoveOutcome},
+    player::Player,
+};
+
+/// Something that happened in a [`Game`](crate::go::game::Game).
+///
+/// A move fires [`GameEvent::MovePlayed`] first, followed by [`GameEvent::Captured`] for every
+/// captured group, [`GameEvent::KoCreated`] and [`GameEvent::Atari`] for every group the move put
+/// in atari, or by [`GameEvent::Pass`] for a pass. Ending a game fires
+/// [`GameEvent::Resignation`] for a resignation followed by [`GameEvent::GameEnded`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// Any move, including passes and setup moves.
+    MovePlayed(MoveOutcome),
+    /// A group of `player`'s stones was captured.
+    Captured {
+        player: Player,
+        stones: CoordinateSet,
+    },
+    Pass {
+        player: Player,
+    },
+    Resignation {
+        player: Player,
+    },
+    /// The player to move can not play on `at` because of a ko.
+    KoCreated {
+        at: FlexibleCoordinate,
+    },
+    /// A group of `player`'s stones next to the move has a single liberty left.
+    Atari {
+        player: Player,
+        stones: CoordinateSet,
+        liberty: FlexibleCoordinate,
+    },
+    GameEnded(GameResult),
+}
+
+/// Receives the events of a game, closures taking a [`GameEvent`] are observers too.
+///
+/// Observers are `Send + Sync` so games can be shared between threads.
+pub trait GameObserver: Send + Sync {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+impl<F: FnMut(&GameEvent) + Send + Sync> GameObserver for F {
+    fn on_event(&mut self, event: &GameEvent) {
+        self(event)
+    }
+}
+
+/// Returned when adding an observer, to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+/// The observers of a game, called in the order they were added.
+///
+/// Cloning gives an empty list, observers stay with the game they were added to.
+#[derive(Default)]
+pub struct Observers {
+    next_id: u64,
+    observers: Vec<(ObserverId, Box<dyn GameObserver>)>,
+}
+
+impl Observers {
+    pub fn add(&mut self, observer: Box<dyn GameObserver>) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        self.observers.push((id, observer));
+        id
+    }
+
+    /// Returns whether the observer was still there.
+    pub fn remove(&mut self, id: ObserverId) -> bool {
+        let len = self.observers.len();
+        self.observers.retain(|(x, _)| *x != id);
+        self.observers.len() != len
+    }
+
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    /// Sends every event to every observer, all observers see an event before the next one is
+    /// sent.
+    pub fn notify(&mut self, events: &[GameEvent]) {
+        for event in events {
+            for (_, observer) in &mut self.observers {
+                observer.on_event(event);
+            }
+        }
+    }
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Debug for Observers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Observers({})", self.observers.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::go::{
+        any_game::AnyGame,
+        bitmask::TestMask,
+        bitmask_board::BitMaskBoard,
+        bitmask19::BitMask19,
+        game::Game,
+        player::{B, W},
+        playermove::{Move, PlaceStoneMove},
+    };
+
+    fn place(player: Player, x: u16, y: u16) -> Move {
+        Move::PlaceStone(PlaceStoneMove {
+            player,
+            coord: FlexibleCoordinate { x, y },
+        })
+    }
+
+    fn recorder(log: &Arc<Mutex<Vec<String>>>, name: &'static str) -> impl GameObserver + use<> {
+        let log = log.clone();
+        move |event: &GameEvent| {
+            let text = match event {
+                GameEvent::MovePlayed(_) => "move".to_string(),
+                GameEvent::Captured { player, stones } => {
+                    format!("captured {player:?} {}", stones.len())
+                }
+                GameEvent::Pass { player } => format!("pass {player:?}"),
+                GameEvent::Resignation { player } => format!("resignation {player:?}"),
+                GameEvent::KoCreated { at } => format!("ko {},{}", at.x, at.y),
+                GameEvent::Atari {
+                    player, liberty, ..
+                } => {
+                    format!("atari {player:?} {},{}", liberty.x, liberty.y)
+                }
+                GameEvent::GameEnded(result) => format!("ended {result}"),
+            };
+            log.lock()
+                .expect("Expected lock")
+                .push(format!("{name}: {text}"));
+        }
+    }
+
+    #[test]
+    fn given_two_observers_when_ko_is_taken_then_both_should_see_the_events_in_order() {
+        // Given
+        let e = None;
+        let mut position = vec![vec![e; 9]; 9];
+        position[0][1] = W;
+        position[0][2] = B;
+        position[1][0] = W;
+        position[1][2] = W;
+        position[1][3] = B;
+        position[2][1] = W;
+        position[2][2] = B;
+        let mut game = Game::new(BitMaskBoard::from_position(
+            || TestMask::empty((9, 9)),
+            position,
+        ));
+        let log = Arc::new(Mutex::new(vec![]));
+        game.add_observer(recorder(&log, "a"));
+        game.add_observer(recorder(&log, "b"));
+
+        // When
+        game.make_move(&place(Player::Black, 1, 1))
+            .expect("Expected ko capture to be allowed");
+        game.make_move(&Move::Skip {
+            player: Player::White,
+        })
+        .expect("Expected pass to be allowed");
+
+        // Then
+        let expected = [
+            "a: move",
+            "b: move",
+            "a: captured White 1",
+            "b: captured White 1",
+            "a: ko 2,1",
+            "b: ko 2,1",
+            "a: atari White 0,0",
+            "b: atari White 0,0",
+            "a: move",
+            "b: move",
+            "a: pass White",
+            "b: pass White",
+        ];
+        assert_eq!(
+            expected.as_slice(),
+            log.lock().expect("Expected lock").as_slice()
+        );
+    }
+
+    #[test]
+    fn given_removed_observer_when_player_resigns_then_only_the_others_should_be_called() {
+        // Given
+        let mut game = Game::new(BitMaskBoard::new(|| TestMask::empty((9, 9))));
+        let log = Arc::new(Mutex::new(vec![]));
+        let removed = game.add_observer(recorder(&log, "a"));
+        game.add_observer(recorder(&log, "b"));
+
+        // When
+        let was_there = game.remove_observer(removed);
+        let mut copy = game.clone();
+        copy.end(GameResult::Resignation {
+            winner: Player::White,
+        });
+        game.end(GameResult::Resignation {
+            winner: Player::White,
+        });
+
+        // Then
+        assert!(was_there);
+        assert!(!game.remove_observer(removed));
+        assert_eq!(
+            vec!["b: resignation Black", "b: ended W+R"],
+            *log.lock().expect("Expected lock")
+        );
+    }
+
+    #[test]
+    fn given_game_types_when_shared_between_threads_then_they_should_be_sync() {
+        fn assert_sync<T: Sync>() {}
+
+        assert_sync::<Game<BitMaskBoard<BitMask19>>>();
+        assert_sync::<AnyGame>();
+    }
+}
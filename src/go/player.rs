@@ -3,7 +3,10 @@ use std::{fmt::Display, ops::Not};
 pub const B: Option<Player> = Some(Player::Black);
 pub const W: Option<Player> = Some(Player::White);
 
+/// Serialized as `"black"` or `"white"`.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[repr(usize)]
 pub enum Player {
     Black,
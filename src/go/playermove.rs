@@ -1,11 +1,40 @@
-use crate::go::{coordinate::FlexibleCoordinate, player::Player};
+use crate::go::{coordinate::FlexibleCoordinate, coordinate_set::CoordinateSet, player::Player};
 
+/// Serialized with a `type` of `place_stone`, `skip` or `setup` next to the fields of the move,
+/// like `{"type": "place_stone", "player": "black", "coord": {"x": 3, "y": 3}}`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum Move {
     PlaceStone(PlaceStoneMove),
     Skip { player: Player },
+    Setup(SetupMove),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlaceStoneMove {
     pub player: Player,
     pub coord: FlexibleCoordinate,
 }
+
+/// Changes the board without going through the move rules, like the SGF `AB`, `AW`, `AE` and `PL`
+/// properties do for handicap stones and problem positions.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetupMove {
+    pub add_black: CoordinateSet,
+    pub add_white: CoordinateSet,
+    pub clear: CoordinateSet,
+    /// Player that should make the next move, None keeps the current player.
+    pub player_to_move: Option<Player>,
+}
+
+impl SetupMove {
+    pub fn is_empty(&self) -> bool {
+        self.add_black.is_empty()
+            && self.add_white.is_empty()
+            && self.clear.is_empty()
+            && self.player_to_move.is_none()
+    }
+}
@@ -0,0 +1,450 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::go::{
+    bitmask::FlexibleBitMask, bitmask_board::BitMaskBoard, board::FlexibleBoard,
+    coordinate::FlexibleCoordinate, coordinate_notation::CoordinateNotation,
+    dynamic_bitmask::DynamicBitMask, game::Game, player::Player,
+};
+
+/// Largest width and height the notation supports, the ko point uses the 52 SGF letters.
+pub const MAX_SIZE: u16 = 52;
+
+/// A position that can be continued from, written as one line of six space separated fields:
+///
+/// ```text
+/// <size> <stones> <to move> <ko> <captures> <komi>
+/// 9 1OX6/OX1X5/1OX6/9/9/9/9/9/9 w cb 1:0 6.5
+/// ```
+///
+/// - Size is `19` for square boards or `<width>x<height>`, like `13x9`.
+/// - Stones are the rows from the top, separated by `/`. In a row `X` is a black stone, `O` a
+///   white stone and a number is that many empty points.
+/// - To move is `b` or `w`.
+/// - Ko is the point the player to move can not retake as SGF letters, `aa` is the top left, or
+///   `-` if there is none.
+/// - Captures are `<stones captured by black>:<stones captured by white>`.
+/// - Komi is a decimal number, or `-` if it is unknown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub width: u16,
+    pub height: u16,
+    /// `position[y][x]`, in the layout of [`BitMaskBoard::from_position`].
+    pub position: Vec<Vec<Option<Player>>>,
+    pub to_move: Player,
+    pub ko: Option<FlexibleCoordinate>,
+    pub captured_by_black: u16,
+    pub captured_by_white: u16,
+    pub komi: Option<f64>,
+}
+
+impl Position {
+    pub fn from_board<TBoard: FlexibleBoard>(board: &TBoard, to_move: Player) -> Self {
+        let (width, height) = board.get_size();
+        Self {
+            width,
+            height,
+            position: (0..height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| board.get_player_at(&FlexibleCoordinate { x, y }))
+                        .collect()
+                })
+                .collect(),
+            to_move,
+            ko: None,
+            captured_by_black: 0,
+            captured_by_white: 0,
+            komi: None,
+        }
+    }
+
+    /// The game does not know its komi, so it is passed separately.
+    pub fn from_game<TBoard: FlexibleBoard>(game: &Game<TBoard>, komi: Option<f64>) -> Self {
+        Self {
+            ko: game.get_ko(),
+            captured_by_black: game.get_captures(Player::Black),
+            captured_by_white: game.get_captures(Player::White),
+            komi,
+            ..Self::from_board(game.get_board(), game.get_current_player())
+        }
+    }
+
+    /// Builds a board from the stones, the bitmasks from `mask_factory` need to have the size of
+    /// the position.
+    pub fn to_board<TBitMask, TMaskFactory>(
+        &self,
+        mask_factory: TMaskFactory,
+    ) -> Result<BitMaskBoard<TBitMask>, PositionError>
+    where
+        TBitMask: FlexibleBitMask + Eq + PartialEq + std::fmt::Debug + Clone,
+        TMaskFactory: Fn() -> TBitMask,
+    {
+        let size = mask_factory().get_size();
+        if size != (self.width, self.height) {
+            return Err(PositionError::SizeMismatch {
+                expected: size,
+                found: (self.width, self.height),
+            });
+        }
+
+        Ok(BitMaskBoard::from_position(
+            mask_factory,
+            self.position.clone(),
+        ))
+    }
+
+    /// Builds a game that continues from this position, with an empty history.
+    pub fn to_game<TBitMask, TMaskFactory>(
+        &self,
+        mask_factory: TMaskFactory,
+    ) -> Result<Game<BitMaskBoard<TBitMask>>, PositionError>
+    where
+        TBitMask: FlexibleBitMask + Eq + PartialEq + std::fmt::Debug + Clone,
+        TMaskFactory: Fn() -> TBitMask,
+    {
+        Ok(Game::from_position(
+            self.to_board(mask_factory)?,
+            self.to_move,
+            self.ko,
+            (self.captured_by_black, self.captured_by_white),
+        ))
+    }
+}
+
+/// Parses a position into a game on a board of its own size.
+pub fn parse_game(notation: &str) -> Result<Game<BitMaskBoard<DynamicBitMask>>, PositionError> {
+    let position: Position = notation.parse()?;
+    let size = (position.width, position.height);
+    position.to_game(|| DynamicBitMask::init(size))
+}
+
+impl FromStr for Position {
+    type Err = PositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let mut field = |name: &'static str| fields.next().ok_or(PositionError::MissingField(name));
+        let size = field("size")?;
+        let stones = field("stones")?;
+        let to_move = field("player to move")?;
+        let ko = field("ko")?;
+        let captures = field("captures")?;
+        let komi = field("komi")?;
+        if let Some(extra) = fields.next() {
+            return Err(PositionError::UnexpectedField(extra.to_string()));
+        }
+
+        let invalid_size = || PositionError::InvalidSize(size.to_string());
+        let (width, height) = match size.split_once('x') {
+            Some((width, height)) => (
+                width.parse().map_err(|_| invalid_size())?,
+                height.parse().map_err(|_| invalid_size())?,
+            ),
+            None => {
+                let size = size.parse().map_err(|_| invalid_size())?;
+                (size, size)
+            }
+        };
+        if !(1..=MAX_SIZE).contains(&width) || !(1..=MAX_SIZE).contains(&height) {
+            return Err(invalid_size());
+        }
+
+        let rows: Vec<&str> = stones.split('/').collect();
+        if rows.len() != height as usize {
+            return Err(PositionError::RowCount {
+                expected: height,
+                found: rows.len(),
+            });
+        }
+        let position = rows
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                parse_row(row, width).ok_or_else(|| PositionError::InvalidRow {
+                    row: y + 1,
+                    text: row.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let to_move = match to_move {
+            "b" => Player::Black,
+            "w" => Player::White,
+            _ => return Err(PositionError::InvalidPlayer(to_move.to_string())),
+        };
+
+        let ko = match ko {
+            "-" => None,
+            _ => Some(
+                CoordinateNotation::Sgf
+                    .parse(ko, (width, height))
+                    .ok()
+                    .filter(|coord| position[coord.y as usize][coord.x as usize].is_none())
+                    .ok_or_else(|| PositionError::InvalidKo(ko.to_string()))?,
+            ),
+        };
+
+        let invalid_captures = || PositionError::InvalidCaptures(captures.to_string());
+        let (captured_by_black, captured_by_white) =
+            captures.split_once(':').ok_or_else(invalid_captures)?;
+
+        Ok(Self {
+            width,
+            height,
+            position,
+            to_move,
+            ko,
+            captured_by_black: captured_by_black.parse().map_err(|_| invalid_captures())?,
+            captured_by_white: captured_by_white.parse().map_err(|_| invalid_captures())?,
+            komi: match komi {
+                "-" => None,
+                _ => Some(
+                    komi.parse()
+                        .map_err(|_| PositionError::InvalidKomi(komi.to_string()))?,
+                ),
+            },
+        })
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.width == self.height {
+            write!(f, "{} ", self.width)?;
+        } else {
+            write!(f, "{}x{} ", self.width, self.height)?;
+        }
+
+        for (y, row) in self.position.iter().enumerate() {
+            if y > 0 {
+                write!(f, "/")?;
+            }
+            let mut empty = 0;
+            for point in row {
+                match point {
+                    None => empty += 1,
+                    Some(player) => {
+                        if empty > 0 {
+                            write!(f, "{empty}")?;
+                            empty = 0;
+                        }
+                        match player {
+                            Player::Black => write!(f, "X")?,
+                            Player::White => write!(f, "O")?,
+                        }
+                    }
+                }
+            }
+            if empty > 0 {
+                write!(f, "{empty}")?;
+            }
+        }
+
+        match self.to_move {
+            Player::Black => write!(f, " b ")?,
+            Player::White => write!(f, " w ")?,
+        }
+        match self.ko {
+            Some(coord) => write!(
+                f,
+                "{}",
+                CoordinateNotation::Sgf
+                    .format(&coord, (self.width, self.height))
+                    .map_err(|_| std::fmt::Error)?
+            )?,
+            None => write!(f, "-")?,
+        }
+        write!(f, " {}:{} ", self.captured_by_black, self.captured_by_white)?;
+        match self.komi {
+            Some(komi) => write!(f, "{komi}"),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+fn parse_row(row: &str, width: u16) -> Option<Vec<Option<Player>>> {
+    let mut points = vec![];
+    let mut empty = String::new();
+    for character in row.chars() {
+        if character.is_ascii_digit() {
+            empty.push(character);
+            continue;
+        }
+        push_empty(&mut points, &empty, width)?;
+        empty.clear();
+        match character {
+            'X' => points.push(Some(Player::Black)),
+            'O' => points.push(Some(Player::White)),
+            _ => return None,
+        }
+    }
+    push_empty(&mut points, &empty, width)?;
+    (points.len() == width as usize).then_some(points)
+}
+
+/// Adds a run of empty points, None when the run does not fit in the row.
+fn push_empty(points: &mut Vec<Option<Player>>, run: &str, width: u16) -> Option<()> {
+    if run.is_empty() {
+        return Some(());
+    }
+    let run: u16 = run.parse().ok()?;
+    if points.len() + run as usize > width as usize {
+        return None;
+    }
+    points.extend(std::iter::repeat_n(None, run as usize));
+    Some(())
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PositionError {
+    #[error("The position has no {0}")]
+    MissingField(&'static str),
+    #[error("The position has an unexpected field '{0}'")]
+    UnexpectedField(String),
+    #[error("Invalid board size '{0}', expected a size between 1 and 52")]
+    InvalidSize(String),
+    #[error("The position has {found} rows, expected {expected}")]
+    RowCount { expected: u16, found: usize },
+    #[error("Row {row} '{text}' does not fit the board")]
+    InvalidRow { row: usize, text: String },
+    #[error("Invalid player to move '{0}', expected 'b' or 'w'")]
+    InvalidPlayer(String),
+    #[error("Invalid ko point '{0}', expected an empty point or '-'")]
+    InvalidKo(String),
+    #[error("Invalid captures '{0}', expected '<black>:<white>'")]
+    InvalidCaptures(String),
+    #[error("Invalid komi '{0}'")]
+    InvalidKomi(String),
+    #[error("The position is {found:?} but the board is {expected:?}")]
+    SizeMismatch {
+        expected: (u16, u16),
+        found: (u16, u16),
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::{
+        bitmask19::BitMask19,
+        diagram::parse_board,
+        playermove::{Move, PlaceStoneMove},
+    };
+
+    #[test]
+    fn given_game_with_ko_when_written_then_it_should_parse_back_to_the_same_game() {
+        // Given
+        let board = parse_board(
+            ". O X . . . . . .
+             O . O X . . . . .
+             . O X . . . . . .
+             . . . . . . . . .
+             . . . . . . . . .
+             . . . . . . . . .
+             . . . . . . . . .
+             . . . . . . . . .
+             . . . . . . . . .",
+        )
+        .expect("Expected diagram to parse");
+        let mut game = Game::new(board);
+        game.make_move(&Move::PlaceStone(PlaceStoneMove {
+            player: Player::Black,
+            coord: FlexibleCoordinate { x: 1, y: 1 },
+        }))
+        .expect("Expected ko capture");
+
+        // When
+        let notation = Position::from_game(&game, Some(6.5)).to_string();
+        let res = parse_game(&notation).expect("Expected notation to parse");
+
+        // Then
+        assert_eq!("9 1OX6/OX1X5/1OX6/9/9/9/9/9/9 w cb 1:0 6.5", notation);
+        assert_eq!(game.get_board(), res.get_board());
+        assert_eq!(Player::White, res.get_current_player());
+        assert_eq!(Some(FlexibleCoordinate { x: 2, y: 1 }), res.get_ko());
+        assert_eq!(1, res.get_captures(Player::Black));
+    }
+
+    #[test]
+    fn given_rectangular_position_when_parsed_then_it_should_keep_the_size() {
+        // Given
+        let notation = "5x3 X4/5/3OO b - 0:2 -";
+
+        // When
+        let position: Position = notation.parse().expect("Expected notation to parse");
+
+        // Then
+        assert_eq!((5, 3), (position.width, position.height));
+        assert_eq!(Some(Player::White), position.position[2][4]);
+        assert_eq!(None, position.komi);
+        assert_eq!(notation, position.to_string());
+        assert_eq!(
+            Err(PositionError::SizeMismatch {
+                expected: (19, 19),
+                found: (5, 3)
+            }),
+            position.to_board(BitMask19::init).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn given_invalid_notation_when_parsed_then_it_should_say_what_is_wrong() {
+        let parse = |x: &str| x.parse::<Position>().map(|_| ());
+
+        assert_eq!(
+            Err(PositionError::MissingField("komi")),
+            parse("3 3/3/3 b - 0:0")
+        );
+        assert_eq!(
+            Err(PositionError::RowCount {
+                expected: 3,
+                found: 2
+            }),
+            parse("3 3/3 b - 0:0 0")
+        );
+        assert_eq!(
+            Err(PositionError::InvalidRow {
+                row: 2,
+                text: "X3".to_string()
+            }),
+            parse("3 3/X3/3 b - 0:0 0")
+        );
+        assert_eq!(
+            Err(PositionError::InvalidKo("aa".to_string())),
+            parse("3 X2/3/3 b aa 0:0 0")
+        );
+        assert_eq!(
+            Err(PositionError::InvalidPlayer("black".to_string())),
+            parse("3 3/3/3 black - 0:0 0")
+        );
+    }
+
+    #[test]
+    fn given_row_with_oversized_run_when_parsed_then_it_should_be_an_invalid_row() {
+        // Given
+        let huge = "9 99999999999/9/9/9/9/9/9/9/9 b - 0:0 6.5";
+        let too_wide = "9 X60000/9/9/9/9/9/9/9/9 b - 0:0 6.5";
+
+        // When
+        let huge = parse_game(huge).map(|_| ());
+        let too_wide = parse_game(too_wide).map(|_| ());
+
+        // Then
+        assert_eq!(
+            Err(PositionError::InvalidRow {
+                row: 1,
+                text: "99999999999".to_string()
+            }),
+            huge
+        );
+        assert_eq!(
+            Err(PositionError::InvalidRow {
+                row: 1,
+                text: "X60000".to_string()
+            }),
+            too_wide
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+/// Komi given in an even game, black plays first and white gets compensated.
+pub const EVEN_KOMI: f64 = 6.5;
+
+/// Komi given when the weaker player takes black without any handicap stones, or when handicap
+/// stones are placed.
+pub const HANDICAP_KOMI: f64 = 0.5;
+
+/// The largest amount of handicap stones that will be suggested.
+pub const MAX_HANDICAP: u8 = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RankKind {
+    Kyu,
+    Dan,
+    Pro,
+}
+
+/// Servers mark ranks with a suffix to indicate how reliable they are, `3k?` is a rank the server
+/// is not sure about yet and `1d*` is an established rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RankCertainty {
+    Unspecified,
+    Uncertain,
+    Established,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rank {
+    pub kind: RankKind,
+    /// 1-based level within the kind, e.g. 3 for `3k`.
+    pub level: u8,
+    pub certainty: RankCertainty,
+}
+
+impl Rank {
+    pub fn kyu(level: u8) -> Self {
+        Self::new(RankKind::Kyu, level)
+    }
+
+    pub fn dan(level: u8) -> Self {
+        Self::new(RankKind::Dan, level)
+    }
+
+    pub fn pro(level: u8) -> Self {
+        Self::new(RankKind::Pro, level)
+    }
+
+    fn new(kind: RankKind, level: u8) -> Self {
+        Self {
+            kind,
+            level,
+            certainty: RankCertainty::Unspecified,
+        }
+    }
+
+    /// Strength of the rank in handicap stones, 1 kyu is 0, 1 dan is 1 and 30 kyu is -29.
+    ///
+    /// Pro ranks are closer together than amateur ranks, each pro rank counts as a third of a
+    /// stone on top of 9 dan.
+    pub fn strength(&self) -> f64 {
+        self.strength_in_thirds() as f64 / 3.0
+    }
+
+    /// The amount of stones `self` is stronger than `other`, negative if `other` is stronger.
+    pub fn difference(&self, other: &Rank) -> f64 {
+        self.strength() - other.strength()
+    }
+
+    /// Suggested handicap for a game between `self` and `other`, the weaker player takes black.
+    pub fn handicap_against(&self, other: &Rank) -> Handicap {
+        Handicap::from_rank_difference(self.difference(other))
+    }
+
+    fn strength_in_thirds(&self) -> i32 {
+        let level = self.level as i32;
+        match self.kind {
+            RankKind::Kyu => (1 - level) * 3,
+            RankKind::Dan => level * 3,
+            RankKind::Pro => 27 + level,
+        }
+    }
+}
+
+impl PartialOrd for Rank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.strength_in_thirds()
+            .cmp(&other.strength_in_thirds())
+            .then(self.certainty.cmp(&other.certainty))
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            RankKind::Kyu => "k",
+            RankKind::Dan => "d",
+            RankKind::Pro => "p",
+        };
+        let certainty = match self.certainty {
+            RankCertainty::Unspecified => "",
+            RankCertainty::Uncertain => "?",
+            RankCertainty::Established => "*",
+        };
+        write!(f, "{}{}{}", self.level, kind, certainty)
+    }
+}
+
+impl FromStr for Rank {
+    type Err = RankParseError;
+
+    /// Parses ranks like `31k`, `5d`, `2p`, `3k?`, `1d*`, `4 kyu`, `6 dan`, `9 pro`, `2級` and
+    /// `3段`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let (rest, certainty) = if let Some(rest) = trimmed.strip_suffix('?') {
+            (rest, RankCertainty::Uncertain)
+        } else if let Some(rest) = trimmed.strip_suffix('*') {
+            (rest, RankCertainty::Established)
+        } else {
+            (trimmed, RankCertainty::Unspecified)
+        };
+
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| RankParseError::MissingKind(s.to_string()))?;
+        let (digits, kind) = rest.split_at(digits_end);
+        if digits.is_empty() {
+            return Err(RankParseError::MissingLevel(s.to_string()));
+        }
+
+        let kind = match kind.trim().to_lowercase().as_str() {
+            "k" | "kyu" | "級" | "급" => RankKind::Kyu,
+            "d" | "dan" | "段" | "단" => RankKind::Dan,
+            "p" | "pro" => RankKind::Pro,
+            _ => return Err(RankParseError::UnknownKind(s.to_string())),
+        };
+
+        let level: u8 = digits
+            .parse()
+            .map_err(|_| RankParseError::LevelOutOfRange(s.to_string()))?;
+        // Some servers go down to 35 kyu and beyond, we only reject kyu ranks that make no sense.
+        let max_level = match kind {
+            RankKind::Kyu => 50,
+            RankKind::Dan => 9,
+            RankKind::Pro => 9,
+        };
+        if level == 0 || level > max_level {
+            return Err(RankParseError::LevelOutOfRange(s.to_string()));
+        }
+
+        Ok(Rank {
+            kind,
+            level,
+            certainty,
+        })
+    }
+}
+
+/// Handicap stones and komi for a game, the weaker player takes black.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Handicap {
+    pub stones: u8,
+    pub komi: f64,
+}
+
+impl Handicap {
+    /// Suggests a handicap for a strength difference in stones, the sign of the difference is
+    /// ignored.
+    ///
+    /// A difference below one stone is an even game, one stone means the weaker player takes
+    /// black without komi, and larger differences give one handicap stone per rank up to
+    /// [`MAX_HANDICAP`].
+    pub fn from_rank_difference(difference: f64) -> Self {
+        let stones = difference.abs().round() as u32;
+        match stones {
+            0 => Handicap {
+                stones: 0,
+                komi: EVEN_KOMI,
+            },
+            1 => Handicap {
+                stones: 0,
+                komi: HANDICAP_KOMI,
+            },
+            n => Handicap {
+                stones: n.min(MAX_HANDICAP as u32) as u8,
+                komi: HANDICAP_KOMI,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RankParseError {
+    #[error("Rank '{0}' has no kyu, dan or pro indicator")]
+    MissingKind(String),
+    #[error("Rank '{0}' does not start with a level")]
+    MissingLevel(String),
+    #[error("Rank '{0}' has an unknown kind, expected kyu, dan or pro")]
+    UnknownKind(String),
+    #[error("Rank '{0}' has a level that is out of range")]
+    LevelOutOfRange(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_common_rank_strings_when_parsed_then_it_should_return_the_ranks() {
+        // Given
+        let inputs = [
+            "31k", "3k", "5d", "2p", "3k?", "1d*", "4 kyu", "6 Dan", "3段",
+        ];
+
+        // When
+        let res: Vec<Rank> = inputs
+            .iter()
+            .map(|x| x.parse().expect("Expected rank to parse"))
+            .collect();
+
+        // Then
+        let expected = vec![
+            Rank::kyu(31),
+            Rank::kyu(3),
+            Rank::dan(5),
+            Rank::pro(2),
+            Rank {
+                certainty: RankCertainty::Uncertain,
+                ..Rank::kyu(3)
+            },
+            Rank {
+                certainty: RankCertainty::Established,
+                ..Rank::dan(1)
+            },
+            Rank::kyu(4),
+            Rank::dan(6),
+            Rank::dan(3),
+        ];
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn given_invalid_rank_strings_when_parsed_then_it_should_return_errors() {
+        assert_eq!(
+            Err(RankParseError::MissingKind("12".to_string())),
+            "12".parse::<Rank>()
+        );
+        assert_eq!(
+            Err(RankParseError::MissingLevel("k".to_string())),
+            "k".parse::<Rank>()
+        );
+        assert_eq!(
+            Err(RankParseError::UnknownKind("3x".to_string())),
+            "3x".parse::<Rank>()
+        );
+        assert_eq!(
+            Err(RankParseError::LevelOutOfRange("10d".to_string())),
+            "10d".parse::<Rank>()
+        );
+    }
+
+    #[test]
+    fn given_ranks_when_sorted_then_it_should_order_kyu_then_dan_then_pro() {
+        // Given
+        let mut ranks = vec![
+            Rank::pro(1),
+            Rank::dan(1),
+            Rank::kyu(1),
+            Rank::dan(9),
+            Rank::kyu(30),
+            Rank::pro(9),
+        ];
+
+        // When
+        ranks.sort();
+
+        // Then
+        let expected = vec![
+            Rank::kyu(30),
+            Rank::kyu(1),
+            Rank::dan(1),
+            Rank::dan(9),
+            Rank::pro(1),
+            Rank::pro(9),
+        ];
+        assert_eq!(expected, ranks);
+    }
+
+    #[test]
+    fn given_ranks_when_strength_is_called_then_it_should_be_continuous_over_kyu_and_dan() {
+        assert_eq!(-29.0, Rank::kyu(30).strength());
+        assert_eq!(0.0, Rank::kyu(1).strength());
+        assert_eq!(1.0, Rank::dan(1).strength());
+        assert_eq!(2.0, Rank::dan(1).difference(&Rank::kyu(2)));
+    }
+
+    #[test]
+    fn given_rank_differences_when_handicap_is_suggested_then_it_should_scale_with_difference() {
+        assert_eq!(
+            Handicap {
+                stones: 0,
+                komi: EVEN_KOMI
+            },
+            Rank::kyu(3).handicap_against(&Rank::kyu(3))
+        );
+        assert_eq!(
+            Handicap {
+                stones: 0,
+                komi: HANDICAP_KOMI
+            },
+            Rank::kyu(3).handicap_against(&Rank::kyu(4))
+        );
+        assert_eq!(
+            Handicap {
+                stones: 4,
+                komi: HANDICAP_KOMI
+            },
+            Rank::kyu(5).handicap_against(&Rank::kyu(1))
+        );
+        assert_eq!(
+            Handicap {
+                stones: 9,
+                komi: HANDICAP_KOMI
+            },
+            Rank::kyu(31).handicap_against(&Rank::kyu(3))
+        );
+    }
+}
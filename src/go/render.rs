@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+
+use crate::go::{
+    board::FlexibleBoard,
+    coordinate::FlexibleCoordinate,
+    coordinate_notation::{gtp_column_letter, sgf_letter},
+    coordinate_set::CoordinateSet,
+    game::Game,
+    handicap::star_points,
+    player::Player,
+    playermove::{Move, PlaceStoneMove},
+};
+
+const RESET: &str = "\x1b[0m";
+const BOARD: &str = "\x1b[30;43m";
+const BLACK_STONE: &str = "\x1b[30m";
+const WHITE_STONE: &str = "\x1b[97m";
+const MARKER: &str = "\x1b[31m";
+const BLACK_AREA: &str = "\x1b[100m";
+const WHITE_AREA: &str = "\x1b[47m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridStyle {
+    /// `X` and `O` stones on a grid of `.` with `+` star points.
+    Ascii,
+    /// `●` and `○` stones on a box drawing grid.
+    #[default]
+    Unicode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelStyle {
+    /// Column letters without `I` and row numbers from the bottom, boards wider than 25 columns
+    /// only get row numbers.
+    Gtp,
+    /// Column and row letters from the top left.
+    Sgf,
+}
+
+/// Extra information drawn on a point.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Overlay {
+    Number(u32),
+    Letter(char),
+    /// Who the point belongs to, from -1 for white to 1 for black. Shaded as a background colour
+    /// with colours, or as a small square on empty points of at least 0.5 either way without.
+    Ownership(f64),
+}
+
+/// Renders boards for a terminal.
+///
+/// Every point takes the same amount of columns, so the grid stays aligned with wide overlays
+/// such as move numbers. The last move is put between parentheses.
+#[derive(Debug, Clone)]
+pub struct BoardRenderer {
+    pub style: GridStyle,
+    /// Whether to use ANSI colours for the board, stones and ownership.
+    pub colors: bool,
+    pub labels: Option<LabelStyle>,
+    pub star_points: bool,
+    pub last_move: Option<FlexibleCoordinate>,
+    pub ko: Option<FlexibleCoordinate>,
+    pub overlays: HashMap<FlexibleCoordinate, Overlay>,
+}
+
+impl Default for BoardRenderer {
+    fn default() -> Self {
+        Self {
+            style: GridStyle::Unicode,
+            colors: false,
+            labels: Some(LabelStyle::Gtp),
+            star_points: true,
+            last_move: None,
+            ko: None,
+            overlays: HashMap::new(),
+        }
+    }
+}
+
+impl BoardRenderer {
+    /// The default renderer with the last move and the ko of the game marked.
+    pub fn for_game<TBoard: FlexibleBoard>(game: &Game<TBoard>) -> Self {
+        Self {
+            last_move: match game.get_history().last() {
+                Some(Move::PlaceStone(PlaceStoneMove { coord, .. })) => Some(*coord),
+                _ => None,
+            },
+            ko: game.get_ko(),
+            ..Default::default()
+        }
+    }
+
+    pub fn render<TBoard: FlexibleBoard>(&self, board: &TBoard) -> String {
+        let (width, height) = board.get_size();
+        let stars = if self.star_points {
+            star_points((width, height))
+        } else {
+            CoordinateSet::new(vec![])
+        };
+        let cell_width = self
+            .overlays
+            .values()
+            .map(|overlay| overlay_text(overlay).chars().count())
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let (column_labels, row_labels) = self.labels_for((width, height));
+        let row_label_width = row_labels.iter().map(|x| x.len()).max().unwrap_or(0);
+        let connector = match self.style {
+            GridStyle::Ascii => ' ',
+            GridStyle::Unicode => '─',
+        };
+
+        let mut out = String::new();
+        if !column_labels.is_empty() {
+            if row_label_width > 0 {
+                out.push_str(&" ".repeat(row_label_width + 1));
+            }
+            for label in &column_labels {
+                out.push_str(&format!(" {label:>cell_width$}"));
+            }
+            out.push('\n');
+        }
+
+        for y in 0..height {
+            if let Some(label) = row_labels.get(y as usize) {
+                out.push_str(&format!("{label:>row_label_width$} "));
+            }
+            if self.colors {
+                out.push_str(BOARD);
+            }
+
+            for x in 0..width {
+                let coord = FlexibleCoordinate { x, y };
+                let before = if self.last_move == Some(coord) {
+                    Some('(')
+                } else if x > 0 && self.last_move == Some(FlexibleCoordinate { x: x - 1, y }) {
+                    Some(')')
+                } else {
+                    None
+                };
+                let fill = if x == 0 { ' ' } else { connector };
+                match before {
+                    Some(marker) => self.paint(&mut out, MARKER, &marker.to_string()),
+                    None => out.push(fill),
+                }
+
+                let (content, color) = self.point(board, &coord, &stars);
+                let padding = cell_width - content.chars().count();
+                out.extend(std::iter::repeat_n(fill, padding));
+                let area = match self.overlays.get(&coord) {
+                    Some(Overlay::Ownership(owner)) if self.colors && *owner > 0.0 => {
+                        Some(BLACK_AREA)
+                    }
+                    Some(Overlay::Ownership(owner)) if self.colors && *owner < 0.0 => {
+                        Some(WHITE_AREA)
+                    }
+                    _ => None,
+                };
+                if let Some(area) = area {
+                    out.push_str(area);
+                }
+                match color {
+                    Some(color) => self.paint(&mut out, color, &content),
+                    None => out.push_str(&content),
+                }
+                if area.is_some() {
+                    out.push_str(BOARD);
+                }
+            }
+
+            if self.last_move == Some(FlexibleCoordinate { x: width - 1, y }) {
+                self.paint(&mut out, MARKER, ")");
+            }
+            if self.colors {
+                out.push_str(RESET);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Text of a point and the colour of its stone.
+    fn point<TBoard: FlexibleBoard>(
+        &self,
+        board: &TBoard,
+        coord: &FlexibleCoordinate,
+        stars: &CoordinateSet,
+    ) -> (String, Option<&'static str>) {
+        let player = board.get_player_at(coord);
+        let color = match player {
+            Some(Player::Black) => Some(BLACK_STONE),
+            Some(Player::White) => Some(WHITE_STONE),
+            None => None,
+        };
+
+        let overlay = self.overlays.get(coord);
+        if let Some(overlay @ (Overlay::Number(_) | Overlay::Letter(_))) = overlay {
+            return (overlay_text(overlay), color);
+        }
+
+        let unicode = self.style == GridStyle::Unicode;
+        let text = match (player, overlay) {
+            (Some(Player::Black), _) if unicode => '●',
+            (Some(Player::White), _) if unicode && self.colors => '●',
+            (Some(Player::White), _) if unicode => '○',
+            (Some(Player::Black), _) => 'X',
+            (Some(Player::White), _) => 'O',
+            (None, _) if self.ko == Some(*coord) => {
+                return (if unicode { '□' } else { '*' }.to_string(), Some(MARKER));
+            }
+            (None, Some(Overlay::Ownership(owner))) if !self.colors && owner.abs() >= 0.5 => {
+                match (*owner > 0.0, unicode) {
+                    (true, true) => '▪',
+                    (false, true) => '▫',
+                    (true, false) => 'x',
+                    (false, false) => 'o',
+                }
+            }
+            (None, _) if stars.contains(coord) => {
+                if unicode {
+                    '╋'
+                } else {
+                    '+'
+                }
+            }
+            (None, _) if unicode => grid_char(coord, board.get_size()),
+            (None, _) => '.',
+        };
+        (text.to_string(), color)
+    }
+
+    fn labels_for(&self, board_size: (u16, u16)) -> (Vec<String>, Vec<String>) {
+        let (width, height) = board_size;
+        match self.labels {
+            None => (vec![], vec![]),
+            Some(LabelStyle::Gtp) => (
+                (0..width)
+                    .map(|x| gtp_column_letter(x).map(|x| x.to_string()))
+                    .collect::<Option<Vec<_>>>()
+                    .unwrap_or_default(),
+                (0..height).map(|y| (height - y).to_string()).collect(),
+            ),
+            Some(LabelStyle::Sgf) => {
+                let letters = |size: u16| {
+                    (0..size)
+                        .map(|x| sgf_letter(x).map(|x| x.to_string()))
+                        .collect::<Option<Vec<_>>>()
+                        .unwrap_or_default()
+                };
+                (letters(width), letters(height))
+            }
+        }
+    }
+
+    fn paint(&self, out: &mut String, color: &str, text: &str) {
+        if self.colors {
+            out.push_str(color);
+            out.push_str(text);
+            out.push_str(BOARD);
+        } else {
+            out.push_str(text);
+        }
+    }
+}
+
+fn overlay_text(overlay: &Overlay) -> String {
+    match overlay {
+        Overlay::Number(number) => number.to_string(),
+        Overlay::Letter(letter) => letter.to_string(),
+        Overlay::Ownership(_) => String::new(),
+    }
+}
+
+fn grid_char(coord: &FlexibleCoordinate, board_size: (u16, u16)) -> char {
+    let (width, height) = board_size;
+    let (left, right) = (coord.x == 0, coord.x + 1 == width);
+    let (top, bottom) = (coord.y == 0, coord.y + 1 == height);
+    match (top, bottom, left, right) {
+        (true, _, true, _) => '┌',
+        (true, _, _, true) => '┐',
+        (true, _, _, _) => '┬',
+        (_, true, true, _) => '└',
+        (_, true, _, true) => '┘',
+        (_, true, _, _) => '┴',
+        (_, _, true, _) => '├',
+        (_, _, _, true) => '┤',
+        _ => '┼',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::diagram::parse_board;
+
+    #[test]
+    fn given_ascii_renderer_when_rendered_then_it_should_mark_the_last_move_and_ko() {
+        // Given
+        let board = parse_board(
+            ". X O . .
+             X . X O .
+             . X O . .
+             . . . . .
+             . . . . .",
+        )
+        .expect("Expected diagram to parse");
+        let renderer = BoardRenderer {
+            style: GridStyle::Ascii,
+            last_move: Some(FlexibleCoordinate { x: 2, y: 1 }),
+            ko: Some(FlexibleCoordinate { x: 1, y: 1 }),
+            ..Default::default()
+        };
+
+        // When
+        let res = renderer.render(&board);
+
+        // Then
+        let expected = "   A B C D E
+5  . X O . .
+4  X *(X)O .
+3  . X O . .
+2  . . . . .
+1  . . . . .
+";
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn given_unicode_renderer_with_overlays_when_rendered_then_columns_should_stay_aligned() {
+        // Given
+        let board = parse_board(
+            "X . .
+             . O .
+             . . .",
+        )
+        .expect("Expected diagram to parse");
+        let renderer = BoardRenderer {
+            labels: Some(LabelStyle::Sgf),
+            overlays: HashMap::from([
+                (FlexibleCoordinate { x: 0, y: 0 }, Overlay::Number(12)),
+                (FlexibleCoordinate { x: 2, y: 2 }, Overlay::Ownership(-0.9)),
+            ]),
+            ..Default::default()
+        };
+
+        // When
+        let res = renderer.render(&board);
+
+        // Then
+        let expected = "    a  b  c
+a  12──┬──┐
+b   ├──○──┤
+c   └──┴──▫
+";
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn given_colors_when_rendered_then_it_should_use_ansi_codes() {
+        // Given
+        let board = parse_board("X O\n. .").expect("Expected diagram to parse");
+        let renderer = BoardRenderer {
+            colors: true,
+            labels: None,
+            ..Default::default()
+        };
+
+        // When
+        let res = renderer.render(&board);
+
+        // Then
+        assert!(res.starts_with(BOARD));
+        assert!(res.contains(&format!("{WHITE_STONE}●{BOARD}")));
+        assert_eq!(2, res.matches(RESET).count());
+    }
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::go::{
+    any_game::AnyGame, coordinate::FlexibleCoordinate, coordinate_set::CoordinateSet,
+    game::GameResult, player::Player,
+};
+
+/// A ruleset, deciding how games are counted and whether earlier positions may be repeated.
+///
+/// Suicide is refused by [`Game`](crate::go::game::Game) under every ruleset, including the ones
+/// that allow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rules {
+    #[default]
+    Chinese,
+    Japanese,
+    Aga,
+    NewZealand,
+    TrompTaylor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    Area,
+    Territory,
+}
+
+impl Rules {
+    pub fn scoring(&self) -> Scoring {
+        match self {
+            Rules::Japanese => Scoring::Territory,
+            _ => Scoring::Area,
+        }
+    }
+
+    /// Whether a move may not recreate any earlier position of the board, instead of only
+    /// refusing the immediate retake of a ko.
+    pub fn superko(&self) -> bool {
+        !matches!(self, Rules::Japanese)
+    }
+
+    pub fn default_komi(&self) -> f64 {
+        match self {
+            Rules::Japanese => 6.5,
+            _ => 7.5,
+        }
+    }
+
+    /// Value of the SGF `RU` property.
+    pub fn sgf_name(&self) -> &'static str {
+        match self {
+            Rules::Chinese => "Chinese",
+            Rules::Japanese => "Japanese",
+            Rules::Aga => "AGA",
+            Rules::NewZealand => "NZ",
+            Rules::TrompTaylor => "Tromp-Taylor",
+        }
+    }
+
+    /// Counts a finished game with these rules.
+    pub fn score(&self, game: &AnyGame, komi: f64, dead: &CoordinateSet) -> GameResult {
+        let margin = match self.scoring() {
+            Scoring::Area => game.area_score(komi, dead).margin(),
+            Scoring::Territory => {
+                let captures = (
+                    game.get_captures(Player::Black),
+                    game.get_captures(Player::White),
+                );
+                game.territory_score(captures, komi, dead).margin()
+            }
+        };
+        GameResult::from_margin(margin)
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.sgf_name())
+    }
+}
+
+impl FromStr for Rules {
+    type Err = RulesError;
+
+    /// Parses the SGF names and a few common spellings, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "chinese" | "cn" => Ok(Rules::Chinese),
+            "japanese" | "jp" => Ok(Rules::Japanese),
+            "aga" => Ok(Rules::Aga),
+            "nz" | "new zealand" | "newzealand" => Ok(Rules::NewZealand),
+            "tromp-taylor" | "tromp taylor" | "tt" => Ok(Rules::TrompTaylor),
+            _ => Err(RulesError::Unknown(s.to_string())),
+        }
+    }
+}
+
+/// Positions a game went through, to check for superko.
+#[derive(Debug, Clone, Default)]
+pub struct PositionHistory {
+    seen: HashSet<Vec<u8>>,
+}
+
+impl PositionHistory {
+    /// Remembers the position, returns false when it was seen before.
+    pub fn insert(&mut self, game: &AnyGame) -> bool {
+        self.seen.insert(position_key(game))
+    }
+
+    pub fn contains(&self, game: &AnyGame) -> bool {
+        self.seen.contains(&position_key(game))
+    }
+}
+
+fn position_key(game: &AnyGame) -> Vec<u8> {
+    let (width, height) = game.get_size();
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| FlexibleCoordinate { x, y }))
+        .map(|coord| match game.get_player_at(&coord) {
+            None => 0,
+            Some(Player::Black) => 1,
+            Some(Player::White) => 2,
+        })
+        .collect()
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RulesError {
+    #[error("'{0}' is not a known ruleset")]
+    Unknown(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::playermove::{Move, PlaceStoneMove};
+
+    #[test]
+    fn given_game_with_capture_when_scored_then_only_territory_rules_should_count_prisoners() {
+        // Given
+        let mut game = AnyGame::new((5, 5));
+        let place = |player, x, y| {
+            Move::PlaceStone(PlaceStoneMove {
+                player,
+                coord: FlexibleCoordinate { x, y },
+            })
+        };
+        let walls = (0..5).flat_map(|y| [place(Player::Black, 2, y), place(Player::White, 3, y)]);
+        let capture = [
+            place(Player::Black, 1, 0),
+            place(Player::White, 0, 0),
+            place(Player::Black, 0, 1),
+        ];
+        for m in walls.chain(capture) {
+            game.make_move(&m).expect("Expected legal move");
+        }
+
+        // When
+        let chinese = Rules::Chinese.score(&game, 0.5, &CoordinateSet::new(vec![]));
+        let japanese = Rules::Japanese.score(&game, 0.5, &CoordinateSet::new(vec![]));
+
+        // Then
+        assert_eq!(GameResult::from_margin(4.5), chinese);
+        assert_eq!(GameResult::from_margin(3.5), japanese);
+        assert_eq!("B+4.5", chinese.to_string());
+        assert_eq!(Ok(Rules::TrompTaylor), "Tromp-Taylor".parse());
+    }
+}
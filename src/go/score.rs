@@ -0,0 +1,248 @@
+use std::fmt::Display;
+
+use crate::go::{
+    board::FlexibleBoard, coordinate::FlexibleCoordinate, coordinate_set::CoordinateSet,
+    player::Player,
+};
+
+/// Area score of a position: stones on the board plus the empty points that only touch stones of
+/// one colour. Dead stones count as empty points of the area around them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaScore {
+    pub black_stones: u32,
+    pub white_stones: u32,
+    pub black_territory: u32,
+    pub white_territory: u32,
+    pub komi: f64,
+}
+
+impl AreaScore {
+    pub fn black(&self) -> f64 {
+        (self.black_stones + self.black_territory) as f64
+    }
+
+    pub fn white(&self) -> f64 {
+        (self.white_stones + self.white_territory) as f64 + self.komi
+    }
+
+    /// Points black is ahead by, negative when white is ahead.
+    pub fn margin(&self) -> f64 {
+        self.black() - self.white()
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        let margin = self.margin();
+        if margin > 0.0 {
+            Some(Player::Black)
+        } else if margin < 0.0 {
+            Some(Player::White)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for AreaScore {
+    /// Formats the score like the SGF `RE` property and GTP `final_score`, e.g. `B+3.5`, `W+0.5`
+    /// or `0` for a draw.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let margin = self.margin();
+        match self.winner() {
+            Some(Player::Black) => write!(f, "B+{margin}"),
+            Some(Player::White) => write!(f, "W+{}", -margin),
+            None => write!(f, "0"),
+        }
+    }
+}
+
+/// Territory score of a position: empty points that only touch stones of one colour, plus the
+/// stones captured during the game and the dead stones of the opponent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerritoryScore {
+    pub black_territory: u32,
+    pub white_territory: u32,
+    /// White stones captured by black, including the dead white stones.
+    pub black_prisoners: u32,
+    /// Black stones captured by white, including the dead black stones.
+    pub white_prisoners: u32,
+    pub komi: f64,
+}
+
+impl TerritoryScore {
+    pub fn black(&self) -> f64 {
+        (self.black_territory + self.black_prisoners) as f64
+    }
+
+    pub fn white(&self) -> f64 {
+        (self.white_territory + self.white_prisoners) as f64 + self.komi
+    }
+
+    /// Points black is ahead by, negative when white is ahead.
+    pub fn margin(&self) -> f64 {
+        self.black() - self.white()
+    }
+}
+
+/// Scores the board by territory, `captures` holds the stones captured by black and by white
+/// during the game. Stones in `dead` are removed and added to the prisoners before counting.
+pub fn territory_score<TBoard: FlexibleBoard>(
+    board: &TBoard,
+    captures: (u16, u16),
+    komi: f64,
+    dead: &CoordinateSet,
+) -> TerritoryScore {
+    let area = area_score(board, komi, dead);
+    let dead_of = |player: Player| {
+        dead.iter()
+            .filter(|x| board.get_player_at(x) == Some(player))
+            .count() as u32
+    };
+    TerritoryScore {
+        black_territory: area.black_territory,
+        white_territory: area.white_territory,
+        black_prisoners: captures.0 as u32 + dead_of(Player::White),
+        white_prisoners: captures.1 as u32 + dead_of(Player::Black),
+        komi,
+    }
+}
+
+/// Scores the board by area, stones in `dead` are removed before counting.
+pub fn area_score<TBoard: FlexibleBoard>(
+    board: &TBoard,
+    komi: f64,
+    dead: &CoordinateSet,
+) -> AreaScore {
+    let (width, height) = board.get_size();
+    let alive =
+        |coord: &FlexibleCoordinate| board.get_player_at(coord).filter(|_| !dead.contains(coord));
+
+    let mut score = AreaScore {
+        black_stones: 0,
+        white_stones: 0,
+        black_territory: 0,
+        white_territory: 0,
+        komi,
+    };
+    let mut visited = CoordinateSet::new(vec![]);
+    for y in 0..height {
+        for x in 0..width {
+            let coord = FlexibleCoordinate { x, y };
+            match alive(&coord) {
+                Some(Player::Black) => score.black_stones += 1,
+                Some(Player::White) => score.white_stones += 1,
+                None if !visited.contains(&coord) => {
+                    let (size, touches_black, touches_white) =
+                        flood_empty(&coord, (width, height), &alive, &mut visited);
+                    match (touches_black, touches_white) {
+                        (true, false) => score.black_territory += size,
+                        (false, true) => score.white_territory += size,
+                        _ => {}
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+    score
+}
+
+/// Size of the empty area around `start` and whether it touches black or white stones.
+fn flood_empty(
+    start: &FlexibleCoordinate,
+    board_size: (u16, u16),
+    alive: &impl Fn(&FlexibleCoordinate) -> Option<Player>,
+    visited: &mut CoordinateSet,
+) -> (u32, bool, bool) {
+    let (mut size, mut touches_black, mut touches_white) = (0, false, false);
+    let mut stack = vec![*start];
+    visited.insert(*start);
+    while let Some(coord) = stack.pop() {
+        size += 1;
+        let mut neighbours = CoordinateSet::new(vec![coord]).grow(board_size);
+        neighbours.remove(&coord);
+        for neighbour in neighbours.into_iter() {
+            match alive(&neighbour) {
+                Some(Player::Black) => touches_black = true,
+                Some(Player::White) => touches_white = true,
+                None => {
+                    if visited.insert(neighbour) {
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+    }
+    (size, touches_black, touches_white)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::diagram::parse_board;
+
+    #[test]
+    fn given_finished_position_when_scored_then_territory_should_count_for_the_surrounding_colour()
+    {
+        // Given
+        let board = parse_board(
+            ". X O . .
+             . X O . .
+             X X O . .
+             . X O O O
+             . X O . .",
+        )
+        .expect("Expected diagram to parse");
+
+        // When
+        let score = area_score(&board, 6.5, &CoordinateSet::new(vec![]));
+
+        // Then
+        assert_eq!(6, score.black_stones);
+        assert_eq!(4, score.black_territory);
+        assert_eq!(7, score.white_stones);
+        assert_eq!(8, score.white_territory);
+        assert_eq!(Some(Player::White), score.winner());
+        assert_eq!("W+11.5", score.to_string());
+    }
+
+    #[test]
+    fn given_dead_stone_when_scored_then_it_should_count_for_the_opponent() {
+        // Given
+        let board = parse_board(
+            ". X O .
+             X X O .
+             O O O .
+             . . . X",
+        )
+        .expect("Expected diagram to parse");
+
+        // When
+        let score = area_score(&board, 0.0, &CoordinateSet::set(&[(3, 3)]));
+
+        // Then
+        assert_eq!(3 + 1, score.black() as u32);
+        assert_eq!(5 + 7, score.white() as u32);
+        assert_eq!("W+8", score.to_string());
+    }
+
+    #[test]
+    fn given_captures_and_dead_stone_when_scored_by_territory_then_prisoners_should_count() {
+        // Given
+        let board = parse_board(
+            ". X O .
+             X X O .
+             O O O .
+             . . . X",
+        )
+        .expect("Expected diagram to parse");
+
+        // When
+        let score = territory_score(&board, (2, 1), 6.5, &CoordinateSet::set(&[(3, 3)]));
+
+        // Then
+        assert_eq!(1 + 2, score.black() as u32);
+        assert_eq!(7, score.white_territory);
+        assert_eq!(1 + 1, score.white_prisoners);
+        assert_eq!(-12.5, score.margin());
+    }
+}
@@ -0,0 +1,548 @@
+use std::{fmt::Display, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeControl {
+    /// A single amount of time for the whole game.
+    Absolute { main_time: Duration },
+    /// After main time every move has to be made within a period, a period is lost when it runs
+    /// out and the game is lost when no periods are left.
+    ByoYomi {
+        main_time: Duration,
+        periods: u32,
+        period_time: Duration,
+    },
+    /// After main time a number of stones have to be played within each period.
+    Canadian {
+        main_time: Duration,
+        stones: u32,
+        period_time: Duration,
+    },
+    /// Every move adds an increment to the clock, optionally capped at a maximum.
+    Fischer {
+        main_time: Duration,
+        increment: Duration,
+        max_time: Option<Duration>,
+    },
+    /// Every move has to be made within the same amount of time, unused time is not kept.
+    Simple { per_move: Duration },
+}
+
+/// The clock of one player at a moment in the game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockState {
+    /// Time left on the clock, in overtime this is the time left in the current period.
+    pub time_left: Duration,
+    /// Byo-yomi periods or Canadian stones left in the period, None while in main time.
+    pub overtime_left: Option<u32>,
+}
+
+/// Both clocks after a move, as rebuilt from a game record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveClock {
+    pub black: ClockState,
+    pub white: ClockState,
+    /// Time the player spent on the move, None when the record has no time for the move.
+    pub time_used: Option<Duration>,
+}
+
+impl TimeControl {
+    /// Builds the time control from the SGF `TM` main time in seconds and the free form `OT`
+    /// overtime description, like `3x30 byo-yomi`, `25/600 Canadian` or `30 fischer`.
+    ///
+    /// Returns None when there is no main time and the overtime is not recognised, or when a time
+    /// is too large to be a [`Duration`].
+    pub fn from_sgf(main_time: Option<f64>, overtime: Option<&str>) -> Option<Self> {
+        let main_time = Duration::try_from_secs_f64(main_time.unwrap_or(0.0).max(0.0)).ok()?;
+        let overtime = overtime
+            .map(|x| x.trim().to_lowercase())
+            .unwrap_or_default();
+        let numbers = parse_numbers(&overtime);
+
+        if overtime.contains("fischer") || overtime.contains("increment") {
+            let increment = *numbers.first()?;
+            return Some(TimeControl::Fischer {
+                main_time,
+                increment: Duration::try_from_secs_f64(increment).ok()?,
+                max_time: numbers
+                    .get(1)
+                    .map(|x| Duration::try_from_secs_f64(*x))
+                    .transpose()
+                    .ok()?,
+            });
+        }
+
+        if overtime.contains("simple") {
+            return Some(TimeControl::Simple {
+                per_move: Duration::try_from_secs_f64(*numbers.first()?).ok()?,
+            });
+        }
+
+        if (overtime.contains("canadian") || overtime.contains('/')) && numbers.len() == 2 {
+            return Some(TimeControl::Canadian {
+                main_time,
+                stones: numbers[0] as u32,
+                period_time: Duration::try_from_secs_f64(numbers[1]).ok()?,
+            });
+        }
+
+        if (overtime.contains("byo") || overtime.contains('x')) && numbers.len() == 2 {
+            return Some(TimeControl::ByoYomi {
+                main_time,
+                periods: numbers[0] as u32,
+                period_time: Duration::try_from_secs_f64(numbers[1]).ok()?,
+            });
+        }
+
+        if main_time.is_zero() {
+            return None;
+        }
+
+        Some(TimeControl::Absolute { main_time })
+    }
+
+    pub fn main_time(&self) -> Duration {
+        match self {
+            TimeControl::Absolute { main_time }
+            | TimeControl::ByoYomi { main_time, .. }
+            | TimeControl::Canadian { main_time, .. }
+            | TimeControl::Fischer { main_time, .. } => *main_time,
+            TimeControl::Simple { .. } => Duration::ZERO,
+        }
+    }
+
+    /// The overtime as an SGF `OT` value, None when there is no overtime.
+    pub fn overtime_to_sgf(&self) -> Option<String> {
+        match self {
+            TimeControl::Absolute { .. } => None,
+            TimeControl::Simple { per_move } => Some(format!("{} simple", per_move.as_secs_f64())),
+            TimeControl::ByoYomi {
+                periods,
+                period_time,
+                ..
+            } => Some(format!(
+                "{}x{} byo-yomi",
+                periods,
+                period_time.as_secs_f64()
+            )),
+            TimeControl::Canadian {
+                stones,
+                period_time,
+                ..
+            } => Some(format!("{}/{} Canadian", stones, period_time.as_secs_f64())),
+            TimeControl::Fischer {
+                increment,
+                max_time,
+                ..
+            } => Some(match max_time {
+                Some(max_time) => format!(
+                    "{} {} fischer",
+                    increment.as_secs_f64(),
+                    max_time.as_secs_f64()
+                ),
+                None => format!("{} fischer", increment.as_secs_f64()),
+            }),
+        }
+    }
+
+    /// The clock of a player at the start of the game.
+    pub fn initial_clock(&self) -> ClockState {
+        match self {
+            TimeControl::ByoYomi {
+                main_time,
+                periods,
+                period_time,
+            } if main_time.is_zero() => ClockState {
+                time_left: *period_time,
+                overtime_left: Some(*periods),
+            },
+            TimeControl::Canadian {
+                main_time,
+                stones,
+                period_time,
+            } if main_time.is_zero() => ClockState {
+                time_left: *period_time,
+                overtime_left: Some(*stones),
+            },
+            TimeControl::Simple { per_move } => ClockState {
+                time_left: *per_move,
+                overtime_left: None,
+            },
+            _ => ClockState {
+                time_left: self.main_time(),
+                overtime_left: None,
+            },
+        }
+    }
+
+    /// The clock after `elapsed` has passed while the player is thinking, None when the player
+    /// ran out of time.
+    pub fn elapse(&self, clock: &ClockState, elapsed: Duration) -> Option<ClockState> {
+        let (periods, period_time) = match self {
+            TimeControl::ByoYomi {
+                periods,
+                period_time,
+                ..
+            } => (*periods, *period_time),
+            TimeControl::Canadian {
+                stones,
+                period_time,
+                ..
+            } => (*stones, *period_time),
+            _ => {
+                return Some(ClockState {
+                    time_left: clock.time_left.checked_sub(elapsed)?,
+                    overtime_left: None,
+                });
+            }
+        };
+
+        let (overtime_left, time_left, elapsed) = match clock.overtime_left {
+            Some(overtime_left) => (overtime_left, clock.time_left, elapsed),
+            None => match elapsed.checked_sub(clock.time_left) {
+                Some(overtime_elapsed) => (periods, period_time, overtime_elapsed),
+                None => {
+                    return Some(ClockState {
+                        time_left: clock.time_left - elapsed,
+                        overtime_left: None,
+                    });
+                }
+            },
+        };
+
+        if let TimeControl::Canadian { .. } = self {
+            return Some(ClockState {
+                time_left: time_left.checked_sub(elapsed)?,
+                overtime_left: Some(overtime_left),
+            });
+        }
+
+        // Byo-yomi loses a period every time one is used up completely.
+        match time_left.checked_sub(elapsed) {
+            Some(time_left) if !time_left.is_zero() => Some(ClockState {
+                time_left,
+                overtime_left: Some(overtime_left),
+            }),
+            _ => {
+                let elapsed = elapsed - time_left;
+                let periods_lost = 1 + (elapsed.as_nanos() / period_time.as_nanos().max(1)) as u32;
+                if periods_lost >= overtime_left {
+                    return None;
+                }
+                let period_elapsed = elapsed.as_nanos() % period_time.as_nanos().max(1);
+                Some(ClockState {
+                    time_left: period_time - Duration::from_nanos(period_elapsed as u64),
+                    overtime_left: Some(overtime_left - periods_lost),
+                })
+            }
+        }
+    }
+
+    /// The clock after a move that took `elapsed`, None when the player ran out of time.
+    ///
+    /// On top of the time that passed this adds the Fischer increment and resets byo-yomi,
+    /// Canadian and simple periods where the move completes them.
+    pub fn charge(&self, clock: &ClockState, elapsed: Duration) -> Option<ClockState> {
+        let after = self.elapse(clock, elapsed)?;
+
+        match self {
+            TimeControl::Absolute { .. } => Some(after),
+            TimeControl::Simple { per_move } => Some(ClockState {
+                time_left: *per_move,
+                overtime_left: None,
+            }),
+            TimeControl::Fischer {
+                increment,
+                max_time,
+                ..
+            } => {
+                let mut time_left = after.time_left + *increment;
+                if let Some(max_time) = max_time {
+                    time_left = time_left.min(*max_time);
+                }
+                Some(ClockState {
+                    time_left,
+                    overtime_left: None,
+                })
+            }
+            TimeControl::ByoYomi { period_time, .. } => match after.overtime_left {
+                Some(periods_left) => Some(ClockState {
+                    time_left: *period_time,
+                    overtime_left: Some(periods_left),
+                }),
+                None => Some(after),
+            },
+            TimeControl::Canadian {
+                stones,
+                period_time,
+                ..
+            } => match after.overtime_left {
+                Some(stones_left) if stones_left <= 1 => Some(ClockState {
+                    time_left: *period_time,
+                    overtime_left: Some(*stones),
+                }),
+                Some(stones_left) => Some(ClockState {
+                    time_left: after.time_left,
+                    overtime_left: Some(stones_left - 1),
+                }),
+                None => Some(after),
+            },
+        }
+    }
+
+    /// Time spent on a move, given the clock of the player before the move and the clock that
+    /// was recorded when the move was made.
+    ///
+    /// The clock before the move is expected to be the recorded clock of the previous move of the
+    /// player, byo-yomi and Canadian periods that were in progress are reset as servers do.
+    pub fn time_used(&self, before: &ClockState, after: &ClockState) -> Duration {
+        match (self, before.overtime_left, after.overtime_left) {
+            (TimeControl::Fischer { increment, .. }, _, _) => {
+                (before.time_left + *increment).saturating_sub(after.time_left)
+            }
+            (TimeControl::Simple { per_move }, _, _) => per_move.saturating_sub(after.time_left),
+            (
+                TimeControl::ByoYomi {
+                    periods,
+                    period_time,
+                    ..
+                },
+                None,
+                Some(after_periods),
+            ) => {
+                before.time_left
+                    + *period_time * periods.saturating_sub(after_periods)
+                    + period_time.saturating_sub(after.time_left)
+            }
+            (
+                TimeControl::ByoYomi { period_time, .. },
+                Some(before_periods),
+                Some(after_periods),
+            ) => {
+                *period_time * before_periods.saturating_sub(after_periods)
+                    + period_time.saturating_sub(after.time_left)
+            }
+            (TimeControl::Canadian { period_time, .. }, None, Some(_)) => {
+                before.time_left + period_time.saturating_sub(after.time_left)
+            }
+            (
+                TimeControl::Canadian { period_time, .. },
+                Some(before_stones),
+                Some(after_stones),
+            ) if after_stones >= before_stones => period_time.saturating_sub(after.time_left),
+            _ => before.time_left.saturating_sub(after.time_left),
+        }
+    }
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let main_time = self.main_time().as_secs_f64();
+        match self.overtime_to_sgf() {
+            Some(overtime) => write!(f, "{main_time}s + {overtime}"),
+            None => write!(f, "{main_time}s absolute"),
+        }
+    }
+}
+
+fn parse_numbers(text: &str) -> Vec<f64> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter(|x| !x.is_empty())
+        .filter_map(|x| x.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn given_sgf_time_settings_when_parsed_then_it_should_return_typed_time_controls() {
+        assert_eq!(
+            Some(TimeControl::ByoYomi {
+                main_time: secs(300),
+                periods: 3,
+                period_time: secs(30)
+            }),
+            TimeControl::from_sgf(Some(300.0), Some("3x30 byo-yomi"))
+        );
+        assert_eq!(
+            Some(TimeControl::Canadian {
+                main_time: secs(600),
+                stones: 25,
+                period_time: secs(600)
+            }),
+            TimeControl::from_sgf(Some(600.0), Some("25/600 Canadian"))
+        );
+        assert_eq!(
+            Some(TimeControl::Fischer {
+                main_time: secs(120),
+                increment: secs(10),
+                max_time: None
+            }),
+            TimeControl::from_sgf(Some(120.0), Some("10 fischer"))
+        );
+        assert_eq!(
+            Some(TimeControl::Absolute {
+                main_time: secs(1800)
+            }),
+            TimeControl::from_sgf(Some(1800.0), None)
+        );
+        assert_eq!(None, TimeControl::from_sgf(None, Some("unknown")));
+    }
+
+    #[test]
+    fn given_oversized_sgf_times_when_parsed_then_it_should_return_none() {
+        // Given
+        let huge = 1e30;
+
+        // When
+        let main_time = TimeControl::from_sgf(Some(huge), None);
+        let overtime = TimeControl::from_sgf(Some(60.0), Some(&format!("3x{huge} byo-yomi")));
+        let fischer = TimeControl::from_sgf(Some(60.0), Some(&format!("10 {huge} fischer")));
+
+        // Then
+        assert_eq!(None, main_time);
+        assert_eq!(None, overtime);
+        assert_eq!(None, fischer);
+    }
+
+    #[test]
+    fn given_time_control_when_written_as_overtime_then_it_should_parse_back() {
+        let controls = [
+            TimeControl::ByoYomi {
+                main_time: secs(300),
+                periods: 5,
+                period_time: secs(30),
+            },
+            TimeControl::Canadian {
+                main_time: secs(0),
+                stones: 10,
+                period_time: secs(300),
+            },
+            TimeControl::Fischer {
+                main_time: secs(60),
+                increment: secs(5),
+                max_time: Some(secs(120)),
+            },
+        ];
+
+        for control in controls {
+            let main_time = control.main_time().as_secs_f64();
+            let overtime = control.overtime_to_sgf();
+            let res = TimeControl::from_sgf(Some(main_time), overtime.as_deref());
+            assert_eq!(Some(control), res);
+        }
+    }
+
+    #[test]
+    fn given_byo_yomi_when_charging_past_main_time_then_it_should_use_up_periods() {
+        // Given
+        let control = TimeControl::ByoYomi {
+            main_time: secs(60),
+            periods: 3,
+            period_time: secs(30),
+        };
+        let clock = control.initial_clock();
+
+        // When
+        let in_main_time = control.charge(&clock, secs(50));
+        let one_period_lost = control.charge(&clock, secs(60 + 45));
+        let flagged = control.charge(&clock, secs(60 + 90));
+
+        // Then
+        assert_eq!(
+            Some(ClockState {
+                time_left: secs(10),
+                overtime_left: None
+            }),
+            in_main_time
+        );
+        assert_eq!(
+            Some(ClockState {
+                time_left: secs(30),
+                overtime_left: Some(2)
+            }),
+            one_period_lost
+        );
+        assert_eq!(None, flagged);
+    }
+
+    #[test]
+    fn given_canadian_overtime_when_all_stones_are_played_then_it_should_start_a_new_period() {
+        // Given
+        let control = TimeControl::Canadian {
+            main_time: secs(0),
+            stones: 2,
+            period_time: secs(60),
+        };
+        let clock = control.initial_clock();
+
+        // When
+        let first = control
+            .charge(&clock, secs(20))
+            .expect("Expected time left");
+        let second = control
+            .charge(&first, secs(30))
+            .expect("Expected time left");
+        let flagged = control.charge(&second, secs(61));
+
+        // Then
+        assert_eq!(secs(40), first.time_left);
+        assert_eq!(Some(1), first.overtime_left);
+        assert_eq!(secs(60), second.time_left);
+        assert_eq!(Some(2), second.overtime_left);
+        assert_eq!(None, flagged);
+    }
+
+    #[test]
+    fn given_fischer_and_simple_when_charging_then_it_should_add_increment_or_reset() {
+        let fischer = TimeControl::Fischer {
+            main_time: secs(60),
+            increment: secs(10),
+            max_time: Some(secs(65)),
+        };
+        let simple = TimeControl::Simple { per_move: secs(30) };
+
+        assert_eq!(
+            Some(secs(65)),
+            fischer
+                .charge(&fischer.initial_clock(), secs(1))
+                .map(|x| x.time_left)
+        );
+        assert_eq!(
+            Some(secs(30)),
+            simple
+                .charge(&simple.initial_clock(), secs(29))
+                .map(|x| x.time_left)
+        );
+        assert_eq!(None, simple.charge(&simple.initial_clock(), secs(31)));
+    }
+
+    #[test]
+    fn given_byo_yomi_clocks_when_time_used_is_called_then_it_should_count_lost_periods() {
+        // Given
+        let control = TimeControl::ByoYomi {
+            main_time: secs(300),
+            periods: 3,
+            period_time: secs(30),
+        };
+        let in_main_time = ClockState {
+            time_left: secs(10),
+            overtime_left: None,
+        };
+        let in_second_period = ClockState {
+            time_left: secs(20),
+            overtime_left: Some(2),
+        };
+
+        // When
+        let res = control.time_used(&in_main_time, &in_second_period);
+
+        // Then
+        assert_eq!(secs(10 + 30 + 10), res);
+    }
+}
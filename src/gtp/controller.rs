@@ -0,0 +1,456 @@
+use std::{
+    ffi::OsStr,
+    io::{self, BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, Command as Process, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::{
+    go::{
+        any_game::AnyGame,
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        game::{GameResult, MoveError},
+        player::Player,
+        playermove::{Move, PlaceStoneMove, SetupMove},
+    },
+    gtp::{Command, FinalScore, GtpError, GtpMove, StoneStatus, format_color},
+};
+
+/// Drives an engine that speaks GTP, keeping a game in sync with the moves that are sent to it
+/// and the moves it generates.
+pub struct GtpController<TReader: BufRead, TWriter: Write> {
+    reader: TReader,
+    writer: TWriter,
+    child: Option<Child>,
+    next_id: u32,
+    game: AnyGame,
+}
+
+/// A controller for an engine running as a subprocess.
+pub type ProcessController = GtpController<TimeoutReader, ChildStdin>;
+
+impl ProcessController {
+    /// Starts `program` with `args` and talks to it over its stdin and stdout, stderr is passed
+    /// through.
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[S]) -> Result<Self, ControllerError> {
+        let mut child = Process::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let writer = child.stdin.take().expect("Stdin is piped");
+        let reader = TimeoutReader::new(BufReader::new(
+            child.stdout.take().expect("Stdout is piped"),
+        ));
+        let mut controller = Self::new(reader, writer);
+        controller.child = Some(child);
+        Ok(controller)
+    }
+
+    /// How long to wait for a response, None waits forever. Waiting too long fails with an
+    /// [`io::ErrorKind::TimedOut`] error, the late response is skipped when it arrives.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.reader.timeout = timeout;
+    }
+}
+
+/// Reads the lines of a stream on a separate thread, so waiting for them can time out.
+pub struct TimeoutReader {
+    lines: Receiver<io::Result<String>>,
+    buffer: Vec<u8>,
+    position: usize,
+    pub timeout: Option<Duration>,
+}
+
+impl TimeoutReader {
+    pub fn new(mut reader: impl BufRead + Send + 'static) -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            loop {
+                let mut line = String::new();
+                let res = reader.read_line(&mut line);
+                let done = !matches!(res, Ok(1..));
+                if done && res.is_ok() {
+                    break;
+                }
+                if sender.send(res.map(|_| line)).is_err() || done {
+                    break;
+                }
+            }
+        });
+        Self {
+            lines,
+            buffer: vec![],
+            position: 0,
+            timeout: None,
+        }
+    }
+}
+
+impl Read for TimeoutReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for TimeoutReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position >= self.buffer.len() {
+            let line = match self.timeout {
+                Some(timeout) => match self.lines.recv_timeout(timeout) {
+                    Ok(line) => line?,
+                    Err(RecvTimeoutError::Timeout) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "The engine did not answer in time.",
+                        ));
+                    }
+                    Err(RecvTimeoutError::Disconnected) => String::new(),
+                },
+                None => self.lines.recv().unwrap_or_else(|_| Ok(String::new()))?,
+            };
+            self.buffer = line.into_bytes();
+            self.position = 0;
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+}
+
+impl<TReader: BufRead, TWriter: Write> GtpController<TReader, TWriter> {
+    /// A controller that writes commands to `writer` and reads the responses from `reader`,
+    /// starting from an empty 19x19 game.
+    pub fn new(reader: TReader, writer: TWriter) -> Self {
+        Self {
+            reader,
+            writer,
+            child: None,
+            next_id: 1,
+            game: AnyGame::new((19, 19)),
+        }
+    }
+
+    /// The game as the engine should see it.
+    pub fn game(&self) -> &AnyGame {
+        &self.game
+    }
+
+    /// Sends a command with the next id and returns the text of the successful response.
+    pub fn send(&mut self, name: &str, args: &[&str]) -> Result<String, ControllerError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let command = Command {
+            id: Some(id),
+            ..Command::new(name, args)
+        };
+        writeln!(self.writer, "{command}")?;
+        self.writer.flush()?;
+
+        // Responses to earlier commands that timed out come first.
+        let (response, status, text) = loop {
+            let response = self.read_response()?;
+            let invalid = || ControllerError::InvalidResponse(response.clone());
+            let status = response.chars().next().ok_or_else(invalid)?;
+            let rest = response.get(1..).ok_or_else(invalid)?;
+            let digits = rest.chars().take_while(|x| x.is_ascii_digit()).count();
+            match rest[..digits].parse::<u32>().ok() {
+                Some(found) if found < id => continue,
+                Some(found) if found > id => return Err(invalid()),
+                _ => {}
+            }
+            let text = rest[digits..].trim().to_string();
+            break (response, status, text);
+        };
+        let invalid = || ControllerError::InvalidResponse(response.clone());
+        match status {
+            '=' => Ok(text),
+            '?' => Err(ControllerError::Engine(text)),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Lines up to the empty line that ends a response, skipping empty lines before it.
+    fn read_response(&mut self) -> Result<String, ControllerError> {
+        let mut lines = vec![];
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(ControllerError::Closed);
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            match (line.trim().is_empty(), lines.is_empty()) {
+                (true, true) => continue,
+                (true, false) => return Ok(lines.join("\n")),
+                (false, _) => lines.push(line.to_string()),
+            }
+        }
+    }
+
+    pub fn protocol_version(&mut self) -> Result<u32, ControllerError> {
+        let text = self.send("protocol_version", &[])?;
+        text.parse()
+            .map_err(|_| ControllerError::InvalidResponse(text))
+    }
+
+    pub fn name(&mut self) -> Result<String, ControllerError> {
+        self.send("name", &[])
+    }
+
+    pub fn version(&mut self) -> Result<String, ControllerError> {
+        self.send("version", &[])
+    }
+
+    pub fn list_commands(&mut self) -> Result<Vec<String>, ControllerError> {
+        Ok(self
+            .send("list_commands", &[])?
+            .lines()
+            .map(|x| x.trim().to_string())
+            .collect())
+    }
+
+    /// Changes the board size, which also clears the board.
+    pub fn boardsize(&mut self, size: u16) -> Result<(), ControllerError> {
+        self.send("boardsize", &[&size.to_string()])?;
+        self.game = AnyGame::new((size, size));
+        Ok(())
+    }
+
+    pub fn clear_board(&mut self) -> Result<(), ControllerError> {
+        self.send("clear_board", &[])?;
+        self.game = AnyGame::new(self.game.get_size());
+        Ok(())
+    }
+
+    pub fn komi(&mut self, komi: f64) -> Result<(), ControllerError> {
+        self.send("komi", &[&komi.to_string()])?;
+        Ok(())
+    }
+
+    /// Sends a stone or a pass after checking it is legal in the game. Setup moves have no GTP
+    /// command.
+    pub fn play(&mut self, m: &Move) -> Result<(), ControllerError> {
+        let (player, vertex) = match m {
+            Move::PlaceStone(PlaceStoneMove { player, coord }) => (*player, GtpMove::Play(*coord)),
+            Move::Skip { player } => (*player, GtpMove::Pass),
+            Move::Setup(_) => return Err(ControllerError::UnsupportedMove),
+        };
+        let mut game = self.game.clone();
+        game.make_move(m)?;
+
+        let vertex = vertex.format(self.game.get_size())?;
+        self.send("play", &[format_color(player), &vertex])?;
+        self.game = game;
+        Ok(())
+    }
+
+    /// Asks the engine for a move and plays it in the game, a resignation ends the game.
+    pub fn genmove(&mut self, player: Player) -> Result<GtpMove, ControllerError> {
+        let text = self.send("genmove", &[format_color(player)])?;
+        let generated = GtpMove::parse(&text, self.game.get_size())?;
+        match generated {
+            GtpMove::Play(coord) => {
+                self.game
+                    .make_move(&Move::PlaceStone(PlaceStoneMove { player, coord }))?;
+            }
+            GtpMove::Pass => {
+                self.game.make_move(&Move::Skip { player })?;
+            }
+            GtpMove::Resign => self.game.end(GameResult::Resignation { winner: !player }),
+        }
+        Ok(generated)
+    }
+
+    pub fn undo(&mut self) -> Result<(), ControllerError> {
+        self.send("undo", &[])?;
+        self.game.undo();
+        Ok(())
+    }
+
+    /// Places handicap stones where the engine puts them, white moves next.
+    pub fn fixed_handicap(
+        &mut self,
+        stones: u8,
+    ) -> Result<Vec<FlexibleCoordinate>, ControllerError> {
+        let text = self.send("fixed_handicap", &[&stones.to_string()])?;
+        let coords = self.parse_vertices(&text)?;
+        self.game.make_move(&Move::Setup(SetupMove {
+            add_black: CoordinateSet::new(coords.clone()),
+            add_white: CoordinateSet::new(vec![]),
+            clear: CoordinateSet::new(vec![]),
+            player_to_move: Some(Player::White),
+        }))?;
+        Ok(coords)
+    }
+
+    pub fn final_score(&mut self) -> Result<FinalScore, ControllerError> {
+        Ok(self.send("final_score", &[])?.parse()?)
+    }
+
+    pub fn final_status_list(
+        &mut self,
+        status: StoneStatus,
+    ) -> Result<Vec<FlexibleCoordinate>, ControllerError> {
+        let text = self.send("final_status_list", &[&status.to_string()])?;
+        self.parse_vertices(&text)
+    }
+
+    /// The board as the engine draws it, the format differs between engines.
+    pub fn showboard(&mut self) -> Result<String, ControllerError> {
+        self.send("showboard", &[])
+    }
+
+    /// Ends the session and waits for a subprocess to exit.
+    pub fn quit(mut self) -> Result<(), ControllerError> {
+        self.send("quit", &[])?;
+        if let Some(mut child) = self.child.take() {
+            child.wait()?;
+        }
+        Ok(())
+    }
+
+    /// Vertices separated by spaces or line breaks.
+    fn parse_vertices(&self, text: &str) -> Result<Vec<FlexibleCoordinate>, ControllerError> {
+        text.split_whitespace()
+            .map(|x| match GtpMove::parse(x, self.game.get_size())? {
+                GtpMove::Play(coord) => Ok(coord),
+                _ => Err(ControllerError::InvalidResponse(text.to_string())),
+            })
+            .collect()
+    }
+}
+
+impl<TReader: BufRead, TWriter: Write> Drop for GtpController<TReader, TWriter> {
+    /// Stops a subprocess that was not ended with [`GtpController::quit`].
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl ControllerError {
+    /// Whether the engine did not answer in time, see [`GtpController::set_timeout`].
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ControllerError::Io(error) if error.kind() == io::ErrorKind::TimedOut)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ControllerError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("The engine closed the connection.")]
+    Closed,
+    #[error("The engine answered with an error: {0}")]
+    Engine(String),
+    #[error("Could not understand the response '{0}'.")]
+    InvalidResponse(String),
+    #[error(transparent)]
+    Gtp(#[from] GtpError),
+    /// The move is illegal in the game, or the engine played one.
+    #[error(transparent)]
+    Move(#[from] MoveError),
+    #[error("Setup moves can not be sent over GTP.")]
+    UnsupportedMove,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_scripted_engine_when_driven_then_responses_should_be_typed_and_game_in_sync() {
+        // Given
+        let responses = "=1 \n\n=2 \n\n= C3\n\n?4 illegal move\n\n=5 B+2.5\n\n=6\nC3\nD4\n\n";
+        let mut sent = vec![];
+        let mut controller = GtpController::new(responses.as_bytes(), &mut sent);
+
+        // When
+        controller.boardsize(5).expect("Expected boardsize");
+        let black = Move::PlaceStone(PlaceStoneMove {
+            player: Player::Black,
+            coord: FlexibleCoordinate { x: 3, y: 1 },
+        });
+        controller.play(&black).expect("Expected play");
+        let generated = controller.genmove(Player::White);
+        let refused = controller.play(&Move::PlaceStone(PlaceStoneMove {
+            player: Player::Black,
+            coord: FlexibleCoordinate { x: 0, y: 4 },
+        }));
+        let locally_illegal = controller.play(&black);
+        let score = controller.final_score();
+        let alive = controller.final_status_list(StoneStatus::Alive);
+        let game = controller.game().clone();
+        drop(controller);
+
+        // Then
+        let c3 = FlexibleCoordinate { x: 2, y: 2 };
+        assert_eq!(GtpMove::Play(c3), generated.expect("Expected genmove"));
+        assert!(matches!(refused, Err(ControllerError::Engine(x)) if x == "illegal move"));
+        assert!(matches!(
+            locally_illegal,
+            Err(ControllerError::Move(MoveError::CoordinateOccupied { .. }))
+        ));
+        assert_eq!(
+            FinalScore {
+                winner: Some(Player::Black),
+                margin: Some(2.5)
+            },
+            score.expect("Expected a score")
+        );
+        assert_eq!(
+            vec![c3, FlexibleCoordinate { x: 3, y: 1 }],
+            alive.expect("Expected vertices")
+        );
+        assert_eq!(Some(Player::White), game.get_player_at(&c3));
+        assert_eq!(None, game.get_player_at(&FlexibleCoordinate { x: 0, y: 4 }));
+        assert_eq!(Player::Black, game.get_current_player());
+        assert_eq!(
+            "1 boardsize 5\n2 play B D4\n3 genmove W\n4 play B A1\n5 final_score\n6 final_status_list alive\n",
+            String::from_utf8(sent).expect("Expected utf8")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_stub_engine_process_when_spawned_then_it_should_answer_over_pipes() {
+        // Given
+        let script = r#"while read -r id command rest; do
+  case "$command" in
+    name) printf '=%s stub\n\n' "$id" ;;
+    genmove) printf '=%s E5\n\n' "$id" ;;
+    quit) printf '=%s\n\n' "$id"; exit 0 ;;
+    *) printf '=%s\n\n' "$id" ;;
+  esac
+done"#;
+        let mut controller =
+            ProcessController::spawn("sh", &["-c", script]).expect("Expected sh to start");
+
+        // When
+        controller.boardsize(9).expect("Expected boardsize");
+        let name = controller.name().expect("Expected name");
+        let generated = controller.genmove(Player::Black).expect("Expected genmove");
+        let stone = controller
+            .game()
+            .get_player_at(&FlexibleCoordinate { x: 4, y: 4 });
+
+        // Then
+        assert_eq!("stub", name);
+        assert_eq!(GtpMove::Play(FlexibleCoordinate { x: 4, y: 4 }), generated);
+        assert_eq!(Some(Player::Black), stone);
+        controller.quit().expect("Expected the stub to quit");
+    }
+}
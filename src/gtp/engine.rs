@@ -0,0 +1,421 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    go::{
+        any_game::AnyGame,
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        game::{GameResult, MoveError},
+        handicap::fixed_handicap_placement,
+        player::Player,
+        playermove::{Move, PlaceStoneMove, SetupMove},
+        render::GridStyle,
+    },
+    gtp::{Command, GtpMove, format_response, parse_color},
+    parser::gsf::{ParsedGame, parse_sgf_bytes},
+};
+
+const COMMANDS: [&str; 17] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "komi",
+    "play",
+    "genmove",
+    "undo",
+    "showboard",
+    "final_score",
+    "final_status_list",
+    "loadsgf",
+    "fixed_handicap",
+];
+
+/// Picks the moves for `genmove`.
+pub trait MoveGenerator {
+    /// A move for `player`, the engine plays it on the game afterwards. Illegal moves are
+    /// answered with an error.
+    fn generate(&mut self, game: &AnyGame, player: Player) -> GtpMove;
+}
+
+/// Plays a random legal move that does not fill one of its own eyes, and passes when there is
+/// none left.
+#[derive(Debug, Clone)]
+pub struct RandomMoveGenerator {
+    state: u64,
+}
+
+impl RandomMoveGenerator {
+    pub fn new(seed: u64) -> Self {
+        // Xorshift gets stuck on 0.
+        Self { state: seed | 1 }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+impl MoveGenerator for RandomMoveGenerator {
+    fn generate(&mut self, game: &AnyGame, player: Player) -> GtpMove {
+        let (width, height) = game.get_size();
+        let mut candidates: Vec<FlexibleCoordinate> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| FlexibleCoordinate { x, y }))
+            .filter(|coord| game.get_player_at(coord).is_none() && !is_eye(game, coord, player))
+            .collect();
+
+        while !candidates.is_empty() {
+            let index = (self.next() % candidates.len() as u64) as usize;
+            let coord = candidates.swap_remove(index);
+            if game.is_legal(&Move::PlaceStone(PlaceStoneMove { player, coord })) {
+                return GtpMove::Play(coord);
+            }
+        }
+        GtpMove::Pass
+    }
+}
+
+/// An empty point with only stones of `player` next to it.
+fn is_eye(game: &AnyGame, coord: &FlexibleCoordinate, player: Player) -> bool {
+    let mut neighbours = CoordinateSet::new(vec![*coord]).grow(game.get_size());
+    neighbours.remove(coord);
+    neighbours
+        .iter()
+        .all(|x| game.get_player_at(x) == Some(player))
+}
+
+/// A Go Text Protocol version 2 engine on top of [`AnyGame`].
+///
+/// Scores by area with every stone alive, `final_status_list dead` is always empty. Boards go up
+/// to 25x25, the largest size GTP coordinates can describe.
+pub struct GtpEngine<TGenerator: MoveGenerator> {
+    game: AnyGame,
+    komi: f64,
+    generator: TGenerator,
+}
+
+impl<TGenerator: MoveGenerator> GtpEngine<TGenerator> {
+    /// An engine with an empty 19x19 board and a komi of 7.5.
+    pub fn new(generator: TGenerator) -> Self {
+        Self {
+            game: AnyGame::new((19, 19)),
+            komi: 7.5,
+            generator,
+        }
+    }
+
+    pub fn game(&self) -> &AnyGame {
+        &self.game
+    }
+
+    pub fn komi(&self) -> f64 {
+        self.komi
+    }
+
+    /// Answers commands from `input` until it ends or `quit` is received.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let Some(command) = Command::parse(&line?) else {
+                continue;
+            };
+            let response = self.execute(&command);
+            output.write_all(format_response(command.id, &response).as_bytes())?;
+            output.flush()?;
+            if command.name == "quit" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The text of the response to a command, errors hold the text of a failure response.
+    pub fn execute(&mut self, command: &Command) -> Result<String, String> {
+        let args: Vec<&str> = command.args.iter().map(|x| x.as_str()).collect();
+        let size = self.game.get_size();
+        let syntax_error = || "syntax error".to_string();
+
+        match (command.name.as_str(), args.as_slice()) {
+            ("protocol_version", []) => Ok("2".to_string()),
+            ("name", []) => Ok("go-lib".to_string()),
+            ("version", []) => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            ("known_command", [name]) => Ok(COMMANDS.contains(name).to_string()),
+            ("list_commands", []) => Ok(COMMANDS.join("\n")),
+            ("quit", []) => Ok(String::new()),
+            ("boardsize", [size]) => {
+                let size: u16 = size.parse().map_err(|_| syntax_error())?;
+                if !(2..=25).contains(&size) {
+                    return Err("unacceptable size".to_string());
+                }
+                self.game = AnyGame::new((size, size));
+                Ok(String::new())
+            }
+            ("clear_board", []) => {
+                self.game = AnyGame::new(size);
+                Ok(String::new())
+            }
+            ("komi", [komi]) => {
+                self.komi = komi.parse().map_err(|_| syntax_error())?;
+                Ok(String::new())
+            }
+            ("play", [color, vertex]) => {
+                let player = parse_color(color).map_err(|_| syntax_error())?;
+                let m = match GtpMove::parse(vertex, size).map_err(|_| syntax_error())? {
+                    GtpMove::Play(coord) => Move::PlaceStone(PlaceStoneMove { player, coord }),
+                    GtpMove::Pass => Move::Skip { player },
+                    GtpMove::Resign => return Err(syntax_error()),
+                };
+                self.game
+                    .make_move(&m)
+                    .map_err(|_| "illegal move".to_string())?;
+                Ok(String::new())
+            }
+            ("genmove", [color]) => {
+                let player = parse_color(color).map_err(|_| syntax_error())?;
+                if self.game.is_over() {
+                    return Err("game is over".to_string());
+                }
+                let generated = self.generator.generate(&self.game, player);
+                match generated {
+                    GtpMove::Play(coord) => {
+                        self.game
+                            .make_move(&Move::PlaceStone(PlaceStoneMove { player, coord }))
+                            .map_err(|_| "generated an illegal move".to_string())?;
+                    }
+                    GtpMove::Pass => {
+                        self.game
+                            .make_move(&Move::Skip { player })
+                            .map_err(|_| "generated an illegal move".to_string())?;
+                    }
+                    GtpMove::Resign => {
+                        self.game.end(GameResult::Resignation { winner: !player });
+                    }
+                }
+                generated.format(size).map_err(|x| x.to_string())
+            }
+            ("undo", []) => match self.game.undo() {
+                true => Ok(String::new()),
+                false => Err("cannot undo".to_string()),
+            },
+            ("showboard", []) => Ok(format!("\n{}", self.game.render(GridStyle::Ascii))),
+            ("final_score", []) => Ok(self
+                .game
+                .area_score(self.komi, &CoordinateSet::new(vec![]))
+                .to_string()),
+            ("final_status_list", [status]) => match *status {
+                "alive" => Ok(self.vertices(occupied)),
+                "dead" | "seki" => Ok(String::new()),
+                _ => Err(syntax_error()),
+            },
+            ("loadsgf", [file]) => self.load_sgf(file, None),
+            ("loadsgf", [file, move_number]) => {
+                let move_number = move_number.parse().map_err(|_| syntax_error())?;
+                self.load_sgf(file, Some(move_number))
+            }
+            ("fixed_handicap", [stones]) => {
+                let stones: u8 = stones.parse().map_err(|_| syntax_error())?;
+                if !self.game.get_history().is_empty() || !self.vertices(occupied).is_empty() {
+                    return Err("board not empty".to_string());
+                }
+                let coords = fixed_handicap_placement(size, stones)
+                    .map_err(|_| "invalid number of stones".to_string())?;
+                self.game
+                    .make_move(&Move::Setup(SetupMove {
+                        add_black: coords,
+                        add_white: CoordinateSet::new(vec![]),
+                        clear: CoordinateSet::new(vec![]),
+                        player_to_move: Some(Player::White),
+                    }))
+                    .expect("Placing stones on an empty board is allowed");
+                Ok(self.vertices(occupied))
+            }
+            (name, _) if COMMANDS.contains(&name) => Err(syntax_error()),
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    /// Loads the position before `move_number`, or after the last move without one.
+    fn load_sgf(&mut self, file: &str, move_number: Option<usize>) -> Result<String, String> {
+        let cannot_load = || "cannot load file".to_string();
+        let bytes = std::fs::read(file).map_err(|_| cannot_load())?;
+        let parsed = parse_sgf_bytes(&bytes).map_err(|_| cannot_load())?;
+        if parsed.width > 25 || parsed.height > 25 {
+            return Err(cannot_load());
+        }
+
+        let moves = moves_before(&parsed, move_number);
+        self.game = replay((parsed.width, parsed.height), moves).map_err(|_| cannot_load())?;
+        if let Some(komi) = parsed.komi {
+            self.komi = komi;
+        }
+        let next = match self.game.get_current_player() {
+            Player::Black => "black",
+            Player::White => "white",
+        };
+        Ok(next.to_string())
+    }
+
+    /// Vertices of the points matching `filter` in reading order, separated by spaces.
+    fn vertices(&self, filter: impl Fn(&AnyGame, &FlexibleCoordinate) -> bool) -> String {
+        let size = self.game.get_size();
+        (0..size.1)
+            .flat_map(|y| (0..size.0).map(move |x| FlexibleCoordinate { x, y }))
+            .filter(|coord| filter(&self.game, coord))
+            .filter_map(|coord| GtpMove::Play(coord).format(size).ok())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn occupied(game: &AnyGame, coord: &FlexibleCoordinate) -> bool {
+    game.get_player_at(coord).is_some()
+}
+
+/// The moves up to the `move_number`th stone or pass, counted from 1 and without setup moves.
+fn moves_before(parsed: &ParsedGame, move_number: Option<usize>) -> &[Move] {
+    let Some(move_number) = move_number else {
+        return &parsed.moves;
+    };
+    let mut played = 0;
+    for (index, m) in parsed.moves.iter().enumerate() {
+        if !matches!(m, Move::Setup(_)) {
+            played += 1;
+            if played >= move_number {
+                return &parsed.moves[..index];
+            }
+        }
+    }
+    &parsed.moves
+}
+
+fn replay(board_size: (u16, u16), moves: &[Move]) -> Result<AnyGame, MoveError> {
+    let mut game = AnyGame::new(board_size);
+    for m in moves {
+        game.make_move(m)?;
+    }
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays the moves it is given in order, and resigns when they run out.
+    struct ScriptedGenerator(Vec<GtpMove>);
+
+    impl MoveGenerator for ScriptedGenerator {
+        fn generate(&mut self, _: &AnyGame, _: Player) -> GtpMove {
+            if self.0.is_empty() {
+                GtpMove::Resign
+            } else {
+                self.0.remove(0)
+            }
+        }
+    }
+
+    fn session(engine: &mut GtpEngine<impl MoveGenerator>, input: &str) -> String {
+        let mut output = vec![];
+        engine
+            .run(input.as_bytes(), &mut output)
+            .expect("Expected writing to a vector to work");
+        String::from_utf8(output).expect("Expected utf8 output")
+    }
+
+    #[test]
+    fn given_session_when_run_then_every_command_should_get_a_response() {
+        // Given
+        let mut engine =
+            GtpEngine::new(ScriptedGenerator(vec![GtpMove::Play(FlexibleCoordinate {
+                x: 2,
+                y: 2,
+            })]));
+        let input = "1 boardsize 5
+2 komi 0.5
+3 play b B2
+4 play w b2
+5 genmove w
+6 undo
+7 genmove white
+8 showboard
+9 final_score
+10 final_status_list alive
+11 fixed_handicap 2
+12 frobnicate
+13 genmove b
+quit
+play b a1
+";
+
+        // When
+        let output = session(&mut engine, input);
+
+        // Then
+        let expected = "=1 \n\n=2 \n\n=3 \n\n?4 illegal move\n\n=5 C3\n\n=6 \n\n=7 resign\n\n=8
+   A B C D E
+5  . . . . .
+4  . . . . .
+3  . . . . .
+2  .(X). . .
+1  . . . . .
+
+=9 B+24.5\n\n=10 B2\n\n?11 board not empty\n\n?12 unknown command\n\n?13 game is over\n\n= \n\n";
+        assert_eq!(expected, output);
+        assert!(engine.game().is_over());
+    }
+
+    #[test]
+    fn given_sgf_file_when_loaded_then_it_should_stop_before_the_move_number() {
+        // Given
+        let path =
+            std::env::temp_dir().join(format!("go-lib-gtp-loadsgf-{}.sgf", std::process::id()));
+        std::fs::write(
+            &path,
+            "(;GM[1]SZ[9]KM[5.5]HA[2]AB[cg][gc];W[ee];B[ff];W[dd])",
+        )
+        .expect("Expected temp file to be writable");
+        let mut engine = GtpEngine::new(RandomMoveGenerator::new(7));
+        let path = path.to_str().expect("Expected utf8 path");
+
+        // When
+        let res = engine.execute(&Command::new("loadsgf", &[path, "3"]));
+
+        // Then
+        assert_eq!(Ok("white".to_string()), res);
+        assert_eq!(5.5, engine.komi());
+        assert_eq!(
+            Ok("G7 E5 F4 C3".to_string()),
+            engine.execute(&Command::new("final_status_list", &["alive"]))
+        );
+        std::fs::remove_file(path).expect("Expected temp file to be removable");
+    }
+
+    #[test]
+    fn given_random_generator_when_moves_are_generated_then_they_should_be_legal() {
+        // Given
+        let mut engine = GtpEngine::new(RandomMoveGenerator::new(42));
+        engine
+            .execute(&Command::new("boardsize", &["7"]))
+            .expect("Expected 7x7 to be accepted");
+        engine
+            .execute(&Command::new("fixed_handicap", &["4"]))
+            .expect("Expected handicap on an empty board");
+
+        // When
+        let moves: Vec<_> = (0..20)
+            .map(|i| {
+                let color = if i % 2 == 0 { "w" } else { "b" };
+                engine.execute(&Command::new("genmove", &[color]))
+            })
+            .collect();
+
+        // Then
+        assert!(moves.iter().all(|x| x.is_ok()));
+        assert_eq!(1 + 20, engine.game().get_history().len());
+    }
+}
@@ -0,0 +1,231 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::go::{
+    coordinate::FlexibleCoordinate,
+    coordinate_notation::{CoordinateError, CoordinateNotation},
+    player::Player,
+};
+
+pub mod controller;
+pub mod engine;
+pub mod referee;
+
+/// A line of the Go Text Protocol, `[id] command_name [arguments]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub id: Option<u32>,
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+impl Command {
+    pub fn new(name: &str, args: &[&str]) -> Self {
+        Self {
+            id: None,
+            name: name.to_string(),
+            args: args.iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    /// Parses a line after removing comments and control characters, None for lines that are
+    /// empty afterwards.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.split('#').next().unwrap_or_default();
+        let line: String = line
+            .chars()
+            .filter_map(|c| match c {
+                '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+
+        let mut words = line.split_whitespace().peekable();
+        let id = words.peek().and_then(|x| x.parse().ok());
+        if id.is_some() {
+            words.next();
+        }
+        let name = words.next()?.to_string();
+        Some(Self {
+            id,
+            name,
+            args: words.map(|x| x.to_string()).collect(),
+        })
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(id) = self.id {
+            write!(f, "{id} ")?;
+        }
+        write!(f, "{}", self.name)?;
+        for arg in &self.args {
+            write!(f, " {arg}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a response as `=[id] text` or `?[id] error`, ending with the empty line that closes
+/// it. Text that starts with a line break, like a board, starts on the line after the status.
+pub fn format_response(id: Option<u32>, response: &Result<String, String>) -> String {
+    let (status, text) = match response {
+        Ok(text) => ('=', text),
+        Err(text) => ('?', text),
+    };
+    let id = id.map(|x| x.to_string()).unwrap_or_default();
+    let separator = if text.starts_with('\n') { "\n" } else { " " };
+    // Empty lines would end the response early.
+    let lines: Vec<&str> = text.lines().filter(|x| !x.trim().is_empty()).collect();
+    format!("{status}{id}{separator}{}\n\n", lines.join("\n"))
+}
+
+/// Parses `b`, `black`, `w` or `white`, ignoring case.
+pub fn parse_color(text: &str) -> Result<Player, GtpError> {
+    match text.to_ascii_lowercase().as_str() {
+        "b" | "black" => Ok(Player::Black),
+        "w" | "white" => Ok(Player::White),
+        _ => Err(GtpError::InvalidColor(text.to_string())),
+    }
+}
+
+pub fn format_color(player: Player) -> &'static str {
+    match player {
+        Player::Black => "B",
+        Player::White => "W",
+    }
+}
+
+/// A vertex, or the `resign` that `genmove` may answer with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GtpMove {
+    Play(FlexibleCoordinate),
+    Pass,
+    Resign,
+}
+
+impl GtpMove {
+    pub fn parse(text: &str, board_size: (u16, u16)) -> Result<Self, GtpError> {
+        match text.to_ascii_lowercase().as_str() {
+            "pass" => Ok(GtpMove::Pass),
+            "resign" => Ok(GtpMove::Resign),
+            _ => Ok(GtpMove::Play(
+                CoordinateNotation::Gtp.parse(text, board_size)?,
+            )),
+        }
+    }
+
+    pub fn format(&self, board_size: (u16, u16)) -> Result<String, GtpError> {
+        match self {
+            GtpMove::Play(coord) => Ok(CoordinateNotation::Gtp.format(coord, board_size)?),
+            GtpMove::Pass => Ok("pass".to_string()),
+            GtpMove::Resign => Ok("resign".to_string()),
+        }
+    }
+}
+
+/// Answer to `final_score`, like `B+3.5`, `W+R` or `0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FinalScore {
+    /// None for a draw.
+    pub winner: Option<Player>,
+    /// Points the winner is ahead by, None when the engine does not give them.
+    pub margin: Option<f64>,
+}
+
+impl FromStr for FinalScore {
+    type Err = GtpError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || GtpError::InvalidScore(s.to_string());
+        let text = s.trim();
+        if text == "0" {
+            return Ok(Self {
+                winner: None,
+                margin: Some(0.0),
+            });
+        }
+
+        let (color, margin) = text.split_once('+').ok_or_else(invalid)?;
+        let winner = parse_color(color).map_err(|_| invalid())?;
+        Ok(Self {
+            winner: Some(winner),
+            margin: margin.parse().ok(),
+        })
+    }
+}
+
+/// The statuses `final_status_list` can be asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoneStatus {
+    Alive,
+    Dead,
+    Seki,
+}
+
+impl Display for StoneStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoneStatus::Alive => write!(f, "alive"),
+            StoneStatus::Dead => write!(f, "dead"),
+            StoneStatus::Seki => write!(f, "seki"),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum GtpError {
+    #[error("'{0}' is not a color")]
+    InvalidColor(String),
+    #[error("'{0}' is not a score")]
+    InvalidScore(String),
+    #[error(transparent)]
+    InvalidVertex(#[from] CoordinateError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_line_with_id_and_comment_when_parsed_then_it_should_keep_the_command() {
+        // Given
+        let line = "12 play\tB d4 # opening\r";
+
+        // When
+        let command = Command::parse(line).expect("Expected a command");
+
+        // Then
+        assert_eq!(
+            Command {
+                id: Some(12),
+                name: "play".to_string(),
+                args: vec!["B".to_string(), "d4".to_string()]
+            },
+            command
+        );
+        assert_eq!("12 play B d4", command.to_string());
+        assert_eq!(None, Command::parse("   # only a comment"));
+    }
+
+    #[test]
+    fn given_responses_when_formatted_then_they_should_end_with_an_empty_line() {
+        assert_eq!("=3 D4\n\n", format_response(Some(3), &Ok("D4".to_string())));
+        assert_eq!(
+            "? illegal move\n\n",
+            format_response(None, &Err("illegal move".to_string()))
+        );
+        assert_eq!(
+            "=\n   A B\n2  . .\n1  . .\n\n",
+            format_response(None, &Ok("\n   A B\n2  . .\n\n1  . .\n".to_string()))
+        );
+        assert_eq!(
+            Ok(GtpMove::Play(FlexibleCoordinate { x: 8, y: 0 })),
+            GtpMove::parse("j19", (19, 19))
+        );
+        assert_eq!(Ok(GtpMove::Pass), GtpMove::parse("PASS", (19, 19)));
+    }
+}
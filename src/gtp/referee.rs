@@ -0,0 +1,597 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{
+    go::{
+        any_game::AnyGame,
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        game::GameResult,
+        player::Player,
+        playermove::{Move, PlaceStoneMove},
+        rules::{PositionHistory, Rules},
+    },
+    gtp::{
+        GtpMove, StoneStatus,
+        controller::{ControllerError, ProcessController},
+        engine::MoveGenerator,
+    },
+    parser::gsf::{ParsedGame, properties::NodeProperties, writer::write_sgf},
+};
+
+/// One side of a match, a GTP engine or a built-in bot.
+pub trait MatchPlayer {
+    fn name(&self) -> String;
+
+    /// Starts an empty game on a square board.
+    fn new_game(&mut self, board_size: u16, komi: f64) -> Result<(), PlayerError>;
+
+    /// Tells the player about a move of the opponent.
+    fn play(&mut self, m: &Move) -> Result<(), PlayerError>;
+
+    /// Asks the player for a move, taking longer than `time_limit` is a timeout.
+    fn genmove(
+        &mut self,
+        player: Player,
+        time_limit: Option<Duration>,
+    ) -> Result<GtpMove, PlayerError>;
+
+    /// The stones the player considers dead at the end of the game, None when it can not tell.
+    fn dead_stones(&mut self) -> Option<Vec<FlexibleCoordinate>> {
+        None
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PlayerError {
+    #[error("The player did not answer in time.")]
+    Timeout,
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// A built-in bot playing the moves of a [`MoveGenerator`].
+///
+/// The time limit can only be checked after the generator returns.
+pub struct Bot<TGenerator: MoveGenerator> {
+    name: String,
+    generator: TGenerator,
+    game: AnyGame,
+}
+
+impl<TGenerator: MoveGenerator> Bot<TGenerator> {
+    pub fn new(name: &str, generator: TGenerator) -> Self {
+        Self {
+            name: name.to_string(),
+            generator,
+            game: AnyGame::new((19, 19)),
+        }
+    }
+}
+
+impl<TGenerator: MoveGenerator> MatchPlayer for Bot<TGenerator> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn new_game(&mut self, board_size: u16, _komi: f64) -> Result<(), PlayerError> {
+        self.game = AnyGame::new((board_size, board_size));
+        Ok(())
+    }
+
+    fn play(&mut self, m: &Move) -> Result<(), PlayerError> {
+        self.game
+            .make_move(m)
+            .map(|_| ())
+            .map_err(|error| PlayerError::Failed(error.to_string()))
+    }
+
+    fn genmove(
+        &mut self,
+        player: Player,
+        time_limit: Option<Duration>,
+    ) -> Result<GtpMove, PlayerError> {
+        let start = Instant::now();
+        let generated = self.generator.generate(&self.game, player);
+        if time_limit.is_some_and(|limit| start.elapsed() > limit) {
+            return Err(PlayerError::Timeout);
+        }
+        // The referee decides what happens with an illegal move.
+        if let Some(m) = to_move(generated, player) {
+            let _ = self.game.make_move(&m);
+        }
+        Ok(generated)
+    }
+}
+
+/// A GTP engine running as a subprocess, named after its `name` and `version`.
+pub struct EnginePlayer {
+    name: String,
+    controller: ProcessController,
+}
+
+impl EnginePlayer {
+    pub fn new(mut controller: ProcessController) -> Result<Self, ControllerError> {
+        let name = controller.name()?;
+        let name = match controller.version() {
+            Ok(version) if !version.is_empty() => format!("{name} {version}"),
+            _ => name,
+        };
+        Ok(Self { name, controller })
+    }
+}
+
+impl MatchPlayer for EnginePlayer {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn new_game(&mut self, board_size: u16, komi: f64) -> Result<(), PlayerError> {
+        self.controller
+            .boardsize(board_size)
+            .and_then(|_| self.controller.clear_board())
+            .and_then(|_| self.controller.komi(komi))
+            .map_err(|error| PlayerError::Failed(error.to_string()))
+    }
+
+    fn play(&mut self, m: &Move) -> Result<(), PlayerError> {
+        self.controller
+            .play(m)
+            .map_err(|error| PlayerError::Failed(error.to_string()))
+    }
+
+    fn genmove(
+        &mut self,
+        player: Player,
+        time_limit: Option<Duration>,
+    ) -> Result<GtpMove, PlayerError> {
+        self.controller.set_timeout(time_limit);
+        let res = self.controller.genmove(player);
+        self.controller.set_timeout(None);
+        res.map_err(|error| match error {
+            error if error.is_timeout() => PlayerError::Timeout,
+            // Judging the move is up to the referee.
+            ControllerError::Move(_) => PlayerError::Failed("illegal move".to_string()),
+            error => PlayerError::Failed(error.to_string()),
+        })
+    }
+
+    fn dead_stones(&mut self) -> Option<Vec<FlexibleCoordinate>> {
+        self.controller.final_status_list(StoneStatus::Dead).ok()
+    }
+}
+
+/// How the games of a match are played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchSettings {
+    pub games: u32,
+    pub board_size: u16,
+    pub komi: f64,
+    pub rules: Rules,
+    /// Time for a single move, None for no limit.
+    pub time_limit: Option<Duration>,
+    /// Games still going after this many moves are scored as they are.
+    pub max_moves: usize,
+    /// Directory to write `game-001.sgf`, `game-002.sgf`, ... into.
+    pub sgf_dir: Option<PathBuf>,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            games: 10,
+            board_size: 19,
+            komi: Rules::default().default_komi(),
+            rules: Rules::default(),
+            time_limit: None,
+            max_moves: 1000,
+            sgf_dir: None,
+        }
+    }
+}
+
+/// A finished game of a match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub black: String,
+    pub white: String,
+    pub result: GameResult,
+    pub sgf: String,
+}
+
+/// Plays a single game, with [`AnyGame`] under `settings.rules` deciding which moves are legal.
+///
+/// A player that times out loses on time, a player that fails, plays an illegal move or refuses
+/// a legal move of the opponent forfeits. Two passes in a row or reaching `max_moves` end the game
+/// with a count, dead stones are only removed when both players agree on them.
+pub fn play_game(
+    black: &mut dyn MatchPlayer,
+    white: &mut dyn MatchPlayer,
+    settings: &MatchSettings,
+    name: &str,
+) -> GameRecord {
+    let size = settings.board_size;
+    let mut game = AnyGame::new((size, size));
+    // Why a game was forfeited, for the comment of the last node.
+    let mut reason = None;
+    let result = play_moves(&mut game, black, white, settings, &mut reason);
+    game.end(result.clone());
+
+    let mut properties = vec![NodeProperties::default(); game.get_history().len()];
+    let mut root_properties = NodeProperties::default();
+    properties
+        .last_mut()
+        .unwrap_or(&mut root_properties)
+        .comment = reason;
+    let parsed = ParsedGame {
+        width: size,
+        height: size,
+        black_player: Some(black.name()),
+        white_player: Some(white.name()),
+        komi: Some(settings.komi),
+        result: Some(result.to_string()),
+        rules: Some(settings.rules.sgf_name().to_string()),
+        game_name: Some(name.to_string()),
+        root_properties,
+        moves: game.get_history().to_vec(),
+        properties,
+        ..Default::default()
+    };
+    GameRecord {
+        black: black.name(),
+        white: white.name(),
+        result,
+        sgf: write_sgf(&parsed),
+    }
+}
+
+fn play_moves(
+    game: &mut AnyGame,
+    black: &mut dyn MatchPlayer,
+    white: &mut dyn MatchPlayer,
+    settings: &MatchSettings,
+    reason: &mut Option<String>,
+) -> GameResult {
+    let size = settings.board_size;
+    if let Err(error) = black.new_game(size, settings.komi) {
+        *reason = Some(format!("Black could not start: {error}"));
+        return GameResult::Forfeit {
+            winner: Player::White,
+        };
+    }
+    if let Err(error) = white.new_game(size, settings.komi) {
+        *reason = Some(format!("White could not start: {error}"));
+        return GameResult::Forfeit {
+            winner: Player::Black,
+        };
+    }
+
+    let mut positions = PositionHistory::default();
+    positions.insert(game);
+    let mut passes = 0;
+    loop {
+        if passes >= 2 || game.get_history().len() >= settings.max_moves {
+            return score(game, black, white, settings);
+        }
+        let player = game.get_current_player();
+        let (mover, opponent): (&mut dyn MatchPlayer, &mut dyn MatchPlayer) = match player {
+            Player::Black => (&mut *black, &mut *white),
+            Player::White => (&mut *white, &mut *black),
+        };
+
+        let generated = match mover.genmove(player, settings.time_limit) {
+            Ok(generated) => generated,
+            Err(PlayerError::Timeout) => return GameResult::Timeout { winner: !player },
+            Err(PlayerError::Failed(error)) => {
+                *reason = Some(format!("{player:?} failed: {error}"));
+                return GameResult::Forfeit { winner: !player };
+            }
+        };
+        let Some(m) = to_move(generated, player) else {
+            return GameResult::Resignation { winner: !player };
+        };
+
+        let mut next = game.clone();
+        let legal = next.make_move(&m).is_ok()
+            && (!settings.rules.superko()
+                || matches!(m, Move::Skip { .. })
+                || !positions.contains(&next));
+        if !legal {
+            let vertex = generated.format((size, size)).unwrap_or_default();
+            *reason = Some(format!("{player:?} played the illegal move {vertex}"));
+            return GameResult::Forfeit { winner: !player };
+        }
+        *game = next;
+        positions.insert(game);
+        passes = match m {
+            Move::Skip { .. } => passes + 1,
+            _ => 0,
+        };
+
+        if let Err(error) = opponent.play(&m) {
+            *reason = Some(format!("{:?} refused a legal move: {error}", !player));
+            return GameResult::Forfeit { winner: player };
+        }
+    }
+}
+
+fn to_move(generated: GtpMove, player: Player) -> Option<Move> {
+    match generated {
+        GtpMove::Play(coord) => Some(Move::PlaceStone(PlaceStoneMove { player, coord })),
+        GtpMove::Pass => Some(Move::Skip { player }),
+        GtpMove::Resign => None,
+    }
+}
+
+fn score(
+    game: &AnyGame,
+    black: &mut dyn MatchPlayer,
+    white: &mut dyn MatchPlayer,
+    settings: &MatchSettings,
+) -> GameResult {
+    let as_set = |stones: Vec<FlexibleCoordinate>| stones.into_iter().collect::<HashSet<_>>();
+    let dead = match (black.dead_stones(), white.dead_stones()) {
+        (Some(a), Some(b)) if as_set(a.clone()) == as_set(b.clone()) => a,
+        _ => vec![],
+    };
+    settings
+        .rules
+        .score(game, settings.komi, &CoordinateSet::new(dead))
+}
+
+/// Plays `settings.games` games, `first` takes black in the first game and the colours alternate
+/// after every game. Each record is passed to `on_game` once the game is over, and written to
+/// `settings.sgf_dir` when one is set.
+pub fn run_match(
+    first: &mut dyn MatchPlayer,
+    second: &mut dyn MatchPlayer,
+    settings: &MatchSettings,
+    mut on_game: impl FnMut(&GameRecord),
+) -> io::Result<MatchSummary> {
+    if let Some(dir) = &settings.sgf_dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut summary = MatchSummary {
+        first: first.name(),
+        second: second.name(),
+        ..Default::default()
+    };
+    for index in 0..settings.games {
+        let name = format!("Game {}", index + 1);
+        let first_is_black = index.is_multiple_of(2);
+        let record = if first_is_black {
+            play_game(first, second, settings, &name)
+        } else {
+            play_game(second, first, settings, &name)
+        };
+
+        if let Some(dir) = &settings.sgf_dir {
+            fs::write(dir.join(format!("game-{:03}.sgf", index + 1)), &record.sgf)?;
+        }
+        summary.add(&record.result, first_is_black);
+        on_game(&record);
+    }
+    Ok(summary)
+}
+
+/// Results of a match from the point of view of the first player.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MatchSummary {
+    pub first: String,
+    pub second: String,
+    pub first_wins: u32,
+    pub second_wins: u32,
+    pub draws: u32,
+    pub black_wins: u32,
+    pub white_wins: u32,
+}
+
+impl MatchSummary {
+    fn add(&mut self, result: &GameResult, first_is_black: bool) {
+        match result.winner() {
+            None => self.draws += 1,
+            Some(winner) => {
+                match winner {
+                    Player::Black => self.black_wins += 1,
+                    Player::White => self.white_wins += 1,
+                }
+                if (winner == Player::Black) == first_is_black {
+                    self.first_wins += 1;
+                } else {
+                    self.second_wins += 1;
+                }
+            }
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.first_wins + self.second_wins + self.draws
+    }
+
+    /// Share of the points the first player scored, a draw counts as half a win.
+    pub fn win_rate(&self) -> f64 {
+        match self.games() {
+            0 => 0.5,
+            games => (self.first_wins as f64 + self.draws as f64 / 2.0) / games as f64,
+        }
+    }
+
+    /// Wilson score interval of the win rate at a 95% confidence level.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let games = self.games() as f64;
+        if games == 0.0 {
+            return (0.0, 1.0);
+        }
+        let z: f64 = 1.96;
+        let rate = self.win_rate();
+        let denominator = 1.0 + z * z / games;
+        let centre = (rate + z * z / (2.0 * games)) / denominator;
+        let spread =
+            z * (rate * (1.0 - rate) / games + z * z / (4.0 * games * games)).sqrt() / denominator;
+        ((centre - spread).max(0.0), (centre + spread).min(1.0))
+    }
+}
+
+impl Display for MatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (low, high) = self.confidence_interval();
+        writeln!(
+            f,
+            "{} vs {}: {} wins, {} losses, {} draws in {} games",
+            self.first,
+            self.second,
+            self.first_wins,
+            self.second_wins,
+            self.draws,
+            self.games()
+        )?;
+        writeln!(
+            f,
+            "{} win rate {:.1}% (95% CI {:.1}% - {:.1}%)",
+            self.first,
+            self.win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0
+        )?;
+        write!(
+            f,
+            "Black won {}, white won {}",
+            self.black_wins, self.white_wins
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gtp::engine::RandomMoveGenerator;
+
+    struct ScriptedGenerator(Vec<GtpMove>);
+
+    impl MoveGenerator for ScriptedGenerator {
+        fn generate(&mut self, _: &AnyGame, _: Player) -> GtpMove {
+            if self.0.len() > 1 {
+                self.0.remove(0)
+            } else {
+                self.0[0]
+            }
+        }
+    }
+
+    fn scripted(name: &str, moves: &[GtpMove]) -> Bot<ScriptedGenerator> {
+        Bot::new(name, ScriptedGenerator(moves.to_vec()))
+    }
+
+    #[test]
+    fn given_bot_that_resigns_when_match_is_played_then_colours_should_alternate() {
+        // Given
+        let mut random = Bot::new("random", RandomMoveGenerator::new(7));
+        let mut resigner = scripted("resigner", &[GtpMove::Resign]);
+        let settings = MatchSettings {
+            games: 4,
+            board_size: 9,
+            ..Default::default()
+        };
+
+        // When
+        let mut records = vec![];
+        let summary = run_match(&mut random, &mut resigner, &settings, |x| {
+            records.push(x.clone())
+        })
+        .expect("Expected no files to be written");
+
+        // Then
+        let colours: Vec<_> = records.iter().map(|x| x.black.as_str()).collect();
+        assert_eq!(vec!["random", "resigner", "random", "resigner"], colours);
+        assert_eq!(4, summary.first_wins);
+        assert_eq!((2, 2), (summary.black_wins, summary.white_wins));
+        assert_eq!(1.0, summary.win_rate());
+        let (low, high) = summary.confidence_interval();
+        assert!((low - 0.51).abs() < 0.01, "{low}");
+        assert_eq!(1.0, high);
+        assert!(records[0].sgf.contains("PB[random]PW[resigner]"));
+        assert!(records[0].sgf.contains("RE[B+R]"));
+        assert!(records[1].sgf.contains("RE[W+R]"));
+        assert!(
+            summary
+                .to_string()
+                .contains("4 wins, 0 losses, 0 draws in 4 games")
+        );
+    }
+
+    #[test]
+    fn given_illegal_move_or_two_passes_when_game_is_played_then_it_should_be_adjudicated() {
+        // Given
+        let a9 = GtpMove::Play(FlexibleCoordinate { x: 0, y: 0 });
+        let mut repeater = scripted("repeater", &[a9]);
+        let mut passer = scripted("passer", &[GtpMove::Pass]);
+        let mut other_passer = scripted("other passer", &[GtpMove::Pass]);
+        let settings = MatchSettings {
+            board_size: 9,
+            ..Default::default()
+        };
+
+        // When
+        let forfeit = play_game(&mut repeater, &mut passer, &settings, "Game 1");
+        let counted = play_game(&mut passer, &mut other_passer, &settings, "Game 2");
+
+        // Then
+        assert_eq!(
+            GameResult::Forfeit {
+                winner: Player::White
+            },
+            forfeit.result
+        );
+        assert!(
+            forfeit
+                .sgf
+                .contains(";W[]C[Black played the illegal move A9]\n)")
+        );
+        assert_eq!(GameResult::from_margin(-7.5), counted.result);
+        assert!(counted.sgf.contains("RU[Chinese]"));
+        assert!(counted.sgf.contains("RE[W+7.5]"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn given_slow_engine_when_time_limit_is_passed_then_it_should_lose_on_time() {
+        // Given
+        let script = r#"while read -r id command rest; do
+  case "$command" in
+    name) printf '=%s slow\n\n' "$id" ;;
+    genmove) sleep 1; printf '=%s pass\n\n' "$id" ;;
+    *) printf '=%s\n\n' "$id" ;;
+  esac
+done"#;
+        let controller =
+            ProcessController::spawn("sh", &["-c", script]).expect("Expected sh to start");
+        let mut slow = EnginePlayer::new(controller).expect("Expected a name");
+        let mut passer = scripted("passer", &[GtpMove::Pass]);
+        let settings = MatchSettings {
+            board_size: 9,
+            time_limit: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+
+        // When
+        let record = play_game(&mut passer, &mut slow, &settings, "Game 1");
+
+        // Then
+        assert_eq!("slow", record.white);
+        assert_eq!(
+            GameResult::Timeout {
+                winner: Player::Black
+            },
+            record.result
+        );
+        assert!(record.sgf.contains("RE[B+T]"));
+    }
+}
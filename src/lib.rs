@@ -1,4 +1,6 @@
 #![allow(dead_code)] // library code does not need to be explicitly used to be useful
+pub mod analysis;
+pub mod figure;
 pub mod go;
+pub mod gtp;
 pub mod parser;
-
@@ -0,0 +1,303 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::parser::{
+    binary::{BinaryError, decode, encode},
+    gsf::{ParsedGame, SgfError, collection::SgfCollectionReader},
+};
+
+/// First bytes of every container file.
+pub const CONTAINER_MAGIC: &[u8; 4] = b"GOBC";
+/// Version of the container layout, written after the magic bytes.
+pub const CONTAINER_VERSION: u8 = 1;
+
+const HEADER_LEN: u64 = 5;
+const INDEX_ENTRY_LEN: u64 = 12;
+const FOOTER_LEN: u64 = 16;
+
+/// Writes many encoded games into one file.
+///
+/// The file starts with [`CONTAINER_MAGIC`] and [`CONTAINER_VERSION`], followed by the games.
+/// After the games comes the index with a `u64` offset and `u32` length for every game, and a
+/// footer with the `u64` offset of the index and the `u64` amount of games. All numbers are
+/// little endian.
+pub struct ContainerWriter<W: Write> {
+    writer: W,
+    position: u64,
+    index: Vec<(u64, u32)>,
+}
+
+impl<W: Write> ContainerWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self, BinaryError> {
+        writer.write_all(CONTAINER_MAGIC)?;
+        writer.write_all(&[CONTAINER_VERSION])?;
+        Ok(Self {
+            writer,
+            position: HEADER_LEN,
+            index: vec![],
+        })
+    }
+
+    /// Appends a game and returns its index in the container.
+    pub fn add(&mut self, game: &ParsedGame) -> Result<usize, BinaryError> {
+        let encoded = encode(game)?;
+        let len = u32::try_from(encoded.len()).map_err(|_| BinaryError::InvalidValue("game"))?;
+        self.writer.write_all(&encoded)?;
+        self.index.push((self.position, len));
+        self.position += len as u64;
+        Ok(self.index.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Writes the index and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, BinaryError> {
+        for (offset, len) in &self.index {
+            self.writer.write_all(&offset.to_le_bytes())?;
+            self.writer.write_all(&len.to_le_bytes())?;
+        }
+        self.writer.write_all(&self.position.to_le_bytes())?;
+        self.writer
+            .write_all(&(self.index.len() as u64).to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads single games from a container without reading the rest of the file.
+pub struct ContainerReader<R: Read + Seek> {
+    reader: R,
+    index: Vec<(u64, u32)>,
+}
+
+impl<R: Read + Seek> ContainerReader<R> {
+    /// Checks the header and loads the index.
+    pub fn open(mut reader: R) -> Result<Self, BinaryError> {
+        let mut header = [0; HEADER_LEN as usize];
+        reader.seek(SeekFrom::Start(0))?;
+        read_exact(&mut reader, &mut header)?;
+        if &header[..4] != CONTAINER_MAGIC {
+            return Err(BinaryError::InvalidMagic);
+        }
+        if header[4] != CONTAINER_VERSION {
+            return Err(BinaryError::UnsupportedVersion(header[4]));
+        }
+
+        let end = reader.seek(SeekFrom::End(0))?;
+        if end < HEADER_LEN + FOOTER_LEN {
+            return Err(BinaryError::UnexpectedEnd);
+        }
+        reader.seek(SeekFrom::Start(end - FOOTER_LEN))?;
+        let mut footer = [0; FOOTER_LEN as usize];
+        read_exact(&mut reader, &mut footer)?;
+        let index_offset = u64::from_le_bytes(footer[..8].try_into().expect("8 bytes"));
+        let count = u64::from_le_bytes(footer[8..].try_into().expect("8 bytes"));
+
+        let index_len = count
+            .checked_mul(INDEX_ENTRY_LEN)
+            .filter(|len| index_offset.checked_add(*len) == Some(end - FOOTER_LEN))
+            .ok_or(BinaryError::InvalidValue("index"))?;
+        reader.seek(SeekFrom::Start(index_offset))?;
+        let mut entries = vec![0; index_len as usize];
+        read_exact(&mut reader, &mut entries)?;
+        let index = entries
+            .chunks_exact(INDEX_ENTRY_LEN as usize)
+            .map(|entry| {
+                let offset = u64::from_le_bytes(entry[..8].try_into().expect("8 bytes"));
+                let len = u32::from_le_bytes(entry[8..].try_into().expect("4 bytes"));
+                // Games are stored between the header and the index.
+                if offset < HEADER_LEN
+                    || offset > index_offset
+                    || u64::from(len) > index_offset - offset
+                {
+                    return Err(BinaryError::InvalidValue("index"));
+                }
+                Ok((offset, len))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { reader, index })
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Reads and decodes the game at `index`, None if the container has fewer games.
+    pub fn get(&mut self, index: usize) -> Option<Result<ParsedGame, BinaryError>> {
+        let (offset, len) = *self.index.get(index)?;
+        Some(self.read_game(offset, len))
+    }
+
+    /// Decodes every game in order.
+    pub fn iter(&mut self) -> impl Iterator<Item = Result<ParsedGame, BinaryError>> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    fn read_game(&mut self, offset: u64, len: u32) -> Result<ParsedGame, BinaryError> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut bytes = vec![0; len as usize];
+        read_exact(&mut self.reader, &mut bytes)?;
+        decode(&bytes)
+    }
+}
+
+/// Amount of games a conversion wrote and skipped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub converted: usize,
+    /// Games that failed to parse or could not be encoded.
+    pub skipped: usize,
+}
+
+/// Adds every game of an SGF collection to a container, skipping games that are invalid. Stops at
+/// the first error reading the SGF data.
+pub fn convert_sgf<R: BufRead, W: Write>(
+    sgf: R,
+    writer: &mut ContainerWriter<W>,
+) -> Result<ConversionReport, BinaryError> {
+    let mut report = ConversionReport::default();
+    for game in SgfCollectionReader::new(sgf) {
+        let game = match game {
+            Ok(game) => game,
+            Err(SgfError::Io(error)) => return Err(BinaryError::Io(error)),
+            Err(_) => {
+                report.skipped += 1;
+                continue;
+            }
+        };
+        match writer.add(&game) {
+            Ok(_) => report.converted += 1,
+            Err(BinaryError::Io(error)) => return Err(BinaryError::Io(error)),
+            Err(_) => report.skipped += 1,
+        }
+    }
+    Ok(report)
+}
+
+/// Converts SGF files into one container, see [`convert_sgf`].
+pub fn convert_sgf_files<P: AsRef<Path>, W: Write>(
+    paths: impl IntoIterator<Item = P>,
+    writer: &mut ContainerWriter<W>,
+) -> Result<ConversionReport, BinaryError> {
+    let mut report = ConversionReport::default();
+    for path in paths {
+        let file_report = convert_sgf(BufReader::new(File::open(path)?), writer)?;
+        report.converted += file_report.converted;
+        report.skipped += file_report.skipped;
+    }
+    Ok(report)
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), BinaryError> {
+    reader.read_exact(buf).map_err(|error| match error.kind() {
+        std::io::ErrorKind::UnexpectedEof => BinaryError::UnexpectedEnd,
+        _ => BinaryError::Io(error),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn given_sgf_collection_when_converted_then_games_should_be_readable_in_any_order() {
+        // Given
+        let sgf = "(;GM[1]SZ[19]PB[First];B[dd];W[pp])
+(;GM[1]SZ[9]PB[Second];B[ee])
+(;GM[3]SZ[8];B[aa])
+(;GM[1]SZ[13]PB[Third]GN[Rect];B[aa];W[bb];B[])";
+        let mut writer =
+            ContainerWriter::new(Cursor::new(vec![])).expect("Expected header to be written");
+
+        // When
+        let report = convert_sgf(sgf.as_bytes(), &mut writer).expect("Expected conversion");
+        let bytes = writer.finish().expect("Expected index to be written");
+        let mut reader = ContainerReader::open(bytes).expect("Expected container to open");
+
+        // Then
+        assert_eq!(3, report.converted);
+        assert_eq!(1, report.skipped);
+        assert_eq!(3, reader.len());
+        let third = reader
+            .get(2)
+            .expect("Expected a third game")
+            .expect("Expected game to decode");
+        assert_eq!(Some("Third".to_string()), third.black_player);
+        assert_eq!(3, third.moves.len());
+        let first = reader
+            .get(0)
+            .expect("Expected a first game")
+            .expect("Expected game to decode");
+        assert_eq!(Some("First".to_string()), first.black_player);
+        assert!(reader.get(3).is_none());
+        assert_eq!(3, reader.iter().filter(|game| game.is_ok()).count());
+    }
+
+    #[test]
+    fn given_other_data_when_opened_then_it_should_not_be_a_container() {
+        assert!(matches!(
+            ContainerReader::open(Cursor::new(b"(;GM[1]SZ[19])".to_vec())),
+            Err(BinaryError::InvalidMagic)
+        ));
+    }
+
+    #[test]
+    fn given_index_pointing_outside_of_the_games_when_opened_then_it_should_be_rejected() {
+        // Given
+        let mut writer =
+            ContainerWriter::new(Cursor::new(vec![])).expect("Expected header to be written");
+        convert_sgf("(;GM[1]SZ[9];B[ee])".as_bytes(), &mut writer).expect("Expected conversion");
+        let bytes = writer
+            .finish()
+            .expect("Expected index to be written")
+            .into_inner();
+        let entry = bytes.len() - (FOOTER_LEN + INDEX_ENTRY_LEN) as usize;
+        let mut too_long = bytes.clone();
+        too_long[entry + 8..entry + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut in_header = bytes;
+        in_header[entry..entry + 8].copy_from_slice(&1u64.to_le_bytes());
+
+        // When
+        let too_long = ContainerReader::open(Cursor::new(too_long));
+        let in_header = ContainerReader::open(Cursor::new(in_header));
+
+        // Then
+        assert!(matches!(too_long, Err(BinaryError::InvalidValue("index"))));
+        assert!(matches!(in_header, Err(BinaryError::InvalidValue("index"))));
+    }
+
+    #[test]
+    fn given_sgf_that_can_not_be_read_when_converted_then_it_should_return_the_io_error() {
+        // Given
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk on fire"))
+            }
+        }
+        let mut writer =
+            ContainerWriter::new(Cursor::new(vec![])).expect("Expected header to be written");
+
+        // When
+        let res = convert_sgf(BufReader::new(FailingReader), &mut writer);
+
+        // Then
+        assert!(matches!(res, Err(BinaryError::Io(_))));
+    }
+}
@@ -0,0 +1,713 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::{
+    go::{
+        any_game::AnyGame,
+        board::FlexibleBoard,
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        game::Game,
+        player::Player,
+        playermove::{Move, PlaceStoneMove, SetupMove},
+        time_control::TimeControl,
+    },
+    parser::gsf::{ParsedGame, ReplayError, properties::NodeProperties},
+};
+
+pub mod container;
+
+/// Version of the game encoding, the first byte of every encoded game.
+pub const FORMAT_VERSION: u8 = 1;
+
+const BLACK_PLAYER: u16 = 1 << 0;
+const WHITE_PLAYER: u16 = 1 << 1;
+const BLACK_RANK: u16 = 1 << 2;
+const WHITE_RANK: u16 = 1 << 3;
+const KOMI: u16 = 1 << 4;
+const HANDICAP: u16 = 1 << 5;
+const TIME_CONTROL: u16 = 1 << 6;
+const RESULT: u16 = 1 << 7;
+const RULES: u16 = 1 << 8;
+const DATE: u16 = 1 << 9;
+const EVENT: u16 = 1 << 10;
+const GAME_NAME: u16 = 1 << 11;
+const PLACE: u16 = 1 << 12;
+const DEAD_STONES: u16 = 1 << 13;
+
+/// Largest width and height, the largest board SGF coordinates can describe.
+const MAX_SIZE: u16 = 52;
+
+/// Encodes the metadata and moves of a game, comments and other node properties are left out.
+///
+/// Version 1 of the layout, varints are unsigned LEB128:
+///
+/// - `u8` format version.
+/// - varint width and height, from 1 up to 52 like SGF coordinates.
+/// - varint field flags, followed by every field that is present in flag order: black player,
+///   white player, black rank, white rank, komi, handicap, time control, result, rules, date,
+///   event, game name, place and dead stones. Text is a varint byte length and UTF-8, ranks are
+///   written as text, komi is an `f64` in little endian, handicap a `u8`, dead stones a varint
+///   count and varint point indices.
+/// - varint amount of moves, followed by the moves as a bit stream of `bits` wide codes, least
+///   significant bit first, where `bits` fits `width * height + 3` codes. That is 9 bits a move
+///   up to 19x19.
+///
+/// Points are numbered `y * width + x`. Code 0 is a pass and `index + 1` a stone on a point. The
+/// player alternates, code `width * height + 1` in front of a move means the same player moves
+/// again. Code `width * height + 2` is a setup move, followed by the black, white and cleared
+/// points each ended by code 0, and the player to move as 0 for none, 1 for black or 2 for white.
+pub fn encode(game: &ParsedGame) -> Result<Vec<u8>, BinaryError> {
+    let mut out = vec![FORMAT_VERSION];
+    write_varint(&mut out, game.width as u64);
+    write_varint(&mut out, game.height as u64);
+
+    let flags = [
+        (BLACK_PLAYER, game.black_player.is_some()),
+        (WHITE_PLAYER, game.white_player.is_some()),
+        (BLACK_RANK, game.black_rank.is_some()),
+        (WHITE_RANK, game.white_rank.is_some()),
+        (KOMI, game.komi.is_some()),
+        (HANDICAP, game.handicap.is_some()),
+        (TIME_CONTROL, game.time_control.is_some()),
+        (RESULT, game.result.is_some()),
+        (RULES, game.rules.is_some()),
+        (DATE, game.date.is_some()),
+        (EVENT, game.event.is_some()),
+        (GAME_NAME, game.game_name.is_some()),
+        (PLACE, game.place.is_some()),
+        (DEAD_STONES, !game.dead_stones.is_empty()),
+    ]
+    .iter()
+    .filter(|(_, present)| *present)
+    .fold(0, |flags, (flag, _)| flags | flag);
+    write_varint(&mut out, flags as u64);
+
+    let points = Points::new(game.width, game.height)?;
+
+    for text in [&game.black_player, &game.white_player]
+        .into_iter()
+        .flatten()
+    {
+        write_string(&mut out, text);
+    }
+    for rank in [game.black_rank, game.white_rank].into_iter().flatten() {
+        write_string(&mut out, &rank.to_string());
+    }
+    if let Some(komi) = game.komi {
+        out.extend(komi.to_le_bytes());
+    }
+    if let Some(handicap) = game.handicap {
+        out.push(handicap);
+    }
+    if let Some(time_control) = &game.time_control {
+        write_time_control(&mut out, time_control);
+    }
+    for text in [
+        &game.result,
+        &game.rules,
+        &game.date,
+        &game.event,
+        &game.game_name,
+        &game.place,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        write_string(&mut out, text);
+    }
+    if !game.dead_stones.is_empty() {
+        write_varint(&mut out, game.dead_stones.len() as u64);
+        for coord in &game.dead_stones {
+            write_varint(&mut out, points.index(coord)? as u64);
+        }
+    }
+
+    write_varint(&mut out, game.moves.len() as u64);
+    let mut bits = BitWriter::new(points.bits);
+    let mut expected = Player::Black;
+    for m in &game.moves {
+        match m {
+            Move::PlaceStone(PlaceStoneMove { player, .. }) | Move::Skip { player } => {
+                if *player != expected {
+                    bits.write(points.same_player_code());
+                }
+                expected = !*player;
+            }
+            Move::Setup(_) => {}
+        }
+
+        match m {
+            Move::PlaceStone(PlaceStoneMove { coord, .. }) => bits.write(points.index(coord)? + 1),
+            Move::Skip { .. } => bits.write(0),
+            Move::Setup(setup_move) => {
+                bits.write(points.setup_code());
+                for coords in [
+                    &setup_move.add_black,
+                    &setup_move.add_white,
+                    &setup_move.clear,
+                ] {
+                    let mut indices = coords
+                        .iter()
+                        .map(|coord| points.index(coord))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    indices.sort();
+                    for index in indices {
+                        bits.write(index + 1);
+                    }
+                    bits.write(0);
+                }
+                bits.write(match setup_move.player_to_move {
+                    None => 0,
+                    Some(Player::Black) => 1,
+                    Some(Player::White) => 2,
+                });
+                if let Some(player) = setup_move.player_to_move {
+                    expected = player;
+                }
+            }
+        }
+    }
+    out.extend(bits.finish());
+
+    Ok(out)
+}
+
+/// Decodes a game written by [`encode`].
+pub fn decode(bytes: &[u8]) -> Result<ParsedGame, BinaryError> {
+    let mut reader = ByteReader { bytes, position: 0 };
+
+    let version = reader.read_u8()?;
+    if version != FORMAT_VERSION {
+        return Err(BinaryError::UnsupportedVersion(version));
+    }
+
+    let width = reader.read_u16()?;
+    let height = reader.read_u16()?;
+    let points = Points::new(width, height)?;
+    let flags = reader.read_varint()?;
+    let has = |flag: u16| flags & flag as u64 != 0;
+
+    let mut game = ParsedGame {
+        width,
+        height,
+        ..Default::default()
+    };
+
+    let mut text = |flag: u16| -> Result<Option<String>, BinaryError> {
+        if has(flag) {
+            Ok(Some(reader.read_string()?))
+        } else {
+            Ok(None)
+        }
+    };
+    game.black_player = text(BLACK_PLAYER)?;
+    game.white_player = text(WHITE_PLAYER)?;
+    let rank = |rank: Option<String>| {
+        rank.map(|rank| rank.parse().map_err(|_| BinaryError::InvalidValue("rank")))
+            .transpose()
+    };
+    game.black_rank = rank(text(BLACK_RANK)?)?;
+    game.white_rank = rank(text(WHITE_RANK)?)?;
+
+    if has(KOMI) {
+        let komi = reader.read_bytes(8)?;
+        game.komi = Some(f64::from_le_bytes(komi.try_into().expect("Read 8 bytes")));
+    }
+    if has(HANDICAP) {
+        game.handicap = Some(reader.read_u8()?);
+    }
+    if has(TIME_CONTROL) {
+        game.time_control = Some(read_time_control(&mut reader)?);
+    }
+
+    let mut text = |flag: u16| -> Result<Option<String>, BinaryError> {
+        if has(flag) {
+            Ok(Some(reader.read_string()?))
+        } else {
+            Ok(None)
+        }
+    };
+    game.result = text(RESULT)?;
+    game.rules = text(RULES)?;
+    game.date = text(DATE)?;
+    game.event = text(EVENT)?;
+    game.game_name = text(GAME_NAME)?;
+    game.place = text(PLACE)?;
+
+    if has(DEAD_STONES) {
+        let count = reader.read_varint()?;
+        for _ in 0..count {
+            let index = u32::try_from(reader.read_varint()?)
+                .map_err(|_| BinaryError::InvalidValue("dead stone"))?;
+            game.dead_stones.push(points.coordinate(index)?);
+        }
+    }
+
+    let move_count = reader.read_varint()?;
+    let mut bits = BitReader::new(reader.remaining(), points.bits);
+    let mut expected = Player::Black;
+    for _ in 0..move_count {
+        let mut code = bits.read()?;
+        if code == points.same_player_code() {
+            expected = !expected;
+            code = bits.read()?;
+        }
+
+        let m = if code == 0 {
+            Move::Skip { player: expected }
+        } else if code <= points.count {
+            Move::PlaceStone(PlaceStoneMove {
+                player: expected,
+                coord: points.coordinate(code - 1)?,
+            })
+        } else if code == points.setup_code() {
+            let mut read_list = || -> Result<CoordinateSet, BinaryError> {
+                let mut coords = vec![];
+                loop {
+                    match bits.read()? {
+                        0 => return Ok(CoordinateSet::new(coords)),
+                        code => coords.push(points.coordinate(code - 1)?),
+                    }
+                }
+            };
+            let add_black = read_list()?;
+            let add_white = read_list()?;
+            let clear = read_list()?;
+            let player_to_move = match bits.read()? {
+                0 => None,
+                1 => Some(Player::Black),
+                2 => Some(Player::White),
+                _ => return Err(BinaryError::InvalidValue("player to move")),
+            };
+            Move::Setup(SetupMove {
+                add_black,
+                add_white,
+                clear,
+                player_to_move,
+            })
+        } else {
+            return Err(BinaryError::InvalidValue("move"));
+        };
+
+        match &m {
+            Move::PlaceStone(_) | Move::Skip { .. } => expected = !expected,
+            Move::Setup(setup_move) => {
+                if let Some(player) = setup_move.player_to_move {
+                    expected = player;
+                }
+            }
+        }
+        game.moves.push(m);
+    }
+
+    game.properties = vec![NodeProperties::default(); game.moves.len()];
+    Ok(game)
+}
+
+/// Encodes the size, result and moves of a game that is being played.
+pub fn encode_game<TBoard: FlexibleBoard>(game: &Game<TBoard>) -> Result<Vec<u8>, BinaryError> {
+    let (width, height) = game.get_board().get_size();
+    encode(&ParsedGame {
+        width,
+        height,
+        result: game.get_result().map(|result| result.to_string()),
+        moves: game.get_history().to_vec(),
+        ..Default::default()
+    })
+}
+
+/// Decodes a game and replays its moves on the board backend that fits its size.
+pub fn decode_game(bytes: &[u8]) -> Result<AnyGame, BinaryError> {
+    Ok(decode(bytes)?.replay()?)
+}
+
+#[derive(Debug, Error)]
+pub enum BinaryError {
+    #[error("Could not read or write the binary data")]
+    Io(#[from] std::io::Error),
+    #[error("The data is not in the binary game format")]
+    InvalidMagic,
+    #[error("Version {0} of the binary game format is not supported")]
+    UnsupportedVersion(u8),
+    #[error("The binary data ends unexpectedly")]
+    UnexpectedEnd,
+    #[error("Invalid {0} in the binary data")]
+    InvalidValue(&'static str),
+    #[error("Boards of {width}x{height} can not be encoded")]
+    InvalidSize { width: u16, height: u16 },
+    #[error("Coordinate {x},{y} is outside of the board")]
+    OutOfBounds { x: u16, y: u16 },
+    #[error(transparent)]
+    Replay(#[from] ReplayError),
+}
+
+/// Numbering of the points of a board and the codes of the move stream.
+struct Points {
+    width: u16,
+    height: u16,
+    count: u32,
+    bits: u8,
+}
+
+impl Points {
+    fn new(width: u16, height: u16) -> Result<Self, BinaryError> {
+        if !(1..=MAX_SIZE).contains(&width) || !(1..=MAX_SIZE).contains(&height) {
+            return Err(BinaryError::InvalidSize { width, height });
+        }
+        let count = width as u32 * height as u32;
+        let codes = count + 3;
+        let bits = (u32::BITS - (codes - 1).leading_zeros()) as u8;
+        Ok(Self {
+            width,
+            height,
+            count,
+            bits,
+        })
+    }
+
+    fn same_player_code(&self) -> u32 {
+        self.count + 1
+    }
+
+    fn setup_code(&self) -> u32 {
+        self.count + 2
+    }
+
+    fn index(&self, coord: &FlexibleCoordinate) -> Result<u32, BinaryError> {
+        if coord.x >= self.width || coord.y >= self.height {
+            return Err(BinaryError::OutOfBounds {
+                x: coord.x,
+                y: coord.y,
+            });
+        }
+        Ok(coord.y as u32 * self.width as u32 + coord.x as u32)
+    }
+
+    fn coordinate(&self, index: u32) -> Result<FlexibleCoordinate, BinaryError> {
+        if index >= self.count {
+            return Err(BinaryError::InvalidValue("point"));
+        }
+        Ok(FlexibleCoordinate {
+            x: (index % self.width as u32) as u16,
+            y: (index / self.width as u32) as u16,
+        })
+    }
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn new(bits: u8) -> Self {
+        Self {
+            bytes: vec![],
+            used: 0,
+            bits,
+        }
+    }
+
+    fn write(&mut self, code: u32) {
+        for bit in 0..self.bits {
+            if self.used.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if code & (1 << bit) != 0 {
+                *self.bytes.last_mut().expect("Pushed above") |= 1 << (self.used % 8);
+            }
+            self.used += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    bits: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8], bits: u8) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            bits,
+        }
+    }
+
+    fn read(&mut self) -> Result<u32, BinaryError> {
+        let mut code = 0;
+        for bit in 0..self.bits {
+            let byte = self
+                .bytes
+                .get(self.position / 8)
+                .ok_or(BinaryError::UnexpectedEnd)?;
+            if byte & (1 << (self.position % 8)) != 0 {
+                code |= 1 << bit;
+            }
+            self.position += 1;
+        }
+        Ok(code)
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], BinaryError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(BinaryError::UnexpectedEnd)?;
+        let res = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(res)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, BinaryError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, BinaryError> {
+        u16::try_from(self.read_varint()?).map_err(|_| BinaryError::InvalidValue("size"))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, BinaryError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BinaryError::InvalidValue("varint"))
+    }
+
+    fn read_string(&mut self) -> Result<String, BinaryError> {
+        let len =
+            usize::try_from(self.read_varint()?).map_err(|_| BinaryError::InvalidValue("text"))?;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| BinaryError::InvalidValue("text"))
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) {
+    write_varint(out, text.len() as u64);
+    out.extend(text.as_bytes());
+}
+
+/// A `u8` kind followed by the durations in milliseconds and the counts as varints.
+fn write_time_control(out: &mut Vec<u8>, time_control: &TimeControl) {
+    let millis = |out: &mut Vec<u8>, duration: &Duration| {
+        write_varint(out, duration.as_millis() as u64);
+    };
+    match time_control {
+        TimeControl::Absolute { main_time } => {
+            out.push(1);
+            millis(out, main_time);
+        }
+        TimeControl::ByoYomi {
+            main_time,
+            periods,
+            period_time,
+        } => {
+            out.push(2);
+            millis(out, main_time);
+            write_varint(out, *periods as u64);
+            millis(out, period_time);
+        }
+        TimeControl::Canadian {
+            main_time,
+            stones,
+            period_time,
+        } => {
+            out.push(3);
+            millis(out, main_time);
+            write_varint(out, *stones as u64);
+            millis(out, period_time);
+        }
+        TimeControl::Fischer {
+            main_time,
+            increment,
+            max_time,
+        } => {
+            out.push(4);
+            millis(out, main_time);
+            millis(out, increment);
+            match max_time {
+                Some(max_time) => {
+                    out.push(1);
+                    millis(out, max_time);
+                }
+                None => out.push(0),
+            }
+        }
+        TimeControl::Simple { per_move } => {
+            out.push(5);
+            millis(out, per_move);
+        }
+    }
+}
+
+fn read_time_control(reader: &mut ByteReader) -> Result<TimeControl, BinaryError> {
+    let millis = |reader: &mut ByteReader| -> Result<Duration, BinaryError> {
+        Ok(Duration::from_millis(reader.read_varint()?))
+    };
+    let count = |reader: &mut ByteReader| -> Result<u32, BinaryError> {
+        u32::try_from(reader.read_varint()?).map_err(|_| BinaryError::InvalidValue("time control"))
+    };
+
+    Ok(match reader.read_u8()? {
+        1 => TimeControl::Absolute {
+            main_time: millis(reader)?,
+        },
+        2 => TimeControl::ByoYomi {
+            main_time: millis(reader)?,
+            periods: count(reader)?,
+            period_time: millis(reader)?,
+        },
+        3 => TimeControl::Canadian {
+            main_time: millis(reader)?,
+            stones: count(reader)?,
+            period_time: millis(reader)?,
+        },
+        4 => TimeControl::Fischer {
+            main_time: millis(reader)?,
+            increment: millis(reader)?,
+            max_time: match reader.read_u8()? {
+                0 => None,
+                _ => Some(millis(reader)?),
+            },
+        },
+        5 => TimeControl::Simple {
+            per_move: millis(reader)?,
+        },
+        _ => return Err(BinaryError::InvalidValue("time control")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{go::rank::Rank, parser::gsf::parse_sgf};
+
+    #[test]
+    fn given_parsed_game_when_encoded_then_decoding_should_give_the_same_game() {
+        // Given
+        let game = parse_sgf(
+            "(;GM[1]SZ[19]PB[Black]PW[White]BR[3k]WR[2d]KM[0.5]HA[2]RE[W+R]DT[2024-01-01]
+TM[600]OT[3x30 byo-yomi]AB[dp][pd]
+;W[dd];B[pp];W[];W[qq];B[tt]
+;AE[dd]AW[aa]PL[B];B[cc])",
+        )
+        .expect("Expected sgf to parse");
+
+        // When
+        let encoded = encode(&game).expect("Expected game to encode");
+        let decoded = decode(&encoded).expect("Expected game to decode");
+
+        // Then
+        assert_eq!(FORMAT_VERSION, encoded[0]);
+        assert_eq!((19, 19), (decoded.width, decoded.height));
+        assert_eq!(game.black_player, decoded.black_player);
+        assert_eq!(Some(Rank::dan(2)), decoded.white_rank);
+        assert_eq!(game.komi, decoded.komi);
+        assert_eq!(game.handicap, decoded.handicap);
+        assert_eq!(game.time_control, decoded.time_control);
+        assert_eq!(game.result, decoded.result);
+        assert_eq!(game.date, decoded.date);
+        assert_eq!(game.moves, decoded.moves);
+        assert_eq!(decoded.moves.len(), decoded.properties.len());
+    }
+
+    #[test]
+    fn given_long_19x19_game_when_encoded_then_moves_should_take_about_a_byte_each() {
+        // Given
+        let mut game = Game::new(crate::go::bitmask_board::BitMaskBoard::new(
+            crate::go::bitmask19::BitMask19::init,
+        ));
+        let mut player = Player::Black;
+        for y in 0..10 {
+            for x in 0..19 {
+                if (x + y) % 2 == 0 {
+                    continue;
+                }
+                game.make_move(&Move::PlaceStone(PlaceStoneMove {
+                    player,
+                    coord: FlexibleCoordinate { x, y: y * 2 },
+                }))
+                .expect("Expected move to be legal");
+                player = !player;
+            }
+        }
+        let moves = game.get_history().len();
+
+        // When
+        let encoded = encode_game(&game).expect("Expected game to encode");
+        let decoded = decode_game(&encoded).expect("Expected game to decode");
+
+        // Then
+        assert!(encoded.len() <= 10 + moves * 9 / 8 + 1);
+        assert_eq!(moves, 95);
+        let AnyGame::Nineteen(decoded) = decoded else {
+            panic!("Expected a 19x19 game");
+        };
+        assert_eq!(game.get_board(), decoded.get_board());
+    }
+
+    #[test]
+    fn given_invalid_data_when_decoded_then_it_should_report_why() {
+        assert!(matches!(
+            decode(&[2, 19, 19]),
+            Err(BinaryError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            decode(&[FORMAT_VERSION, 19]),
+            Err(BinaryError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            decode(&[FORMAT_VERSION, 9, 9, 0, 1, 0b0111_1111]),
+            Err(BinaryError::InvalidValue("move"))
+        ));
+        assert!(matches!(
+            decode(&[FORMAT_VERSION, 0xFF, 0xFF, 0x03, 0xFF, 0xFF, 0x03, 0, 0]),
+            Err(BinaryError::InvalidSize {
+                width: 65535,
+                height: 65535
+            })
+        ));
+        assert!(matches!(
+            encode(&ParsedGame {
+                width: 53,
+                height: 53,
+                ..Default::default()
+            }),
+            Err(BinaryError::InvalidSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::{
+    go::{
+        coordinate::FlexibleCoordinate,
+        player::Player,
+        playermove::{Move, PlaceStoneMove},
+    },
+    parser::{
+        ImportError,
+        gsf::{ParsedGame, fixed_handicap_setup, properties::NodeProperties},
+    },
+};
+
+/// Tygem only plays on 19x19.
+const BOARD_SIZE: u16 = 19;
+
+/// Parses a Tygem `.gib` game record.
+///
+/// The header holds `\[KEY=VALUE\]` lines, the moves are `STO` lines between `\GS` and `\GE`.
+pub fn parse_gib(gib: &str) -> Result<ParsedGame, ImportError> {
+    let mut game = ParsedGame {
+        width: BOARD_SIZE,
+        height: BOARD_SIZE,
+        ..Default::default()
+    };
+    let mut moves = vec![];
+    let mut in_moves = false;
+
+    for (index, line) in gib.lines().enumerate() {
+        let line = line.trim();
+
+        if line == "\\GS" {
+            in_moves = true;
+            continue;
+        }
+        if line == "\\GE" {
+            in_moves = false;
+            continue;
+        }
+
+        if in_moves {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.first() {
+                Some(&"INI") => {
+                    let handicap = fields.get(3).and_then(|x| x.parse::<u8>().ok());
+                    if let Some(handicap) = handicap.filter(|x| *x >= 2) {
+                        game.handicap = Some(handicap);
+                        moves.extend(fixed_handicap_setup((BOARD_SIZE, BOARD_SIZE), handicap));
+                    }
+                }
+                Some(&"STO") => moves.push(parse_stone(&fields, index, line)?),
+                Some(&"SKI") => {
+                    let player = match moves.last() {
+                        Some(Move::PlaceStone(PlaceStoneMove { player, .. }))
+                        | Some(Move::Skip { player }) => !*player,
+                        Some(Move::Setup(_)) => Player::White,
+                        None => Player::Black,
+                    };
+                    moves.push(Move::Skip { player });
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line
+            .strip_prefix("\\[")
+            .and_then(|x| x.strip_suffix("\\]"))
+            .and_then(|x| x.split_once('='))
+        else {
+            continue;
+        };
+
+        match key {
+            "GAMEBLACKNAME" => {
+                let (name, rank) = split_name_and_rank(value);
+                game.black_player = Some(name);
+                game.black_rank = rank.and_then(|x| x.parse().ok());
+            }
+            "GAMEWHITENAME" => {
+                let (name, rank) = split_name_and_rank(value);
+                game.white_player = Some(name);
+                game.white_rank = rank.and_then(|x| x.parse().ok());
+            }
+            "GAMENAME" if !value.is_empty() => game.game_name = Some(value.to_string()),
+            "GAMEPLACE" if !value.is_empty() => game.place = Some(value.to_string()),
+            "GAMEDATE" => game.date = parse_date(value),
+            "GAMEINFOMAIN" => {
+                let info = |name: &str| {
+                    value
+                        .split(',')
+                        .find_map(|x| x.strip_prefix(name)?.strip_prefix(':'))
+                };
+                game.komi = info("GONGJE").and_then(parse_tenths);
+                game.result = parse_result(info("GRLT"), info("ZIPSU"));
+            }
+            // The tag repeats the game info with single letter keys, older files only have this.
+            "GAMETAG" => {
+                let tag = |name: char| value.split(',').find_map(|x| x.strip_prefix(name));
+                if game.komi.is_none() {
+                    game.komi = tag('G').and_then(parse_tenths);
+                }
+                if game.result.is_none() {
+                    game.result = parse_result(tag('W'), tag('Z'));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    game.properties = vec![NodeProperties::default(); moves.len()];
+    game.moves = moves;
+    Ok(game)
+}
+
+/// `STO 0 <move number> <color> <x> <y>`, color 1 is black and 2 is white.
+fn parse_stone(fields: &[&str], index: usize, line: &str) -> Result<Move, ImportError> {
+    let invalid = || ImportError::InvalidMove {
+        line: index + 1,
+        text: line.to_string(),
+    };
+
+    let player = match fields.get(3) {
+        Some(&"1") => Player::Black,
+        Some(&"2") => Player::White,
+        _ => return Err(invalid()),
+    };
+    let coordinate = |i: usize| {
+        fields
+            .get(i)
+            .and_then(|x| x.parse::<u16>().ok())
+            .filter(|x| *x < BOARD_SIZE)
+    };
+    let (Some(x), Some(y)) = (coordinate(4), coordinate(5)) else {
+        return Err(invalid());
+    };
+
+    Ok(Move::PlaceStone(PlaceStoneMove {
+        player,
+        coord: FlexibleCoordinate { x, y },
+    }))
+}
+
+/// Names are written as `name (rank)`.
+fn split_name_and_rank(value: &str) -> (String, Option<&str>) {
+    match value
+        .trim()
+        .strip_suffix(')')
+        .and_then(|x| x.rsplit_once('('))
+    {
+        Some((name, rank)) => (name.trim().to_string(), Some(rank.trim())),
+        None => (value.trim().to_string(), None),
+    }
+}
+
+/// Komi and score are written in tenths of a point.
+fn parse_tenths(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().map(|x| x / 10.0)
+}
+
+/// Builds the SGF result from the result kind and the score in tenths of a point.
+fn parse_result(kind: Option<&str>, score: Option<&str>) -> Option<String> {
+    let score = score.and_then(parse_tenths).unwrap_or_default();
+    match kind?.trim() {
+        "0" => Some(format!("B+{score}")),
+        "1" => Some(format!("W+{score}")),
+        "3" => Some("B+R".to_string()),
+        "4" => Some("W+R".to_string()),
+        "7" => Some("B+T".to_string()),
+        "8" => Some("W+T".to_string()),
+        _ => None,
+    }
+}
+
+/// Dates look like `2014- 3- 3-18-09-52`, only the day is kept.
+fn parse_date(value: &str) -> Option<String> {
+    let mut parts = value.split('-').map(|x| x.trim().parse::<u32>());
+    match (parts.next()?, parts.next()?, parts.next()?) {
+        (Ok(year), Ok(month), Ok(day)) => Some(format!("{year:04}-{month:02}-{day:02}")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{go::rank::Rank, parser::gsf::writer::write_sgf};
+
+    #[test]
+    fn given_gib_record_when_parsed_then_it_should_map_the_header_and_moves() {
+        // Given
+        let input = "\\HS
+\\[GAMEBLACKNAME=Lee Sedol (9단)\\]
+\\[GAMEWHITENAME=Gu Li (9D)\\]
+\\[GAMEDATE=2014- 3- 3-18-09-52\\]
+\\[GAMEINFOMAIN=GBKIND:3,GTYPE:0,GCDT:0,GRLT:4,ZIPSU:0,GONGJE:5,TCNT:3\\]
+\\[GAMETAG=S1,R1,D0,G5,W4,Z0\\]
+\\HE
+\\GS
+2 1 0
+INI 0 1 2 &4
+STO 0 2 2 15 15
+STO 0 3 1 3 3
+SKI 0 4
+\\GE
+";
+
+        // When
+        let game = parse_gib(input).expect("Expected gib to parse");
+
+        // Then
+        assert_eq!(Some("Lee Sedol".to_string()), game.black_player);
+        assert_eq!(Some(Rank::dan(9)), game.black_rank);
+        assert_eq!(Some("Gu Li".to_string()), game.white_player);
+        assert_eq!(Some("2014-03-03".to_string()), game.date);
+        assert_eq!(Some(0.5), game.komi);
+        assert_eq!(Some("W+R".to_string()), game.result);
+        assert_eq!(Some(2), game.handicap);
+        assert_eq!(4, game.moves.len());
+        assert!(matches!(
+            game.moves[3],
+            Move::Skip {
+                player: Player::White
+            }
+        ));
+        assert!(game.replay().is_ok());
+        assert!(write_sgf(&game).contains(";W[pp]"));
+    }
+
+    #[test]
+    fn given_gib_handicap_game_when_parsed_then_it_should_place_the_stones_and_score() {
+        // Given
+        let input = "\\HS
+\\[GAMEBLACKNAME=Kim (3급)\\]
+\\[GAMEWHITENAME=Park (1단)\\]
+\\[GAMEINFOMAIN=GBKIND:3,GTYPE:0,GCDT:0,GRLT:0,ZIPSU:35,GONGJE:0,TCNT:1\\]
+\\HE
+\\GS
+2 1 0
+INI 0 1 4 &4
+STO 0 2 2 9 9
+\\GE
+";
+
+        // When
+        let game = parse_gib(input).expect("Expected gib to parse");
+        let timeout = parse_gib("\\[GAMETAG=S1,R1,D0,G65,W7,Z0\\]").expect("Expected gib to parse");
+
+        // Then
+        assert_eq!(Some(Rank::kyu(3)), game.black_rank);
+        assert_eq!(Some(Rank::dan(1)), game.white_rank);
+        assert_eq!(Some(4), game.handicap);
+        assert_eq!(Some(0.0), game.komi);
+        assert_eq!(Some("B+3.5".to_string()), game.result);
+        let Move::Setup(setup_move) = &game.moves[0] else {
+            panic!("Expected handicap stones first");
+        };
+        assert_eq!(4, setup_move.add_black.len());
+        assert!(game.replay().is_ok());
+        assert_eq!(Some(6.5), timeout.komi);
+        assert_eq!(Some("B+T".to_string()), timeout.result);
+    }
+
+    #[test]
+    fn given_gib_with_truncated_move_when_parsed_then_it_should_report_the_line() {
+        // Given
+        let input = "\\HS
+\\HE
+\\GS
+STO 0 2 1 15
+\\GE
+";
+
+        // When
+        let res = parse_gib(input);
+
+        // Then
+        assert!(matches!(
+            res,
+            Err(ImportError::InvalidMove { line: 4, ref text }) if text == "STO 0 2 1 15"
+        ));
+        assert!(matches!(
+            parse_gib("\\GS\nSTO 0 2 3 1 1\n\\GE"),
+            Err(ImportError::InvalidMove { line: 2, .. })
+        ));
+    }
+}
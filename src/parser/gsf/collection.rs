@@ -0,0 +1,256 @@
+use std::io::{BufRead, Read};
+
+use encoding_rs::{Encoding, SHIFT_JIS, UTF_8};
+use sgf_parse::SgfParseError;
+
+use crate::parser::gsf::{ParsedGame, SgfError, parse_game_tree};
+
+/// Reads the games of an SGF collection one game tree at a time, so collections with thousands
+/// of games never have to be held in memory at once.
+///
+/// Every top-level game tree is parsed on its own, a broken or non-Go game is returned as an
+/// error and reading continues with the next game.
+///
+/// Game trees are decoded with the charset of their `CA` property, so older files in GB2312,
+/// Shift_JIS or EUC-KR read correctly. Trees without `CA` are read as UTF-8.
+pub struct SgfCollectionReader<R: BufRead> {
+    reader: R,
+    finished: bool,
+    detect_charset: bool,
+}
+
+impl<R: BufRead> SgfCollectionReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            finished: false,
+            detect_charset: true,
+        }
+    }
+
+    /// Reads a collection that is already decoded to UTF-8, the `CA` property is ignored.
+    pub fn utf8(reader: R) -> Self {
+        Self {
+            reader,
+            finished: false,
+            detect_charset: false,
+        }
+    }
+
+    /// Reads the raw bytes of the next top-level game tree and the charset it declares, None
+    /// when the collection is done.
+    fn next_game_tree(&mut self) -> Option<Result<(Vec<u8>, &'static Encoding), SgfError>> {
+        let mut buffer = vec![];
+        let mut depth = 0usize;
+        let mut in_value = false;
+        let mut escaped = false;
+        let mut trail_byte = false;
+
+        // The identifier of the property that is being read, to find the `CA` value.
+        let mut identifier = vec![];
+        let mut after_letter = false;
+        let mut value = vec![];
+        let mut charset = UTF_8;
+
+        for byte in (&mut self.reader).bytes() {
+            let byte = match byte {
+                Ok(byte) => byte,
+                Err(e) => return Some(Err(SgfError::Io(e))),
+            };
+
+            if depth == 0 {
+                // Anything outside of a game tree is ignored, like the SGF spec allows.
+                if byte == b'(' {
+                    depth = 1;
+                    buffer.push(byte);
+                }
+                continue;
+            }
+
+            buffer.push(byte);
+
+            if in_value {
+                if trail_byte {
+                    // The second byte of a double byte character can look like `\` or `]`.
+                    trail_byte = false;
+                } else if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b']' {
+                    in_value = false;
+                    if self.detect_charset && identifier == b"CA" {
+                        let label = String::from_utf8_lossy(&value);
+                        charset = Encoding::for_label(label.trim().as_bytes()).unwrap_or(UTF_8);
+                    }
+                    continue;
+                } else if is_lead_byte(charset, byte) {
+                    trail_byte = true;
+                }
+                value.push(byte);
+                continue;
+            }
+
+            match byte {
+                b'A'..=b'Z' => {
+                    if !after_letter {
+                        identifier.clear();
+                    }
+                    identifier.push(byte);
+                }
+                // Older files spell identifiers out like `AddBlack`, only the capitals count.
+                b'a'..=b'z' => {}
+                b'[' => {
+                    in_value = true;
+                    value.clear();
+                }
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(Ok((buffer, charset)));
+                    }
+                }
+                _ => {}
+            }
+            after_letter = byte.is_ascii_alphabetic();
+        }
+
+        if depth == 0 {
+            None
+        } else {
+            Some(Err(SgfError::Parse(SgfParseError::UnexpectedEndOfData)))
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SgfCollectionReader<R> {
+    type Item = Result<ParsedGame, SgfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let game_tree = match self.next_game_tree() {
+            Some(Ok(game_tree)) => game_tree,
+            Some(Err(e)) => {
+                self.finished = true;
+                return Some(Err(e));
+            }
+            None => {
+                self.finished = true;
+                return None;
+            }
+        };
+
+        Some(parse_game_tree(&decode(&game_tree.0, game_tree.1)))
+    }
+}
+
+/// Decodes a game tree with its declared charset.
+///
+/// Files are often converted to UTF-8 without updating `CA`, text that is valid UTF-8 is kept as
+/// it is.
+fn decode(game_tree: &[u8], charset: &'static Encoding) -> String {
+    match std::str::from_utf8(game_tree) {
+        Ok(text) => text.to_string(),
+        Err(_) if charset != UTF_8 => charset
+            .decode_without_bom_handling(game_tree)
+            .0
+            .into_owned(),
+        Err(_) => String::from_utf8_lossy(game_tree).into_owned(),
+    }
+}
+
+/// Whether the byte starts a double byte character in the charset.
+fn is_lead_byte(charset: &'static Encoding, byte: u8) -> bool {
+    if charset == SHIFT_JIS {
+        // Half width katakana in 0xA1..=0xDF are single bytes.
+        return matches!(byte, 0x81..=0x9F | 0xE0..=0xFC);
+    }
+    charset != UTF_8 && !charset.is_single_byte() && byte >= 0x81
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::gsf::parse_sgf_collection;
+
+    #[test]
+    fn given_collection_with_multiple_games_when_parsed_then_it_should_return_every_game() {
+        // Given
+        let input = "(;GM[1]SZ[9];B[aa];W[bb])
+(;GM[3]SZ[8];B[aa])
+(;GM[1]SZ[13]C[tricky ) and \\] in a comment];B[cc])";
+
+        // When
+        let res = parse_sgf_collection(input);
+
+        // Then
+        assert_eq!(3, res.len());
+        let first = res[0].as_ref().expect("Expected first game to parse");
+        assert_eq!(9, first.width);
+        assert_eq!(2, first.moves.len());
+        assert!(matches!(res[1], Err(SgfError::NotGo { game: 3 })));
+        let third = res[2].as_ref().expect("Expected third game to parse");
+        assert_eq!(13, third.width);
+        assert_eq!(1, third.moves.len());
+    }
+
+    #[test]
+    fn given_reader_with_variations_when_streamed_then_it_should_yield_top_level_trees_only() {
+        // Given
+        let input = "garbage before (;GM[1];B[aa](;W[bb])(;W[cc]))(;GM[1];B[dd])";
+        let reader = SgfCollectionReader::new(input.as_bytes());
+
+        // When
+        let games: Vec<ParsedGame> = reader.map(|x| x.expect("Expected game to parse")).collect();
+
+        // Then
+        assert_eq!(2, games.len());
+        assert_eq!(2, games[0].moves.len());
+        assert_eq!(1, games[1].moves.len());
+    }
+
+    #[test]
+    fn given_legacy_charsets_when_streamed_then_text_should_be_decoded() {
+        // Given
+        let (gb2312, _, _) = encoding_rs::GBK.encode("(;CA[GB2312]PB[古力]PW[李世石];B[pd])");
+        // `ソ` ends in 0x5C, which must not be read as an escape.
+        let (shift_jis, _, _) = SHIFT_JIS.encode("(;CA[Shift_JIS]PB[ソ]PW[本因坊];B[pd])");
+        let (euc_kr, _, _) = encoding_rs::EUC_KR.encode("(;CA[EUC-KR]PB[이세돌];B[pd])");
+        let input = [gb2312.as_ref(), shift_jis.as_ref(), euc_kr.as_ref()].concat();
+
+        // When
+        let games: Vec<ParsedGame> = SgfCollectionReader::new(input.as_slice())
+            .map(|x| x.expect("Expected game to parse"))
+            .collect();
+
+        // Then
+        assert_eq!(3, games.len());
+        assert_eq!(Some("古力".to_string()), games[0].black_player);
+        assert_eq!(Some("李世石".to_string()), games[0].white_player);
+        assert_eq!(Some("ソ".to_string()), games[1].black_player);
+        assert_eq!(Some("本因坊".to_string()), games[1].white_player);
+        assert_eq!(Some("이세돌".to_string()), games[2].black_player);
+        assert!(games.iter().all(|game| game.moves.len() == 1));
+    }
+
+    #[test]
+    fn given_truncated_collection_when_streamed_then_it_should_report_an_error() {
+        // Given
+        let input = "(;GM[1];B[aa])(;GM[1];B[bb]";
+        let mut reader = SgfCollectionReader::new(input.as_bytes());
+
+        // When
+        let first = reader.next();
+        let second = reader.next();
+        let third = reader.next();
+
+        // Then
+        assert!(matches!(first, Some(Ok(_))));
+        assert!(matches!(second, Some(Err(SgfError::Parse(_)))));
+        assert!(third.is_none());
+    }
+}
@@ -1,17 +1,84 @@
-use sgf_parse::{go::Prop, parse};
+use std::collections::HashSet;
+
+use sgf_parse::{
+    Color, GameTree, SgfNode, SgfParseError,
+    go::{Point, Prop},
+    parse, unknown_game,
+};
+use thiserror::Error;
 
 use crate::go::{
+    any_game::AnyGame,
     board::FlexibleBoard,
     coordinate::FlexibleCoordinate,
-    game::Game,
+    coordinate_set::CoordinateSet,
+    game::{Game, MoveError, MoveOutcome},
+    handicap::fixed_handicap_placement,
     player::Player,
-    playermove::{Move, PlaceStoneMove},
+    playermove::{Move, PlaceStoneMove, SetupMove},
+    rank::Rank,
+    time_control::{MoveClock, TimeControl},
+};
+use crate::parser::gsf::{
+    collection::SgfCollectionReader,
+    properties::{NodeProperties, parse_node_properties},
 };
 
+pub mod collection;
+pub mod properties;
+pub mod writer;
+
+#[derive(Clone, Default)]
 pub struct ParsedGame {
     pub width: u16,
     pub height: u16,
+    /// `PB`
+    pub black_player: Option<String>,
+    /// `PW`
+    pub white_player: Option<String>,
+    /// Rank of the black player from `BR`, None if missing or not a recognised rank.
+    pub black_rank: Option<Rank>,
+    /// Rank of the white player from `WR`, None if missing or not a recognised rank.
+    pub white_rank: Option<Rank>,
+    /// `KM`
+    pub komi: Option<f64>,
+    /// Amount of handicap stones from `HA`.
+    pub handicap: Option<u8>,
+    /// Time settings from `TM` and `OT`.
+    pub time_control: Option<TimeControl>,
+    /// `RE`, e.g. `W+238.5`, `B+R` or `0`.
+    pub result: Option<String>,
+    /// `RU`
+    pub rules: Option<String>,
+    /// `DT`
+    pub date: Option<String>,
+    /// `EV`
+    pub event: Option<String>,
+    /// `GN`
+    pub game_name: Option<String>,
+    /// `PC`
+    pub place: Option<String>,
+    /// Properties of the nodes before the first move.
+    pub root_properties: NodeProperties,
+    /// Moves of the main variation, setup properties are included as [`Move::Setup`].
     pub moves: Vec<Move>,
+    /// Properties of the node each move came from, `properties[i]` belongs to `moves[i]`.
+    pub properties: Vec<NodeProperties>,
+    /// Stones both players agreed are dead at the end of the game, SGF has no property for
+    /// these so they come from other formats only.
+    pub dead_stones: Vec<FlexibleCoordinate>,
+    /// Alternatives to moves of the main variation, written as SGF variations. Only the main
+    /// variation is parsed, so parsed games have none.
+    pub variations: Vec<Variation>,
+}
+
+/// Moves that could have been played instead of `moves[move_number]` of the main variation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variation {
+    pub move_number: usize,
+    pub moves: Vec<Move>,
+    /// Properties of the node each move goes in, missing ones are empty.
+    pub properties: Vec<NodeProperties>,
 }
 
 impl ParsedGame {
@@ -28,70 +95,412 @@ impl ParsedGame {
 
         game
     }
+
+    /// Replays the game on the board backend that fits the size from `SZ` best.
+    pub fn replay(&self) -> Result<AnyGame, ReplayError> {
+        let mut game = AnyGame::new((self.width, self.height));
+
+        for (index, m) in self.moves.iter().enumerate() {
+            game.make_move(m)
+                .map_err(|error| ReplayError { index, error })?;
+        }
+
+        Ok(game)
+    }
+
+    /// Replays the game like [`ParsedGame::replay`], yielding what each move changed. The stream
+    /// ends after the first illegal move.
+    pub fn replay_events(&self) -> ReplayEvents<'_> {
+        ReplayEvents {
+            game: AnyGame::new((self.width, self.height)),
+            moves: self.moves.iter().enumerate(),
+            failed: false,
+        }
+    }
+
+    /// Rebuilds the clocks of both players after every move from the `BL`, `WL`, `OB` and `OW`
+    /// properties. Moves without recorded time keep the clocks of the move before them, time used
+    /// that was recorded directly is preferred over the time derived from the clocks.
+    ///
+    /// Returns None when the game has no time control, otherwise `clocks[i]` belongs to
+    /// `moves[i]`.
+    pub fn replay_clocks(&self) -> Option<Vec<MoveClock>> {
+        let time_control = self.time_control?;
+        let mut black = time_control.initial_clock();
+        let mut white = time_control.initial_clock();
+
+        let clocks = self
+            .moves
+            .iter()
+            .zip(self.properties.iter())
+            .map(|(m, properties)| {
+                let mover = match m {
+                    Move::PlaceStone(PlaceStoneMove { player, .. }) | Move::Skip { player } => {
+                        Some(*player)
+                    }
+                    Move::Setup(_) => None,
+                };
+
+                let mut time_used = properties.time_used;
+                for player in [Player::Black, Player::White] {
+                    let Some(recorded) = properties.clock(player) else {
+                        continue;
+                    };
+                    let clock = match player {
+                        Player::Black => &mut black,
+                        Player::White => &mut white,
+                    };
+                    if mover == Some(player) && time_used.is_none() {
+                        time_used = Some(time_control.time_used(clock, &recorded));
+                    }
+                    *clock = recorded;
+                }
+
+                MoveClock {
+                    black,
+                    white,
+                    time_used,
+                }
+            })
+            .collect();
+
+        Some(clocks)
+    }
 }
 
-pub fn parse_sgf(sgf: &str) -> ParsedGame {
-    let mut moves = vec![];
-    let mut width: u16 = 0;
-    let mut height: u16 = 0;
-
-    let collection = parse(sgf).expect("Invalid sgf file");
-    let go_game = collection.first().unwrap().as_go_node().unwrap();
-    let size = go_game.get_property("SZ").expect("Game had no size");
-    if let Prop::SZ((w, h)) = size {
-        println!("width: {}, height: {}", w, h);
-        width = *w as u16;
-        height = *h as u16;
+#[derive(Debug, Error)]
+#[error("Move {index} of the game is invalid: {error}")]
+pub struct ReplayError {
+    /// 0-based index into [`ParsedGame::moves`].
+    pub index: usize,
+    #[source]
+    pub error: MoveError,
+}
+
+/// Iterator over the outcomes of the moves of a [`ParsedGame`], see
+/// [`ParsedGame::replay_events`].
+pub struct ReplayEvents<'a> {
+    game: AnyGame,
+    moves: std::iter::Enumerate<std::slice::Iter<'a, Move>>,
+    failed: bool,
+}
+
+impl ReplayEvents<'_> {
+    /// The game after the moves that were yielded so far.
+    pub fn game(&self) -> &AnyGame {
+        &self.game
+    }
+}
+
+impl Iterator for ReplayEvents<'_> {
+    type Item = Result<MoveOutcome, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let (index, m) = self.moves.next()?;
+        let res = self
+            .game
+            .make_move(m)
+            .map_err(|error| ReplayError { index, error });
+        self.failed = res.is_err();
+        Some(res)
+    }
+}
+
+/// Parses the first game of an SGF collection.
+///
+/// Use [`parse_sgf_collection`] or [`collection::SgfCollectionReader`] for files that hold more
+/// than one game.
+pub fn parse_sgf(sgf: &str) -> Result<ParsedGame, SgfError> {
+    SgfCollectionReader::utf8(sgf.as_bytes())
+        .next()
+        .unwrap_or(Err(SgfError::NoGames))
+}
+
+/// Parses the first game of an SGF file as read from disk, decoding it with the charset in its
+/// `CA` property.
+pub fn parse_sgf_bytes(sgf: &[u8]) -> Result<ParsedGame, SgfError> {
+    SgfCollectionReader::new(sgf)
+        .next()
+        .unwrap_or(Err(SgfError::NoGames))
+}
+
+/// Parses every game of an SGF collection, games that fail to parse or are not Go games are
+/// reported in place so the indices match the collection.
+pub fn parse_sgf_collection(sgf: &str) -> Vec<Result<ParsedGame, SgfError>> {
+    SgfCollectionReader::utf8(sgf.as_bytes()).collect()
+}
+
+/// Parses the text of a single game tree.
+pub fn parse_game_tree(sgf: &str) -> Result<ParsedGame, SgfError> {
+    let collection = parse(sgf)?;
+    let game_tree = collection.first().ok_or(SgfError::NoGames)?;
+
+    match game_tree {
+        GameTree::GoGame(go_game) => Ok(parse_go_game(go_game)),
+        GameTree::Unknown(node) => Err(SgfError::NotGo {
+            game: match node.get_property("GM") {
+                Some(unknown_game::Prop::GM(game)) => *game,
+                _ => 0,
+            },
+        }),
     }
+}
+
+fn parse_go_game(go_game: &SgfNode<Prop>) -> ParsedGame {
+    let mut moves = vec![];
+
+    // SGF defaults to a 19x19 board when the size is left out.
+    let (width, height) = match go_game.get_property("SZ") {
+        Some(Prop::SZ((w, h))) => (*w as u16, *h as u16),
+        _ => (19, 19),
+    };
+
+    let handicap = match go_game.get_property("HA") {
+        Some(Prop::HA(stones)) => u8::try_from(*stones).ok(),
+        _ => None,
+    };
+
+    let komi = match go_game.get_property("KM") {
+        Some(Prop::KM(komi)) => Some(*komi),
+        _ => None,
+    };
+
+    let main_time = match go_game.get_property("TM") {
+        Some(Prop::TM(main_time)) => Some(*main_time),
+        _ => None,
+    };
+    let overtime = match go_game.get_property("OT") {
+        Some(Prop::OT(overtime)) => Some(overtime.to_string()),
+        _ => None,
+    };
+    let time_control = TimeControl::from_sgf(main_time, overtime.as_deref());
+
+    let mut properties: Vec<NodeProperties> = vec![];
+    let mut root_properties = NodeProperties::default();
 
     for node in go_game.main_variation() {
+        let moves_before = moves.len();
+
+        let setup_move = parse_setup_move(node);
+        if !setup_move.is_empty() {
+            moves.push(Move::Setup(setup_move));
+        }
+
         if let Some(prop) = node.get_move() {
             if let Prop::B(m) = prop {
-                moves.push(match m {
-                    sgf_parse::go::Move::Pass => Move::Skip {
-                        player: Player::Black,
-                    },
-                    sgf_parse::go::Move::Move(point) => Move::PlaceStone(PlaceStoneMove {
-                        player: Player::Black,
-                        coord: FlexibleCoordinate {
-                            x: point.x as u16,
-                            y: point.y as u16,
-                        },
-                    }),
-                });
+                moves.push(parse_move(m, Player::Black, (width, height)));
             }
 
             if let Prop::W(m) = prop {
-                moves.push(match m {
-                    sgf_parse::go::Move::Pass => Move::Skip {
-                        player: Player::White,
-                    },
-                    sgf_parse::go::Move::Move(point) => Move::PlaceStone(PlaceStoneMove {
-                        player: Player::White,
-                        coord: FlexibleCoordinate {
-                            x: point.x as u16,
-                            y: point.y as u16,
-                        },
-                    }),
-                });
+                moves.push(parse_move(m, Player::White, (width, height)));
             }
         }
+
+        // Every move gets the properties of its node, nodes without moves are folded into the
+        // move before them.
+        let node_properties = parse_node_properties(node);
+        if moves.len() > moves_before {
+            properties.resize_with(moves.len() - 1, NodeProperties::default);
+            properties.push(node_properties);
+        } else if let Some(last) = properties.last_mut() {
+            last.merge(node_properties);
+        } else {
+            root_properties.merge(node_properties);
+        }
+    }
+
+    if let Some(stones) = handicap
+        && needs_fixed_handicap(&moves)
+        && let Some(setup_move) = fixed_handicap_setup((width, height), stones)
+    {
+        moves.insert(0, setup_move);
+        properties.insert(0, NodeProperties::default());
     }
 
+    let text = |identifier: &str| game_info_text(go_game, identifier);
+
     ParsedGame {
         width,
         height,
+        black_player: text("PB"),
+        white_player: text("PW"),
+        black_rank: text("BR").and_then(|rank| rank.parse().ok()),
+        white_rank: text("WR").and_then(|rank| rank.parse().ok()),
+        komi,
+        handicap,
+        time_control,
+        result: text("RE"),
+        rules: text("RU"),
+        date: text("DT"),
+        event: text("EV"),
+        game_name: text("GN"),
+        place: text("PC"),
+        root_properties,
         moves,
+        properties,
+        dead_stones: vec![],
+        variations: vec![],
+    }
+}
+
+fn game_info_text(node: &SgfNode<Prop>, identifier: &str) -> Option<String> {
+    match node.get_property(identifier)? {
+        Prop::PB(text)
+        | Prop::PW(text)
+        | Prop::BR(text)
+        | Prop::WR(text)
+        | Prop::RE(text)
+        | Prop::RU(text)
+        | Prop::DT(text)
+        | Prop::EV(text)
+        | Prop::GN(text)
+        | Prop::PC(text) => Some(text.to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SgfError {
+    #[error("Could not read the SGF data")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid SGF: {0}")]
+    Parse(#[from] SgfParseError),
+    #[error("SGF game tree is for game {game}, only Go (GM[1]) is supported")]
+    NotGo { game: i64 },
+    #[error("SGF collection contains no games")]
+    NoGames,
+}
+
+fn parse_move(m: &sgf_parse::go::Move, player: Player, (width, height): (u16, u16)) -> Move {
+    match m {
+        sgf_parse::go::Move::Pass => Move::Skip { player },
+        // FF[3] and older write passes as `tt`, which is off the board up to 19x19.
+        sgf_parse::go::Move::Move(Point { x: 19, y: 19 }) if width <= 19 && height <= 19 => {
+            Move::Skip { player }
+        }
+        sgf_parse::go::Move::Move(point) => Move::PlaceStone(PlaceStoneMove {
+            player,
+            coord: to_coordinate(point),
+        }),
+    }
+}
+
+fn parse_setup_move(node: &SgfNode<Prop>) -> SetupMove {
+    let mut setup_move = SetupMove {
+        add_black: CoordinateSet::new(vec![]),
+        add_white: CoordinateSet::new(vec![]),
+        clear: CoordinateSet::new(vec![]),
+        player_to_move: None,
+    };
+
+    for prop in node.properties() {
+        match prop {
+            Prop::AB(points) => setup_move.add_black = to_coordinate_set(points),
+            Prop::AW(points) => setup_move.add_white = to_coordinate_set(points),
+            Prop::AE(points) => setup_move.clear = to_coordinate_set(points),
+            Prop::PL(Color::Black) => setup_move.player_to_move = Some(Player::Black),
+            Prop::PL(Color::White) => setup_move.player_to_move = Some(Player::White),
+            _ => {}
+        }
+    }
+
+    setup_move
+}
+
+/// Some files only record `HA` and leave the stones out. When the game does not set up any stones
+/// before the first move and white moves first, the stones were placed on the standard points.
+fn needs_fixed_handicap(moves: &[Move]) -> bool {
+    for m in moves {
+        match m {
+            Move::Setup(setup_move) if !setup_move.add_black.is_empty() => return false,
+            Move::Setup(_) => continue,
+            Move::PlaceStone(PlaceStoneMove { player, .. }) | Move::Skip { player } => {
+                return *player == Player::White;
+            }
+        }
+    }
+    false
+}
+
+/// The setup move that places handicap stones on the standard points, white moves next.
+pub(crate) fn fixed_handicap_setup(board_size: (u16, u16), stones: u8) -> Option<Move> {
+    let add_black = fixed_handicap_placement(board_size, stones).ok()?;
+    Some(Move::Setup(SetupMove {
+        add_black,
+        add_white: CoordinateSet::new(vec![]),
+        clear: CoordinateSet::new(vec![]),
+        player_to_move: Some(Player::White),
+    }))
+}
+
+fn to_coordinate(point: &Point) -> FlexibleCoordinate {
+    FlexibleCoordinate {
+        x: point.x as u16,
+        y: point.y as u16,
     }
 }
 
+fn to_coordinate_set(points: &HashSet<Point>) -> CoordinateSet {
+    CoordinateSet::new(points.iter().map(to_coordinate).collect())
+}
+
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use crate::{
-        go::{bitmask::TestMask, bitmask_board::BitMaskBoard, bitmask19::BitMask19},
-        parser::gsf::parse_sgf,
+        go::{
+            any_game::AnyGame, bitmask::TestMask, bitmask_board::BitMaskBoard,
+            bitmask19::BitMask19, board::FlexibleBoard, coordinate::FlexibleCoordinate,
+            coordinate_set::CoordinateSet, game::MoveError, player::Player, playermove::Move,
+            rank::Rank, time_control::TimeControl,
+        },
+        parser::gsf::{ReplayError, parse_sgf, parse_sgf_bytes, properties::Markup},
     };
 
+    #[test]
+    fn given_sgf_with_setup_properties_when_run_then_it_should_place_the_stones() {
+        // Given
+        let input = "(;GM[1]SZ[9]HA[2]AB[cg][gc];W[ee];B[ff]AE[gc];AW[aa]PL[W])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let game = parsed.run(|size| BitMaskBoard::new(|| TestMask::empty(size)));
+
+        // Then
+        let board = game.get_board();
+        let at = |x, y| board.get_player_at(&FlexibleCoordinate { x, y });
+        assert_eq!(Some(2), parsed.handicap);
+        assert_eq!(Some(Player::Black), at(2, 6));
+        assert_eq!(None, at(6, 2));
+        assert_eq!(Some(Player::White), at(4, 4));
+        assert_eq!(Some(Player::Black), at(5, 5));
+        assert_eq!(Some(Player::White), at(0, 0));
+        assert_eq!(Player::White, game.get_current_player());
+    }
+
+    #[test]
+    fn given_sgf_with_handicap_but_no_stones_when_run_then_it_should_place_fixed_handicap() {
+        // Given
+        let input = "(;GM[1]SZ[19]HA[4];W[qc];B[qq])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let game = parsed.run(|_size| BitMaskBoard::new(BitMask19::init));
+
+        // Then
+        let board = game.get_board();
+        let at = |x, y| board.get_player_at(&FlexibleCoordinate { x, y });
+        assert_eq!(Some(Player::Black), at(3, 3));
+        assert_eq!(Some(Player::Black), at(15, 3));
+        assert_eq!(Some(Player::Black), at(3, 15));
+        assert_eq!(Some(Player::Black), at(15, 15));
+        assert_eq!(Some(Player::White), at(16, 2));
+    }
+
     #[test]
     fn should_parse() {
         let input = "(;FF[4]
@@ -337,12 +746,185 @@ ikbenrafaelbieze2001: thank you for not exiting the game <3
 ]
 )))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))))";
 
-        let game = parse_sgf(input);
+        let game = parse_sgf(input).expect("Expected sgf to parse");
 
-        for _ in 0..1000 {
-            let res = game.run(|size| BitMaskBoard::new(BitMask19::init));
-        }
-        // println!("{}", res.get_board().display());
-        // panic!();
+        assert_eq!(Some(Rank::kyu(31)), game.black_rank);
+        assert_eq!(Some(Rank::kyu(3)), game.white_rank);
+
+        let res = game.replay().expect("Expected game to replay");
+        assert!(matches!(res, AnyGame::Nineteen(_)));
+        assert_eq!((19, 19), res.get_size());
+    }
+
+    #[test]
+    fn given_sgf_with_time_left_when_clocks_are_replayed_then_it_should_track_time_used() {
+        // Given
+        let input = "(;GM[1]SZ[9]TM[300]OT[3x30 byo-yomi]
+;B[aa]BL[290];W[bb]WL[280];B[cc]BL[20]OB[3];W[dd];B[ee]BL[15]OB[2])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let clocks = parsed.replay_clocks().expect("Expected clocks");
+
+        // Then
+        assert_eq!(
+            Some(TimeControl::ByoYomi {
+                main_time: Duration::from_secs(300),
+                periods: 3,
+                period_time: Duration::from_secs(30),
+            }),
+            parsed.time_control
+        );
+        let time_used: Vec<Option<u64>> = clocks
+            .iter()
+            .map(|x| x.time_used.map(|x| x.as_secs()))
+            .collect();
+        assert_eq!(
+            vec![Some(10), Some(20), Some(300), None, Some(45)],
+            time_used
+        );
+        assert_eq!(Duration::from_secs(280), clocks[4].white.time_left);
+        assert_eq!(Some(2), clocks[4].black.overtime_left);
+    }
+
+    #[test]
+    fn given_rectangular_sgf_when_replayed_then_it_should_use_the_sgf_size() {
+        // Given
+        let input = "(;GM[1]SZ[7:11];B[gk];W[ak])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let game = parsed.replay().expect("Expected game to replay");
+
+        // Then
+        assert_eq!((7, 11), game.get_size());
+        assert_eq!(
+            Some(Player::Black),
+            game.get_player_at(&FlexibleCoordinate { x: 6, y: 10 })
+        );
+    }
+
+    #[test]
+    fn given_ff3_sgf_when_parsed_then_tt_passes_and_old_properties_should_be_read() {
+        // Given
+        let input = b"(;GaMe[1]FF[3]SZ[19]CA[GB2312]AddBlack[dd][pp]
+;W[pd]L[aa][bb]M[cc];B[tt];W[tt])";
+
+        // When
+        let game = parse_sgf_bytes(input).expect("Expected sgf to parse");
+
+        // Then
+        assert_eq!(4, game.moves.len());
+        assert!(matches!(game.moves[0], Move::Setup(_)));
+        assert!(matches!(
+            game.moves[2],
+            Move::Skip {
+                player: Player::Black
+            }
+        ));
+        assert!(matches!(
+            game.moves[3],
+            Move::Skip {
+                player: Player::White
+            }
+        ));
+        assert_eq!(
+            vec![
+                Markup::Label(FlexibleCoordinate { x: 0, y: 0 }, "a".to_string()),
+                Markup::Label(FlexibleCoordinate { x: 1, y: 1 }, "b".to_string()),
+                Markup::Cross(FlexibleCoordinate { x: 2, y: 2 }),
+            ],
+            game.properties[1].markup
+        );
+        assert!(game.replay().is_ok());
+    }
+
+    #[test]
+    fn given_sgf_with_illegal_move_when_replayed_then_it_should_report_the_move() {
+        // Given
+        let input = "(;GM[1]SZ[9];B[aa];W[aa])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let res = parsed.replay();
+
+        // Then
+        let error = res.expect_err("Expected replay to fail");
+        assert_eq!(1, error.index);
+    }
+
+    #[test]
+    fn given_sgf_with_move_outside_of_the_board_when_replayed_then_it_should_report_the_move() {
+        // Given
+        let near = parse_sgf("(;GM[1]SZ[9];B[aa];W[kk])").expect("Expected sgf to parse");
+        let far = parse_sgf("(;GM[1]SZ[9];B[zz])").expect("Expected sgf to parse");
+
+        // When
+        let near = near.replay();
+        let far = far.replay();
+
+        // Then
+        let error = near.expect_err("Expected replay to fail");
+        assert_eq!(1, error.index);
+        assert!(matches!(error.error, MoveError::OutOfBoard { .. }));
+        let error = far.expect_err("Expected replay to fail");
+        assert_eq!(0, error.index);
+    }
+
+    #[test]
+    fn given_sgf_with_ko_when_replayed_as_events_then_each_event_should_describe_the_move() {
+        // Given
+        let input = "(;GM[1]SZ[9];B[ca];W[ba];B[db];W[ab];B[cc];W[cb];B[gg];W[bc];B[bb];W[ee];B[ff];W[cb];B[])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let events: Vec<_> = parsed
+            .replay_events()
+            .collect::<Result<_, _>>()
+            .expect("Expected legal moves");
+
+        // Then
+        assert_eq!(13, events.len());
+        let take = &events[8];
+        assert_eq!(
+            Some(FlexibleCoordinate { x: 1, y: 1 }),
+            take.placed().map(|x| x.coord)
+        );
+        assert_eq!(vec![CoordinateSet::set(&[(2, 1)])], take.captured);
+        assert_eq!(Some(FlexibleCoordinate { x: 2, y: 1 }), take.ko);
+        assert!(take.ko_changed());
+        assert_eq!((1, 0), (take.captured_by_black, take.captured_by_white));
+        assert_eq!(None, events[9].ko);
+        assert!(events[9].ko_changed());
+        assert_eq!(1, events[11].captured_count());
+        assert_eq!(
+            (1, 1),
+            (events[11].captured_by_black, events[11].captured_by_white)
+        );
+        assert_eq!(None, events[12].placed());
+    }
+
+    #[test]
+    fn given_sgf_with_illegal_move_when_replayed_as_events_then_the_stream_should_stop() {
+        // Given
+        let input = "(;GM[1]SZ[9];B[aa];W[aa];B[bb])";
+        let parsed = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let mut events = parsed.replay_events();
+
+        // Then
+        assert!(matches!(events.next(), Some(Ok(_))));
+        assert!(matches!(
+            events.next(),
+            Some(Err(ReplayError { index: 1, .. }))
+        ));
+        assert!(events.next().is_none());
+        assert!(
+            events
+                .game()
+                .get_player_at(&FlexibleCoordinate { x: 0, y: 0 })
+                .is_some()
+        );
     }
 }
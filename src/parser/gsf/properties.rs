@@ -0,0 +1,342 @@
+use std::time::Duration;
+
+use sgf_parse::{
+    Double, SgfNode,
+    go::{Point, Prop},
+};
+
+use crate::go::{coordinate::FlexibleCoordinate, player::Player, time_control::ClockState};
+use crate::parser::gsf::to_coordinate;
+
+/// Comments, annotations and markup of an SGF node.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeProperties {
+    /// `C`
+    pub comment: Option<String>,
+    /// `N`
+    pub name: Option<String>,
+    /// `V`, the estimated score, positive is good for black.
+    pub value: Option<f64>,
+    /// `GB`, `GW`, `DM` and `UC`
+    pub position: Option<PositionAnnotation>,
+    /// `BM`, `TE`, `DO` and `IT`
+    pub move_annotation: Option<MoveAnnotation>,
+    /// `HO`
+    pub hotspot: Option<Emphasis>,
+    /// `TR`, `SQ`, `CR`, `MA`, `SL`, `LB`, `AR` and `LN`
+    pub markup: Vec<Markup>,
+    /// `DD`, points to dim. An empty list clears the dimmed points of earlier nodes.
+    pub dimmed: Option<Vec<FlexibleCoordinate>>,
+    /// `BL`, seconds black has left, in overtime the seconds left in the current period.
+    pub black_time_left: Option<f64>,
+    /// `WL`, seconds white has left, in overtime the seconds left in the current period.
+    pub white_time_left: Option<f64>,
+    /// `OB`, byo-yomi periods or Canadian stones black has left.
+    pub black_overtime_left: Option<u32>,
+    /// `OW`, byo-yomi periods or Canadian stones white has left.
+    pub white_overtime_left: Option<u32>,
+    /// Time spent on the move, for formats that record it directly. SGF has no property for
+    /// this, it is not written by [`NodeProperties::to_sgf`].
+    pub time_used: Option<Duration>,
+}
+
+impl NodeProperties {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Adds the properties of another node, used when a node without a move is folded into the
+    /// move before it. Comments are joined, markup is added and the other values replace ours.
+    pub fn merge(&mut self, other: NodeProperties) {
+        self.comment = match (self.comment.take(), other.comment) {
+            (Some(ours), Some(theirs)) => Some(format!("{ours}\n{theirs}")),
+            (ours, theirs) => ours.or(theirs),
+        };
+        self.name = other.name.or(self.name.take());
+        self.value = other.value.or(self.value);
+        self.position = other.position.or(self.position);
+        self.move_annotation = other.move_annotation.or(self.move_annotation);
+        self.hotspot = other.hotspot.or(self.hotspot);
+        self.markup.extend(other.markup);
+        self.dimmed = other.dimmed.or(self.dimmed.take());
+        self.black_time_left = other.black_time_left.or(self.black_time_left);
+        self.white_time_left = other.white_time_left.or(self.white_time_left);
+        self.black_overtime_left = other.black_overtime_left.or(self.black_overtime_left);
+        self.white_overtime_left = other.white_overtime_left.or(self.white_overtime_left);
+        self.time_used = other.time_used.or(self.time_used);
+    }
+
+    /// The clock of a player as recorded in this node, None when the node has no time left for
+    /// the player or the time is too large to be a [`Duration`].
+    pub fn clock(&self, player: Player) -> Option<ClockState> {
+        let (time_left, overtime_left) = match player {
+            Player::Black => (self.black_time_left, self.black_overtime_left),
+            Player::White => (self.white_time_left, self.white_overtime_left),
+        };
+        Some(ClockState {
+            time_left: Duration::try_from_secs_f64(time_left?.max(0.0)).ok()?,
+            overtime_left,
+        })
+    }
+
+    /// Writes the properties as SGF, coordinates are sorted so the output is stable.
+    pub fn to_sgf(&self) -> String {
+        let mut res = String::new();
+
+        if let Some(name) = &self.name {
+            res.push_str(&format!("N[{}]", escape_text(name)));
+        }
+        if let Some(comment) = &self.comment {
+            res.push_str(&format!("C[{}]", escape_text(comment)));
+        }
+        if let Some(value) = self.value {
+            res.push_str(&format!("V[{value}]"));
+        }
+        if let Some(position) = self.position {
+            res.push_str(&match position {
+                PositionAnnotation::GoodForBlack(e) => format!("GB[{}]", e.to_sgf()),
+                PositionAnnotation::GoodForWhite(e) => format!("GW[{}]", e.to_sgf()),
+                PositionAnnotation::Even(e) => format!("DM[{}]", e.to_sgf()),
+                PositionAnnotation::Unclear(e) => format!("UC[{}]", e.to_sgf()),
+            });
+        }
+        if let Some(move_annotation) = self.move_annotation {
+            res.push_str(&match move_annotation {
+                MoveAnnotation::Bad(e) => format!("BM[{}]", e.to_sgf()),
+                MoveAnnotation::Tesuji(e) => format!("TE[{}]", e.to_sgf()),
+                MoveAnnotation::Doubtful => "DO[]".to_string(),
+                MoveAnnotation::Interesting => "IT[]".to_string(),
+            });
+        }
+        if let Some(hotspot) = self.hotspot {
+            res.push_str(&format!("HO[{}]", hotspot.to_sgf()));
+        }
+
+        let mut markup = self.markup.clone();
+        markup.sort_by_key(|m| m.sort_key());
+        let mut last_identifier = "";
+        for m in &markup {
+            let identifier = m.identifier();
+            if identifier != last_identifier {
+                res.push_str(identifier);
+                last_identifier = identifier;
+            }
+            res.push_str(&format!("[{}]", m.value_to_sgf()));
+        }
+
+        if let Some(time_left) = self.black_time_left {
+            res.push_str(&format!("BL[{time_left}]"));
+        }
+        if let Some(overtime_left) = self.black_overtime_left {
+            res.push_str(&format!("OB[{overtime_left}]"));
+        }
+        if let Some(time_left) = self.white_time_left {
+            res.push_str(&format!("WL[{time_left}]"));
+        }
+        if let Some(overtime_left) = self.white_overtime_left {
+            res.push_str(&format!("OW[{overtime_left}]"));
+        }
+
+        if let Some(dimmed) = &self.dimmed {
+            res.push_str("DD");
+            if dimmed.is_empty() {
+                res.push_str("[]");
+            }
+            let mut dimmed = dimmed.clone();
+            dimmed.sort_by_key(|c| (c.y, c.x));
+            for coord in dimmed {
+                res.push_str(&format!("[{}]", coordinate_to_sgf(&coord)));
+            }
+        }
+
+        res
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emphasis {
+    Normal,
+    Strong,
+}
+
+impl Emphasis {
+    fn to_sgf(self) -> &'static str {
+        match self {
+            Emphasis::Normal => "1",
+            Emphasis::Strong => "2",
+        }
+    }
+}
+
+impl From<&Double> for Emphasis {
+    fn from(value: &Double) -> Self {
+        match value {
+            Double::One => Emphasis::Normal,
+            Double::Two => Emphasis::Strong,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionAnnotation {
+    GoodForBlack(Emphasis),
+    GoodForWhite(Emphasis),
+    Even(Emphasis),
+    Unclear(Emphasis),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveAnnotation {
+    Bad(Emphasis),
+    Tesuji(Emphasis),
+    Doubtful,
+    Interesting,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Markup {
+    Triangle(FlexibleCoordinate),
+    Square(FlexibleCoordinate),
+    Circle(FlexibleCoordinate),
+    Cross(FlexibleCoordinate),
+    Selected(FlexibleCoordinate),
+    Label(FlexibleCoordinate, String),
+    Arrow(FlexibleCoordinate, FlexibleCoordinate),
+    Line(FlexibleCoordinate, FlexibleCoordinate),
+}
+
+impl Markup {
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            Markup::Triangle(_) => "TR",
+            Markup::Square(_) => "SQ",
+            Markup::Circle(_) => "CR",
+            Markup::Cross(_) => "MA",
+            Markup::Selected(_) => "SL",
+            Markup::Label(_, _) => "LB",
+            Markup::Arrow(_, _) => "AR",
+            Markup::Line(_, _) => "LN",
+        }
+    }
+
+    fn value_to_sgf(&self) -> String {
+        match self {
+            Markup::Triangle(c)
+            | Markup::Square(c)
+            | Markup::Circle(c)
+            | Markup::Cross(c)
+            | Markup::Selected(c) => coordinate_to_sgf(c),
+            Markup::Label(c, text) => {
+                format!("{}:{}", coordinate_to_sgf(c), escape_text(text))
+            }
+            Markup::Arrow(from, to) | Markup::Line(from, to) => {
+                format!("{}:{}", coordinate_to_sgf(from), coordinate_to_sgf(to))
+            }
+        }
+    }
+
+    fn sort_key(&self) -> (&'static str, u16, u16, u16, u16) {
+        let (first, second) = match self {
+            Markup::Triangle(c)
+            | Markup::Square(c)
+            | Markup::Circle(c)
+            | Markup::Cross(c)
+            | Markup::Selected(c)
+            | Markup::Label(c, _) => (c, c),
+            Markup::Arrow(from, to) | Markup::Line(from, to) => (from, to),
+        };
+        (self.identifier(), first.y, first.x, second.y, second.x)
+    }
+}
+
+pub(crate) fn parse_node_properties(node: &SgfNode<Prop>) -> NodeProperties {
+    let mut res = NodeProperties::default();
+
+    for prop in node.properties() {
+        match prop {
+            Prop::C(text) => res.comment = Some(text.to_string()),
+            Prop::N(text) => res.name = Some(text.to_string()),
+            Prop::V(value) => res.value = Some(*value),
+            Prop::GB(e) => res.position = Some(PositionAnnotation::GoodForBlack(e.into())),
+            Prop::GW(e) => res.position = Some(PositionAnnotation::GoodForWhite(e.into())),
+            Prop::DM(e) => res.position = Some(PositionAnnotation::Even(e.into())),
+            Prop::UC(e) => res.position = Some(PositionAnnotation::Unclear(e.into())),
+            Prop::BM(e) => res.move_annotation = Some(MoveAnnotation::Bad(e.into())),
+            Prop::TE(e) => res.move_annotation = Some(MoveAnnotation::Tesuji(e.into())),
+            Prop::DO => res.move_annotation = Some(MoveAnnotation::Doubtful),
+            Prop::IT => res.move_annotation = Some(MoveAnnotation::Interesting),
+            Prop::HO(e) => res.hotspot = Some(e.into()),
+            Prop::TR(points) => res
+                .markup
+                .extend(points.iter().map(|p| Markup::Triangle(to_coordinate(p)))),
+            Prop::SQ(points) => res
+                .markup
+                .extend(points.iter().map(|p| Markup::Square(to_coordinate(p)))),
+            Prop::CR(points) => res
+                .markup
+                .extend(points.iter().map(|p| Markup::Circle(to_coordinate(p)))),
+            Prop::MA(points) => res
+                .markup
+                .extend(points.iter().map(|p| Markup::Cross(to_coordinate(p)))),
+            Prop::SL(points) => res
+                .markup
+                .extend(points.iter().map(|p| Markup::Selected(to_coordinate(p)))),
+            Prop::LB(labels) => res.markup.extend(
+                labels
+                    .iter()
+                    .map(|(p, text)| Markup::Label(to_coordinate(p), text.to_string())),
+            ),
+            Prop::AR(arrows) => res.markup.extend(
+                arrows
+                    .iter()
+                    .map(|(from, to)| Markup::Arrow(to_coordinate(from), to_coordinate(to))),
+            ),
+            Prop::LN(lines) => res.markup.extend(
+                lines
+                    .iter()
+                    .map(|(from, to)| Markup::Line(to_coordinate(from), to_coordinate(to))),
+            ),
+            Prop::DD(points) => res.dimmed = Some(points.iter().map(to_coordinate).collect()),
+            Prop::BL(time_left) => res.black_time_left = Some(*time_left),
+            Prop::WL(time_left) => res.white_time_left = Some(*time_left),
+            Prop::OB(overtime_left) => res.black_overtime_left = u32::try_from(*overtime_left).ok(),
+            Prop::OW(overtime_left) => res.white_overtime_left = u32::try_from(*overtime_left).ok(),
+            // FF[3] labels points with consecutive letters and marks them with `M`.
+            Prop::Unknown(identifier, values) if identifier == "L" => res.markup.extend(
+                ('a'..='z')
+                    .zip(values.iter().filter_map(|v| v.parse::<Point>().ok()))
+                    .map(|(letter, p)| Markup::Label(to_coordinate(&p), letter.to_string())),
+            ),
+            Prop::Unknown(identifier, values) if identifier == "M" => res.markup.extend(
+                values
+                    .iter()
+                    .filter_map(|v| v.parse::<Point>().ok())
+                    .map(|p| Markup::Cross(to_coordinate(&p))),
+            ),
+            _ => {}
+        }
+    }
+
+    // Lists in SGF have no order, sorting keeps the markup the same between runs.
+    res.markup.sort_by_key(|m| m.sort_key());
+    if let Some(dimmed) = &mut res.dimmed {
+        dimmed.sort_by_key(|c| (c.y, c.x));
+    }
+
+    res
+}
+
+pub(crate) fn coordinate_to_sgf(coord: &FlexibleCoordinate) -> String {
+    let to_char = |v: u16| {
+        if v < 26 {
+            (b'a' + v as u8) as char
+        } else {
+            (b'A' + (v - 26) as u8) as char
+        }
+    };
+    format!("{}{}", to_char(coord.x), to_char(coord.y))
+}
+
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(']', "\\]")
+}
@@ -0,0 +1,240 @@
+use crate::{
+    go::{
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        player::Player,
+        playermove::{Move, PlaceStoneMove, SetupMove},
+    },
+    parser::gsf::{
+        ParsedGame,
+        properties::{NodeProperties, coordinate_to_sgf, escape_text},
+    },
+};
+
+/// Writes the game as an SGF FF\[4\] game tree with one node per move.
+///
+/// A setup move at the start of the game is written into the root node when the root has no
+/// properties of its own, which is where other programs expect handicap stones.
+pub fn write_sgf(game: &ParsedGame) -> String {
+    let mut res = String::from("(;GM[1]FF[4]CA[UTF-8]");
+
+    if game.width == game.height {
+        res.push_str(&format!("SZ[{}]", game.width));
+    } else {
+        res.push_str(&format!("SZ[{}:{}]", game.width, game.height));
+    }
+
+    let mut push_text = |identifier: &str, value: &Option<String>| {
+        if let Some(value) = value {
+            res.push_str(&format!("{identifier}[{}]", escape_text(value)));
+        }
+    };
+    push_text("GN", &game.game_name);
+    push_text("EV", &game.event);
+    push_text("DT", &game.date);
+    push_text("PC", &game.place);
+    push_text("PB", &game.black_player);
+    push_text("PW", &game.white_player);
+    push_text("BR", &game.black_rank.map(|rank| rank.to_string()));
+    push_text("WR", &game.white_rank.map(|rank| rank.to_string()));
+    push_text("RU", &game.rules);
+    push_text("RE", &game.result);
+
+    if let Some(komi) = game.komi {
+        res.push_str(&format!("KM[{komi}]"));
+    }
+    if let Some(handicap) = game.handicap {
+        res.push_str(&format!("HA[{handicap}]"));
+    }
+    if let Some(time_control) = game.time_control {
+        res.push_str(&format!("TM[{}]", time_control.main_time().as_secs_f64()));
+        if let Some(overtime) = time_control.overtime_to_sgf() {
+            res.push_str(&format!("OT[{overtime}]"));
+        }
+    }
+
+    res.push_str(&game.root_properties.to_sgf());
+    let mut start = 0;
+    if game.root_properties.is_empty()
+        && let (Some(Move::Setup(setup_move)), Some(properties)) =
+            (game.moves.first(), game.properties.first())
+    {
+        res.push_str(&setup_move_to_sgf(setup_move));
+        res.push_str(&properties.to_sgf());
+        start = 1;
+    }
+    res.push('\n');
+
+    push_main_variation(&mut res, game, start);
+
+    res.push(')');
+    res
+}
+
+/// Writes the main variation from move `start`, branching off where a variation starts.
+fn push_main_variation(res: &mut String, game: &ParsedGame, start: usize) {
+    let default = NodeProperties::default();
+    for index in start..=game.moves.len() {
+        let variations: Vec<_> = game
+            .variations
+            .iter()
+            .filter(|x| x.move_number == index && !x.moves.is_empty())
+            .collect();
+        if !variations.is_empty() {
+            if let Some(m) = game.moves.get(index) {
+                res.push('(');
+                push_node(res, m, game.properties.get(index).unwrap_or(&default));
+                push_main_variation(res, game, index + 1);
+                res.push(')');
+            }
+            for variation in variations {
+                res.push('(');
+                for (i, m) in variation.moves.iter().enumerate() {
+                    push_node(res, m, variation.properties.get(i).unwrap_or(&default));
+                }
+                res.push(')');
+            }
+            return;
+        }
+
+        if let Some(m) = game.moves.get(index) {
+            push_node(res, m, game.properties.get(index).unwrap_or(&default));
+        }
+    }
+}
+
+fn push_node(res: &mut String, m: &Move, properties: &NodeProperties) {
+    res.push(';');
+    res.push_str(&move_to_sgf(m));
+    res.push_str(&properties.to_sgf());
+    res.push('\n');
+}
+
+fn move_to_sgf(m: &Move) -> String {
+    match m {
+        Move::PlaceStone(PlaceStoneMove { player, coord }) => {
+            format!("{}[{}]", player_to_sgf(player), coordinate_to_sgf(coord))
+        }
+        Move::Skip { player } => format!("{}[]", player_to_sgf(player)),
+        Move::Setup(setup_move) => setup_move_to_sgf(setup_move),
+    }
+}
+
+fn setup_move_to_sgf(setup_move: &SetupMove) -> String {
+    let mut res = String::new();
+    res.push_str(&coordinate_list_to_sgf("AB", &setup_move.add_black));
+    res.push_str(&coordinate_list_to_sgf("AW", &setup_move.add_white));
+    res.push_str(&coordinate_list_to_sgf("AE", &setup_move.clear));
+    if let Some(player) = &setup_move.player_to_move {
+        res.push_str(&format!("PL[{}]", player_to_sgf(player)));
+    }
+    res
+}
+
+fn coordinate_list_to_sgf(identifier: &str, coords: &CoordinateSet) -> String {
+    if coords.is_empty() {
+        return String::new();
+    }
+
+    let mut sorted: Vec<&FlexibleCoordinate> = coords.iter().collect();
+    sorted.sort_by_key(|c| (c.y, c.x));
+
+    let mut res = identifier.to_string();
+    for coord in sorted {
+        res.push_str(&format!("[{}]", coordinate_to_sgf(coord)));
+    }
+    res
+}
+
+fn player_to_sgf(player: &Player) -> &'static str {
+    match player {
+        Player::Black => "B",
+        Player::White => "W",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::gsf::{
+        parse_sgf,
+        properties::{Emphasis, Markup, MoveAnnotation, PositionAnnotation},
+    };
+
+    #[test]
+    fn given_sgf_with_markup_and_annotations_when_parsed_then_properties_belong_to_their_move() {
+        // Given
+        let input = "(;GM[1]SZ[9]C[Game start]
+;B[cc]C[chat: hi \\] there]TE[2]TR[aa][bb]LB[dd:A]
+;W[dd]BM[1]GB[1]V[3.5]AR[aa:cc]LN[ab:cd]SQ[ee]CR[ff]MA[gg]DD[hh]
+;C[comment only node]
+;B[]DO[]IT[])";
+
+        // When
+        let game = parse_sgf(input).expect("Expected sgf to parse");
+
+        // Then
+        assert_eq!(Some("Game start".to_string()), game.root_properties.comment);
+        assert_eq!(3, game.properties.len());
+
+        let first = &game.properties[0];
+        assert_eq!(Some("chat: hi ] there".to_string()), first.comment);
+        assert_eq!(
+            Some(MoveAnnotation::Tesuji(Emphasis::Strong)),
+            first.move_annotation
+        );
+        assert!(
+            first
+                .markup
+                .contains(&Markup::Triangle(FlexibleCoordinate { x: 1, y: 1 }))
+        );
+        assert!(first.markup.contains(&Markup::Label(
+            FlexibleCoordinate { x: 3, y: 3 },
+            "A".to_string()
+        )));
+
+        let second = &game.properties[1];
+        assert_eq!(
+            Some(MoveAnnotation::Bad(Emphasis::Normal)),
+            second.move_annotation
+        );
+        assert_eq!(
+            Some(PositionAnnotation::GoodForBlack(Emphasis::Normal)),
+            second.position
+        );
+        assert_eq!(Some(3.5), second.value);
+        assert_eq!(Some("comment only node".to_string()), second.comment);
+        assert_eq!(5, second.markup.len());
+        assert_eq!(Some(vec![FlexibleCoordinate { x: 7, y: 7 }]), second.dimmed);
+    }
+
+    #[test]
+    fn given_parsed_game_when_written_then_parsing_it_again_should_give_the_same_game() {
+        // Given
+        let input = "(;GM[1]SZ[13:9]PB[Black]PW[White]BR[3k]WR[1d]KM[0.5]HA[2]RE[W+R]
+TM[600]OT[25/300 Canadian]AB[cc][kg]C[Handicap]
+;W[dd]C[chat: gl \\] hf]LB[aa:x][bb:y]TR[cc]WL[590.5]
+;B[ee]BM[2]AR[aa:bb]
+;W[]DO[]
+;AE[cc]AW[aa]PL[W])";
+        let game = parse_sgf(input).expect("Expected sgf to parse");
+
+        // When
+        let written = write_sgf(&game);
+        let reparsed = parse_sgf(&written).expect("Expected written sgf to parse");
+
+        // Then
+        assert_eq!((13, 9), (reparsed.width, reparsed.height));
+        assert_eq!(game.black_player, reparsed.black_player);
+        assert_eq!(game.white_rank, reparsed.white_rank);
+        assert_eq!(game.komi, reparsed.komi);
+        assert_eq!(game.handicap, reparsed.handicap);
+        assert!(reparsed.time_control.is_some());
+        assert_eq!(game.time_control, reparsed.time_control);
+        assert_eq!(game.result, reparsed.result);
+        assert_eq!(game.root_properties, reparsed.root_properties);
+        assert_eq!(game.properties, reparsed.properties);
+        assert_eq!(game.moves.len(), reparsed.moves.len());
+        assert_eq!(written, write_sgf(&reparsed));
+    }
+}
@@ -1 +1,23 @@
-mod gsf;
+use thiserror::Error;
+
+pub mod binary;
+pub mod gib;
+pub mod gsf;
+pub mod ngf;
+pub mod ogs;
+pub mod ugf;
+
+/// Errors of the importers for game record formats other than SGF.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Could not read the game record")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The game record has no {0}")]
+    MissingField(&'static str),
+    #[error("Invalid {field} in the game record: {value}")]
+    InvalidValue { field: &'static str, value: String },
+    #[error("Line {line} of the game record is not a valid move: {text}")]
+    InvalidMove { line: usize, text: String },
+}
@@ -0,0 +1,269 @@
+use crate::{
+    go::{
+        coordinate::FlexibleCoordinate,
+        player::Player,
+        playermove::{Move, PlaceStoneMove},
+    },
+    parser::{
+        ImportError,
+        gsf::{ParsedGame, fixed_handicap_setup, properties::NodeProperties},
+    },
+};
+
+/// The header has a fixed number of lines, the moves follow it.
+const HEADER_LINES: usize = 12;
+
+/// Parses a WBaduk `.ngf` game record.
+///
+/// The header is one value per line: title, board size, white player, black player, server,
+/// handicap, an unused line, komi, date, time, result and the amount of moves.
+pub fn parse_ngf(ngf: &str) -> Result<ParsedGame, ImportError> {
+    let lines: Vec<&str> = ngf.lines().map(|x| x.trim()).collect();
+    if lines.len() < HEADER_LINES {
+        return Err(ImportError::MissingField("header"));
+    }
+
+    let size: u16 = lines[1].parse().map_err(|_| ImportError::InvalidValue {
+        field: "board size",
+        value: lines[1].to_string(),
+    })?;
+    let handicap: u8 = lines[5].parse().unwrap_or(0);
+
+    // WBaduk leaves the half point off the komi of even games.
+    let komi = lines[7].parse::<f64>().ok().map(|komi| {
+        if handicap == 0 && komi.fract() == 0.0 {
+            komi + 0.5
+        } else {
+            komi
+        }
+    });
+
+    let (white_player, white_rank) = split_name_and_rank(lines[2]);
+    let (black_player, black_rank) = split_name_and_rank(lines[3]);
+
+    let mut moves = vec![];
+    if handicap >= 2 {
+        moves.extend(fixed_handicap_setup((size, size), handicap));
+    }
+    for (index, line) in lines.iter().enumerate().skip(HEADER_LINES) {
+        if line.starts_with("PM") {
+            moves.push(parse_move(line, index, size)?);
+        }
+    }
+
+    Ok(ParsedGame {
+        width: size,
+        height: size,
+        black_player,
+        white_player,
+        black_rank: black_rank.and_then(|x| x.parse().ok()),
+        white_rank: white_rank.and_then(|x| x.parse().ok()),
+        komi,
+        handicap: Some(handicap).filter(|x| *x >= 2),
+        result: parse_result(lines[10]),
+        date: parse_date(lines[8]),
+        game_name: Some(lines[0].to_string()).filter(|x| !x.is_empty()),
+        properties: vec![NodeProperties::default(); moves.len()],
+        moves,
+        ..Default::default()
+    })
+}
+
+/// `PM<move number><color><x><y>`, coordinates are letters starting at `B`, anything outside of
+/// the board is a pass.
+fn parse_move(line: &str, index: usize, size: u16) -> Result<Move, ImportError> {
+    let bytes = line.as_bytes();
+    let player = match bytes.get(4) {
+        Some(b'B') => Player::Black,
+        Some(b'W') => Player::White,
+        _ => {
+            return Err(ImportError::InvalidMove {
+                line: index + 1,
+                text: line.to_string(),
+            });
+        }
+    };
+
+    let coordinate = |i: usize| {
+        bytes
+            .get(i)
+            .and_then(|x| x.checked_sub(b'B'))
+            .map(u16::from)
+            .filter(|x| *x < size)
+    };
+    match (coordinate(5), coordinate(6)) {
+        (Some(x), Some(y)) => Ok(Move::PlaceStone(PlaceStoneMove {
+            player,
+            coord: FlexibleCoordinate { x, y },
+        })),
+        _ => Ok(Move::Skip { player }),
+    }
+}
+
+/// Players are written as `name rank`, like `Lee 3D*`.
+fn split_name_and_rank(value: &str) -> (Option<String>, Option<&str>) {
+    match value.rsplit_once(' ') {
+        Some((name, rank)) => (Some(name.trim().to_string()), Some(rank)),
+        None if value.is_empty() => (None, None),
+        None => (Some(value.to_string()), None),
+    }
+}
+
+/// Results are sentences like `White wins by resignation!` or `Black wins by 3.5 points`.
+fn parse_result(value: &str) -> Option<String> {
+    let lower = value.to_lowercase();
+    let winner = if lower.contains("white win") {
+        "W"
+    } else if lower.contains("black win") {
+        "B"
+    } else {
+        return None;
+    };
+
+    let margin = if lower.contains("resign") {
+        "R".to_string()
+    } else if lower.contains("time") {
+        "T".to_string()
+    } else {
+        lower
+            .split_whitespace()
+            .find_map(|x| x.parse::<f64>().ok())
+            .map(|x| x.to_string())
+            .unwrap_or_default()
+    };
+
+    Some(format!("{winner}+{margin}"))
+}
+
+/// Dates start with `YYYYMMDD`.
+fn parse_date(value: &str) -> Option<String> {
+    let date = value
+        .get(..8)
+        .filter(|x| x.bytes().all(|b| b.is_ascii_digit()))?;
+    Some(format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::rank::Rank;
+
+    #[test]
+    fn given_ngf_record_when_parsed_then_it_should_map_the_header_and_moves() {
+        // Given
+        let input = "Friendly game
+19
+Kim 5D*
+Park 2K
+WBaduk
+0
+0
+6
+20131123 [17:55]
+5
+Black wins by 3.5 points
+4
+PMABBEDED
+PMACWQDQD
+PMADBPQPQ
+PMAEWAAAA
+";
+
+        // When
+        let game = parse_ngf(input).expect("Expected ngf to parse");
+
+        // Then
+        assert_eq!(Some("Friendly game".to_string()), game.game_name);
+        assert_eq!(Some("Kim".to_string()), game.white_player);
+        assert_eq!(
+            Some("5d*".parse::<Rank>().expect("Expected a rank")),
+            game.white_rank
+        );
+        assert_eq!(Some("Park".to_string()), game.black_player);
+        assert_eq!(Some(Rank::kyu(2)), game.black_rank);
+        assert_eq!(Some(6.5), game.komi);
+        assert_eq!(Some("B+3.5".to_string()), game.result);
+        assert_eq!(Some("2013-11-23".to_string()), game.date);
+        assert_eq!(4, game.moves.len());
+        assert!(matches!(
+            &game.moves[0],
+            Move::PlaceStone(PlaceStoneMove {
+                player: Player::Black,
+                coord: FlexibleCoordinate { x: 3, y: 2 }
+            })
+        ));
+        assert!(matches!(
+            game.moves[3],
+            Move::Skip {
+                player: Player::White
+            }
+        ));
+        assert!(game.replay().is_ok());
+    }
+
+    #[test]
+    fn given_ngf_handicap_game_when_parsed_then_it_should_keep_the_komi_and_result() {
+        // Given
+        let input = "
+9
+Lee 3급
+Choi 2단
+WBaduk
+2
+0
+0
+20200101 [10:00]
+0
+White wins by resignation!
+1
+PMABWFFFF
+";
+
+        // When
+        let game = parse_ngf(input).expect("Expected ngf to parse");
+
+        // Then
+        assert_eq!(None, game.game_name);
+        assert_eq!(Some(Rank::kyu(3)), game.white_rank);
+        assert_eq!(Some(Rank::dan(2)), game.black_rank);
+        assert_eq!(Some(2), game.handicap);
+        assert_eq!(Some(0.0), game.komi);
+        assert_eq!(Some("W+R".to_string()), game.result);
+        assert!(matches!(game.moves[0], Move::Setup(_)));
+        assert_eq!(2, game.moves.len());
+        assert!(game.replay().is_ok());
+        assert_eq!(Some("B+T".to_string()), parse_result("Black wins on time"));
+        assert_eq!(None, parse_result("Jigo"));
+    }
+
+    #[test]
+    fn given_malformed_ngf_when_parsed_then_it_should_say_what_is_wrong() {
+        // Given
+        let header = "Game\n{size}\nKim\nPark\nWBaduk\n0\n0\n6\n20131123\n5\n\n1\n";
+        let truncated = "Game\n19\nKim\nPark\n";
+        let bad_size = header.replace("{size}", "big");
+        let bad_move = header.replace("{size}", "19") + "PMABXDD\n";
+
+        // When
+        let truncated = parse_ngf(truncated);
+        let bad_size = parse_ngf(&bad_size);
+        let bad_move = parse_ngf(&bad_move);
+
+        // Then
+        assert!(matches!(
+            truncated,
+            Err(ImportError::MissingField("header"))
+        ));
+        assert!(matches!(
+            bad_size,
+            Err(ImportError::InvalidValue {
+                field: "board size",
+                ..
+            })
+        ));
+        assert!(matches!(
+            bad_move,
+            Err(ImportError::InvalidMove { line: 13, .. })
+        ));
+    }
+}
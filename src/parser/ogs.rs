@@ -0,0 +1,436 @@
+use std::{path::Path, time::Duration};
+
+use serde_json::Value;
+
+use crate::{
+    go::{
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        player::Player,
+        playermove::{Move, PlaceStoneMove, SetupMove},
+        rank::Rank,
+        time_control::TimeControl,
+    },
+    parser::{
+        ImportError,
+        gsf::{ParsedGame, properties::NodeProperties},
+    },
+};
+
+/// Reads an OGS game saved from the API, see [`parse_ogs_json`].
+pub fn read_ogs_json<P: AsRef<Path>>(path: P) -> Result<ParsedGame, ImportError> {
+    parse_ogs_json(&std::fs::read_to_string(path)?)
+}
+
+/// Parses an OGS game as returned by `/api/v1/games/{id}`, or just its `gamedata` object.
+///
+/// The time spent on every move is kept in [`NodeProperties::time_used`] and the stones that
+/// were removed as dead in [`ParsedGame::dead_stones`].
+pub fn parse_ogs_json(json: &str) -> Result<ParsedGame, ImportError> {
+    let root: Value = serde_json::from_str(json)?;
+    let data = root.get("gamedata").unwrap_or(&root);
+
+    let width = size(data, "width")?;
+    let height = size(data, "height")?;
+    let handicap = data["handicap"].as_u64().and_then(|x| u8::try_from(x).ok());
+
+    let players = [Player::Black, Player::White].map(|player| {
+        let key = player_key(player);
+        // The API response has the players next to the game data, with more details.
+        let info = match root["players"].get(key) {
+            Some(info) if info.is_object() => info,
+            _ => &data["players"][key],
+        };
+        let name = info["username"].as_str().map(|x| x.to_string());
+        let rank = info["ranking"]
+            .as_f64()
+            .or(info["rank"].as_f64())
+            .and_then(ogs_rank);
+        let id = info["id"]
+            .as_u64()
+            .or(data[format!("{key}_player_id")].as_u64());
+        (name, rank, id)
+    });
+    let [
+        (black_player, black_rank, black_id),
+        (white_player, white_rank, white_id),
+    ] = players;
+
+    let mut moves = vec![];
+    let mut properties = vec![];
+
+    let setup_move = SetupMove {
+        add_black: coordinate_list(&data["initial_state"]["black"], width, height)?,
+        add_white: coordinate_list(&data["initial_state"]["white"], width, height)?,
+        clear: CoordinateSet::new(vec![]),
+        player_to_move: match data["initial_player"].as_str() {
+            Some("white") => Some(Player::White),
+            _ => None,
+        },
+    };
+    if !setup_move.is_empty() {
+        moves.push(Move::Setup(setup_move));
+        properties.push(NodeProperties::default());
+    }
+
+    // Handicap stones that are not in the initial state are played as the first black moves.
+    let mut handicap_moves = match handicap {
+        Some(handicap) if handicap >= 2 && moves.is_empty() => handicap as usize,
+        _ => 0,
+    };
+    let mut player = match data["initial_player"].as_str() {
+        Some("white") if handicap_moves == 0 => Player::White,
+        _ => Player::Black,
+    };
+
+    for m in data["moves"].as_array().into_iter().flatten() {
+        let invalid = || ImportError::InvalidValue {
+            field: "move",
+            value: m.to_string(),
+        };
+        let x = m[0].as_i64().ok_or_else(invalid)?;
+        let y = m[1].as_i64().ok_or_else(invalid)?;
+
+        moves.push(if x < 0 || y < 0 {
+            Move::Skip { player }
+        } else {
+            let coord = FlexibleCoordinate {
+                x: u16::try_from(x).map_err(|_| invalid())?,
+                y: u16::try_from(y).map_err(|_| invalid())?,
+            };
+            if coord.x >= width || coord.y >= height {
+                return Err(invalid());
+            }
+            Move::PlaceStone(PlaceStoneMove { player, coord })
+        });
+        properties.push(NodeProperties {
+            time_used: m[2]
+                .as_f64()
+                .and_then(|ms| Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).ok()),
+            ..Default::default()
+        });
+
+        if handicap_moves > 0 {
+            handicap_moves -= 1;
+            if handicap_moves == 0 {
+                player = Player::White;
+            }
+        } else {
+            player = !player;
+        }
+    }
+
+    let winner = match data["winner"].as_u64().or(root["winner"].as_u64()) {
+        Some(id) if Some(id) == black_id => Some("B"),
+        Some(id) if Some(id) == white_id => Some("W"),
+        _ => None,
+    };
+    let outcome = data["outcome"].as_str().or(root["outcome"].as_str());
+
+    Ok(ParsedGame {
+        width,
+        height,
+        black_player,
+        white_player,
+        black_rank,
+        white_rank,
+        komi: data["komi"].as_f64(),
+        handicap: handicap.filter(|x| *x >= 2),
+        time_control: time_control(&data["time_control"]),
+        result: winner.map(|winner| format!("{winner}+{}", result_margin(outcome))),
+        rules: data["rules"].as_str().map(|x| x.to_string()),
+        date: data["start_time"]
+            .as_i64()
+            .or(data["end_time"].as_i64())
+            .map(date_from_timestamp),
+        game_name: data["game_name"].as_str().map(|x| x.to_string()),
+        moves,
+        properties,
+        dead_stones: coordinate_list(&data["removed"], width, height)?
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    })
+}
+
+fn size(data: &Value, field: &'static str) -> Result<u16, ImportError> {
+    let value = &data[field];
+    value
+        .as_u64()
+        .and_then(|x| u16::try_from(x).ok())
+        .filter(|x| (1..=52).contains(x))
+        .ok_or_else(|| match value {
+            Value::Null => ImportError::MissingField(field),
+            _ => ImportError::InvalidValue {
+                field,
+                value: value.to_string(),
+            },
+        })
+}
+
+fn player_key(player: Player) -> &'static str {
+    match player {
+        Player::Black => "black",
+        Player::White => "white",
+    }
+}
+
+/// OGS ranks are numbers where 30 is 1 dan and 29 is 1 kyu.
+fn ogs_rank(value: f64) -> Option<Rank> {
+    let rank = value.floor() as i64;
+    match rank {
+        0..30 => Some(Rank::kyu((30 - rank) as u8)),
+        30.. => Some(Rank::dan((rank - 29).min(9) as u8)),
+        _ => None,
+    }
+}
+
+/// Points are written as a string of SGF coordinates, like `ddpp`.
+fn coordinate_list(value: &Value, width: u16, height: u16) -> Result<CoordinateSet, ImportError> {
+    let text = value.as_str().unwrap_or_default();
+    let invalid = || ImportError::InvalidValue {
+        field: "coordinate list",
+        value: text.to_string(),
+    };
+
+    let letters: Vec<u16> = text
+        .bytes()
+        .map(|x| match x {
+            b'a'..=b'z' => Ok((x - b'a') as u16),
+            b'A'..=b'Z' => Ok((x - b'A') as u16 + 26),
+            _ => Err(invalid()),
+        })
+        .collect::<Result<_, _>>()?;
+    if !letters.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    let coords: Vec<FlexibleCoordinate> = letters
+        .chunks(2)
+        .map(|x| FlexibleCoordinate { x: x[0], y: x[1] })
+        .collect();
+    if coords.iter().any(|c| c.x >= width || c.y >= height) {
+        return Err(invalid());
+    }
+    Ok(CoordinateSet::new(coords))
+}
+
+fn time_control(value: &Value) -> Option<TimeControl> {
+    let seconds = |field: &str| {
+        value[field]
+            .as_f64()
+            .and_then(|x| Duration::try_from_secs_f64(x).ok())
+    };
+    let count = |field: &str| value[field].as_u64().and_then(|x| u32::try_from(x).ok());
+
+    match value["system"]
+        .as_str()
+        .or(value["time_control"].as_str())?
+    {
+        "absolute" => Some(TimeControl::Absolute {
+            main_time: seconds("total_time")?,
+        }),
+        "byoyomi" => Some(TimeControl::ByoYomi {
+            main_time: seconds("main_time")?,
+            periods: count("periods")?,
+            period_time: seconds("period_time")?,
+        }),
+        "canadian" => Some(TimeControl::Canadian {
+            main_time: seconds("main_time")?,
+            stones: count("stones_per_period")?,
+            period_time: seconds("period_time")?,
+        }),
+        "fischer" => Some(TimeControl::Fischer {
+            main_time: seconds("initial_time")?,
+            increment: seconds("time_increment")?,
+            max_time: seconds("max_time"),
+        }),
+        "simple" => Some(TimeControl::Simple {
+            per_move: seconds("per_move")?,
+        }),
+        _ => None,
+    }
+}
+
+/// The part of the result after the winner, like `R` or `12.5`.
+fn result_margin(outcome: Option<&str>) -> String {
+    let Some(outcome) = outcome else {
+        return String::new();
+    };
+    match outcome.to_lowercase().as_str() {
+        "resignation" => "R".to_string(),
+        "timeout" => "T".to_string(),
+        "disqualification" | "abandonment" => "F".to_string(),
+        outcome => outcome
+            .split_whitespace()
+            .next()
+            .and_then(|x| x.parse::<f64>().ok())
+            .map(|x| x.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Formats a unix timestamp in seconds as an SGF date.
+fn date_from_timestamp(timestamp: i64) -> String {
+    // Days to civil date, from Howard Hinnant's date algorithms.
+    let days = timestamp.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::go::time_control::ClockState;
+
+    const GAME: &str = r#"{
+        "id": 1234,
+        "players": {
+            "black": {"id": 10, "username": "alice", "ranking": 27.4},
+            "white": {"id": 20, "username": "bob", "ranking": 31.2}
+        },
+        "gamedata": {
+            "width": 9,
+            "height": 9,
+            "komi": 0.5,
+            "handicap": 2,
+            "rules": "japanese",
+            "initial_player": "black",
+            "initial_state": {"black": "", "white": ""},
+            "start_time": 1600000000,
+            "game_name": "Friendly Match",
+            "time_control": {
+                "system": "byoyomi",
+                "main_time": 60,
+                "periods": 3,
+                "period_time": 10
+            },
+            "moves": [[2, 6, 1500], [6, 2, 800], [4, 4, 3000], [-1, -1, 500]],
+            "removed": "eeac",
+            "winner": 20,
+            "outcome": "Resignation"
+        }
+    }"#;
+
+    #[test]
+    fn given_ogs_api_response_when_parsed_then_it_should_map_game_data_and_timing() {
+        // When
+        let game = parse_ogs_json(GAME).expect("Expected json to parse");
+
+        // Then
+        assert_eq!((9, 9), (game.width, game.height));
+        assert_eq!(Some("alice".to_string()), game.black_player);
+        assert_eq!(Some(Rank::kyu(3)), game.black_rank);
+        assert_eq!(Some(Rank::dan(2)), game.white_rank);
+        assert_eq!(Some(0.5), game.komi);
+        assert_eq!(Some(2), game.handicap);
+        assert_eq!(Some("japanese".to_string()), game.rules);
+        assert_eq!(Some("W+R".to_string()), game.result);
+        assert_eq!(Some("2020-09-13".to_string()), game.date);
+        assert_eq!(Some("Friendly Match".to_string()), game.game_name);
+
+        let players: Vec<Player> = game
+            .moves
+            .iter()
+            .map(|m| match m {
+                Move::PlaceStone(PlaceStoneMove { player, .. }) | Move::Skip { player } => *player,
+                Move::Setup(_) => panic!("Expected no setup"),
+            })
+            .collect();
+        assert_eq!(
+            vec![Player::Black, Player::Black, Player::White, Player::Black],
+            players
+        );
+        assert_eq!(
+            vec![
+                FlexibleCoordinate { x: 0, y: 2 },
+                FlexibleCoordinate { x: 4, y: 4 }
+            ],
+            {
+                let mut dead = game.dead_stones.clone();
+                dead.sort_by_key(|c| (c.y, c.x));
+                dead
+            }
+        );
+
+        let clocks = game.replay_clocks().expect("Expected clocks");
+        assert_eq!(Some(Duration::from_millis(3000)), clocks[2].time_used);
+        assert_eq!(
+            ClockState {
+                time_left: Duration::from_secs(60),
+                overtime_left: None
+            },
+            clocks[3].white
+        );
+        assert!(game.replay().is_ok());
+    }
+
+    #[test]
+    fn given_gamedata_with_initial_state_when_parsed_then_it_should_start_with_a_setup_move() {
+        // Given
+        let input = r#"{
+            "width": 13, "height": 13,
+            "initial_player": "white",
+            "initial_state": {"black": "ddjj", "white": "jd"},
+            "moves": [[3, 9, 100]],
+            "players": {"black": {"id": 1, "username": "a"}, "white": {"id": 2, "username": "b"}}
+        }"#;
+
+        // When
+        let game = parse_ogs_json(input).expect("Expected json to parse");
+
+        // Then
+        let Move::Setup(setup_move) = &game.moves[0] else {
+            panic!("Expected a setup move");
+        };
+        assert_eq!(2, setup_move.add_black.len());
+        assert_eq!(Some(Player::White), setup_move.player_to_move);
+        assert!(matches!(
+            game.moves[1],
+            Move::PlaceStone(PlaceStoneMove {
+                player: Player::White,
+                ..
+            })
+        ));
+        assert_eq!(game.moves.len(), game.properties.len());
+        assert!(matches!(
+            parse_ogs_json(r#"{"height": 9}"#),
+            Err(ImportError::MissingField("width"))
+        ));
+    }
+
+    #[test]
+    fn given_gamedata_with_invalid_times_when_parsed_then_the_times_should_be_left_out() {
+        // Given
+        let input = r#"{
+            "width": 9, "height": 9,
+            "time_control": {"system": "simple", "per_move": -5},
+            "moves": [[2, 2, 1e300], [3, 3, 100]],
+            "players": {"black": {"id": 1, "username": "a"}, "white": {"id": 2, "username": "b"}}
+        }"#;
+
+        // When
+        let game = parse_ogs_json(input).expect("Expected json to parse");
+
+        // Then
+        assert_eq!(None, game.time_control);
+        assert_eq!(None, game.properties[0].time_used);
+        assert_eq!(
+            Some(Duration::from_millis(100)),
+            game.properties[1].time_used
+        );
+    }
+}
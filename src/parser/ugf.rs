@@ -0,0 +1,310 @@
+use crate::{
+    go::{
+        coordinate::FlexibleCoordinate,
+        coordinate_set::CoordinateSet,
+        player::Player,
+        playermove::{Move, PlaceStoneMove, SetupMove},
+    },
+    parser::{
+        ImportError,
+        gsf::{ParsedGame, fixed_handicap_setup, properties::NodeProperties},
+    },
+};
+
+/// Parses a PandaNet/Hirame `.ugf` game record.
+///
+/// The `[Header]` section holds `Key=Value` lines, the `[Data]` section one move per line as
+/// `<coordinate>,<color><move number>,...`. Stones with move number 0 are handicap stones.
+pub fn parse_ugf(ugf: &str) -> Result<ParsedGame, ImportError> {
+    let mut game = ParsedGame {
+        width: 19,
+        height: 19,
+        ..Default::default()
+    };
+    let mut section = "";
+    let mut data = vec![];
+
+    for (index, line) in ugf.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            section = line;
+            continue;
+        }
+
+        match section {
+            "[Header]" => {
+                if let Some((key, value)) = line.split_once('=') {
+                    parse_header(&mut game, key.trim(), value.trim())?;
+                }
+            }
+            "[Data]" if !line.is_empty() => data.push((index, line)),
+            _ => {}
+        }
+    }
+
+    let size = game.width;
+    let mut handicap_stones = vec![];
+    let mut moves = vec![];
+    for (index, line) in data {
+        let invalid = || ImportError::InvalidMove {
+            line: index + 1,
+            text: line.to_string(),
+        };
+
+        let mut fields = line.split(',');
+        let coordinate = fields.next().ok_or_else(invalid)?;
+        let color = fields.next().ok_or_else(invalid)?;
+        let player = match color.get(..1) {
+            Some("B") => Player::Black,
+            Some("W") => Player::White,
+            _ => return Err(invalid()),
+        };
+        let number: u32 = color[1..].parse().map_err(|_| invalid())?;
+
+        let coord = parse_coordinate(coordinate, size);
+        match (number, coord) {
+            (0, Some(coord)) => handicap_stones.push(coord),
+            (0, None) => return Err(invalid()),
+            (_, Some(coord)) => moves.push(Move::PlaceStone(PlaceStoneMove { player, coord })),
+            (_, None) => moves.push(Move::Skip { player }),
+        }
+    }
+
+    if !handicap_stones.is_empty() {
+        moves.insert(
+            0,
+            Move::Setup(SetupMove {
+                add_black: CoordinateSet::new(handicap_stones),
+                add_white: CoordinateSet::new(vec![]),
+                clear: CoordinateSet::new(vec![]),
+                player_to_move: Some(Player::White),
+            }),
+        );
+    } else if let Some(handicap) = game.handicap {
+        moves.splice(0..0, fixed_handicap_setup((size, size), handicap));
+    }
+
+    game.properties = vec![NodeProperties::default(); moves.len()];
+    game.moves = moves;
+    Ok(game)
+}
+
+fn parse_header(game: &mut ParsedGame, key: &str, value: &str) -> Result<(), ImportError> {
+    let text = || Some(value.to_string()).filter(|x| !x.is_empty());
+
+    match key {
+        "Title" => game.game_name = text(),
+        "Place" => game.place = text(),
+        "Rule" => game.rules = text(),
+        // `2004/08/13,10:15`
+        "Date" => {
+            game.date = value
+                .split(',')
+                .next()
+                .map(|x| x.replace('/', "-"))
+                .filter(|x| !x.is_empty())
+        }
+        "Size" => {
+            let size = value.parse().map_err(|_| ImportError::InvalidValue {
+                field: "board size",
+                value: value.to_string(),
+            })?;
+            game.width = size;
+            game.height = size;
+        }
+        // `<name>,<rank>,...`
+        "PlayerB" => {
+            let mut fields = value.split(',').map(|x| x.trim());
+            game.black_player = fields.next().map(|x| x.to_string());
+            game.black_rank = fields.next().and_then(|x| x.parse().ok());
+        }
+        "PlayerW" => {
+            let mut fields = value.split(',').map(|x| x.trim());
+            game.white_player = fields.next().map(|x| x.to_string());
+            game.white_rank = fields.next().and_then(|x| x.parse().ok());
+        }
+        // `<handicap>,<komi>`
+        "Hdcp" => {
+            let mut fields = value.split(',').map(|x| x.trim());
+            game.handicap = fields
+                .next()
+                .and_then(|x| x.parse().ok())
+                .filter(|x| *x >= 2);
+            game.komi = fields.next().and_then(|x| x.parse().ok());
+        }
+        // `<winner>,<margin>`, the margin is a score, `R` for resignation or `T` for time.
+        "Winner" => {
+            let (winner, margin) = value.split_once(',').unwrap_or((value, ""));
+            let margin = match margin.trim() {
+                "C" | "R" => "R".to_string(),
+                "T" => "T".to_string(),
+                margin => margin
+                    .parse::<f64>()
+                    .map(|x| x.to_string())
+                    .unwrap_or_default(),
+            };
+            game.result = match winner.trim() {
+                "B" => Some(format!("B+{margin}")),
+                "W" => Some(format!("W+{margin}")),
+                _ => None,
+            };
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Columns are letters from `A` on the left, rows are letters from `A` at the bottom. Anything
+/// outside of the board is a pass.
+fn parse_coordinate(value: &str, size: u16) -> Option<FlexibleCoordinate> {
+    let bytes = value.as_bytes();
+    let letter = |i: usize| {
+        bytes
+            .get(i)
+            .and_then(|x| x.checked_sub(b'A'))
+            .map(u16::from)
+            .filter(|x| *x < size)
+    };
+    Some(FlexibleCoordinate {
+        x: letter(0)?,
+        y: size - 1 - letter(1)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{go::rank::Rank, parser::gsf::writer::write_sgf};
+
+    #[test]
+    fn given_ugf_record_when_parsed_then_it_should_map_the_header_and_moves() {
+        // Given
+        let input = "[Header]
+Lang=JPN
+Title=Honinbo League
+Place=Tokyo
+Date=2004/08/13,10:15
+Rule=JPN
+Size=19
+Hdcp=2,0.5
+Winner=W,C
+PlayerB=Cho,7d,,
+PlayerW=Yoda,9d,,
+[Data]
+DP,B0,0,0
+PD,B0,0,0
+QQ,W1,1,0
+DD,B2,2,0
+YA,W3,3,0
+[Figure]
+";
+
+        // When
+        let game = parse_ugf(input).expect("Expected ugf to parse");
+
+        // Then
+        assert_eq!(Some("Honinbo League".to_string()), game.game_name);
+        assert_eq!(Some("2004-08-13".to_string()), game.date);
+        assert_eq!(Some("Cho".to_string()), game.black_player);
+        assert_eq!(Some(Rank::dan(9)), game.white_rank);
+        assert_eq!(Some(2), game.handicap);
+        assert_eq!(Some(0.5), game.komi);
+        assert_eq!(Some("W+R".to_string()), game.result);
+        assert_eq!(4, game.moves.len());
+        let Move::Setup(setup_move) = &game.moves[0] else {
+            panic!("Expected handicap stones first");
+        };
+        assert!(
+            setup_move
+                .add_black
+                .contains(&FlexibleCoordinate { x: 3, y: 3 })
+        );
+        assert!(
+            setup_move
+                .add_black
+                .contains(&FlexibleCoordinate { x: 15, y: 15 })
+        );
+        assert!(matches!(
+            &game.moves[1],
+            Move::PlaceStone(PlaceStoneMove {
+                player: Player::White,
+                coord: FlexibleCoordinate { x: 16, y: 2 }
+            })
+        ));
+        assert!(matches!(
+            game.moves[3],
+            Move::Skip {
+                player: Player::White
+            }
+        ));
+        assert!(game.replay().is_ok());
+        assert!(write_sgf(&game).contains("AB[dd][pp]"));
+    }
+
+    #[test]
+    fn given_ugf_handicap_without_stones_when_parsed_then_it_should_use_fixed_placement() {
+        // Given
+        let input = "[Header]
+Size=9
+Hdcp=3,0
+Winner=B,12.5
+PlayerB=Kang,4급,,
+PlayerW=Seo,3단,,
+[Data]
+EE,W1,1,0
+";
+
+        // When
+        let game = parse_ugf(input).expect("Expected ugf to parse");
+
+        // Then
+        assert_eq!((9, 9), (game.width, game.height));
+        assert_eq!(Some(Rank::kyu(4)), game.black_rank);
+        assert_eq!(Some(Rank::dan(3)), game.white_rank);
+        assert_eq!(Some(3), game.handicap);
+        assert_eq!(Some(0.0), game.komi);
+        assert_eq!(Some("B+12.5".to_string()), game.result);
+        let Move::Setup(setup_move) = &game.moves[0] else {
+            panic!("Expected handicap stones first");
+        };
+        assert_eq!(3, setup_move.add_black.len());
+        assert!(game.replay().is_ok());
+    }
+
+    #[test]
+    fn given_malformed_ugf_when_parsed_then_it_should_say_what_is_wrong() {
+        // Given
+        let bad_size = "[Header]\nSize=nineteen\n";
+        let truncated = "[Header]\nSize=19\n[Data]\nQQ\n";
+        let bad_color = "[Header]\nSize=19\n[Data]\nQQ,X1,1,0\n";
+        let passed_handicap = "[Header]\nSize=19\n[Data]\nZZ,B0,0,0\n";
+
+        // When
+        let bad_size = parse_ugf(bad_size);
+        let truncated = parse_ugf(truncated);
+        let bad_color = parse_ugf(bad_color);
+        let passed_handicap = parse_ugf(passed_handicap);
+
+        // Then
+        assert!(matches!(
+            bad_size,
+            Err(ImportError::InvalidValue {
+                field: "board size",
+                ..
+            })
+        ));
+        assert!(matches!(
+            truncated,
+            Err(ImportError::InvalidMove { line: 4, .. })
+        ));
+        assert!(matches!(
+            bad_color,
+            Err(ImportError::InvalidMove { line: 4, .. })
+        ));
+        assert!(matches!(
+            passed_handicap,
+            Err(ImportError::InvalidMove { line: 4, .. })
+        ));
+    }
+}